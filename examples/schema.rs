@@ -1,10 +1,9 @@
 use std::env::current_dir;
 use std::fs::create_dir_all;
 
-use cosmwasm_schema::{export_schema, remove_schemas, schema_for};
+use cosmwasm_schema::{export_schema, remove_schemas};
 
-use election::msg::{VoteResponse, HandleMsg, InitMsg, QueryMsg};
-use election::state::State;
+use election::schema::schemas;
 
 fn main() {
     let mut out_dir = current_dir().unwrap();
@@ -12,9 +11,7 @@ fn main() {
     create_dir_all(&out_dir).unwrap();
     remove_schemas(&out_dir).unwrap();
 
-    export_schema(&schema_for!(InitMsg), &out_dir);
-    export_schema(&schema_for!(HandleMsg), &out_dir);
-    export_schema(&schema_for!(QueryMsg), &out_dir);
-    export_schema(&schema_for!(State), &out_dir);
-    export_schema(&schema_for!(VoteResponse), &out_dir);
+    for schema in schemas() {
+        export_schema(&schema, &out_dir);
+    }
 }
@@ -0,0 +1,62 @@
+//! A bloom filter over canonical addresses. Bit `i` of the filter is
+//! `bits[i / 8] >> (i % 8) & 1` and an item is a member if the bits
+//! `(h1 + j * h2) mod m` are set for every `j` in `0..hashes`, where `m` is
+//! the number of bits, `h1` is the 64-bit FNV-1a hash of the item and `h2` is
+//! the FNV-1a hash of the item followed by a `0xff` byte, with the lowest bit set.
+//!
+//! With `n` members the false positive rate is about `(1 - e^(-hashes * n / m))^hashes`,
+//! smallest for `hashes = m / n * ln 2`.
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// contains reports whether `item` may be in the set. False positives are
+/// possible, false negatives are not.
+pub fn contains(bits: &[u8], hashes: u32, item: &[u8]) -> bool {
+    positions(bits, hashes, item).all(|i| bits[i / 8] >> (i % 8) & 1 == 1)
+}
+
+/// insert adds `item` to the filter
+pub fn insert(bits: &mut [u8], hashes: u32, item: &[u8]) {
+    let positions: Vec<usize> = positions(bits, hashes, item).collect();
+    for i in positions {
+        bits[i / 8] |= 1 << (i % 8);
+    }
+}
+
+fn positions(bits: &[u8], hashes: u32, item: &[u8]) -> impl Iterator<Item = usize> {
+    let m = bits.len() as u64 * 8;
+    let h1 = fnv1a(FNV_OFFSET, item);
+    let h2 = fnv1a(h1, &[0xff]) | 1;
+    (0..u64::from(hashes)).map(move |j| (h1.wrapping_add(j.wrapping_mul(h2)) % m) as usize)
+}
+
+fn fnv1a(mut hash: u64, data: &[u8]) -> u64 {
+    for byte in data {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fnv1a_matches_reference() {
+        assert_eq!(0xcbf2_9ce4_8422_2325, fnv1a(FNV_OFFSET, b""));
+        assert_eq!(0xaf63_dc4c_8601_ec8c, fnv1a(FNV_OFFSET, b"a"));
+    }
+
+    #[test]
+    fn inserted_items_are_members() {
+        let mut bits = vec![0u8; 64];
+        for item in &[b"alice".as_ref(), b"bob", b"carol"] {
+            insert(&mut bits, 4, item);
+        }
+        assert!(contains(&bits, 4, b"alice"));
+        assert!(contains(&bits, 4, b"carol"));
+        assert!(!contains(&bits, 4, b"mallory"));
+    }
+}
@@ -1,30 +1,500 @@
+use crate::bloom;
 use crate::error::ContractError;
-use crate::msg::{HandleMsg, InitMsg, QueryMsg, Vote, VoteResponse};
-use crate::state::{config, config_read, State, VoteInfo};
+use crate::ledger::add_coin;
+use crate::merkle;
+use crate::msg::{
+    Allocation, Answer, AuditLogResponse, BalanceResponse, Ballot, BallotEntry, BallotResponse,
+    CanVoteResponse, CandidateCountResponse, CandidateLabels, CandidateProposalsResponse,
+    CandidateRules, CandidateVote, CandidateVotesResponse, ChallengesResponse, CompletionHookMsg,
+    ConfigResponse, ContributionsResponse, Cw20QueryMsg, Cw4QueryMsg, Cw721HandleMsg,
+    Cw721QueryMsg, DisclosuresResponse, DisputeConfig, ElectionStatus, ElectionSummary,
+    ElectionsResponse, EligibilityFilter, EligibilityParams, ExtensionsResponse, FactoryLink,
+    HandleMsg, HistogramBucket, HistoryResponse, HistoryRule, InitMsg, LeaderboardResponse,
+    LedgerResponse, LegacyVoteResponse, Limits, LockedBalanceResponse, MemberResponse, MerkleProof,
+    Metadata, MintMsg, MultiResponse, OptionTotal, OracleFallback, ParticipationResponse,
+    PartyList, Phase, PhaseResponse, ProxyResponse, PublishedWinner, QueryMsg, Question,
+    QuestionMethod, QuestionOutcome, Referendum, ReferendumChoice, ReferendumResponse,
+    ReferendumStatus, RegistryConfig, RegistryMsg, ResultResponse, ResultStatus, RewardSplit, Role,
+    RoleGrant, Share, SimulatedTallyResponse, SnapshotResponse, SpawnedElectionsResponse,
+    StakeWeight, StakedBalanceAtHeightResponse, StakingQueryMsg, StaleElection,
+    StaleElectionsResponse, StatsResponse, SudoMsg, TallyRoundsResponse, TemplateResponse,
+    TiePolicy, TokensResponse, Vote, VoteCheck, VoteResponse, VoteSort, VoterRollResponse,
+    VoterSetDiffResponse, VotersResponse, VotingPowerQueryMsg, VotingPowerResponse, VotingSystem,
+    WeightDecay, WithdrawalPolicy,
+};
+use crate::sha256::sha256;
+use crate::snapshot::{
+    crc32, encode_ballot, encode_ballots, encode_tally, SCHEMA_ID, SCHEMA_VERSION,
+};
+use crate::state::{
+    archives, archives_read, audit_log, audit_log_read, ballot_fees, ballot_fees_read,
+    ballot_payers, ballot_payers_read, campaign_totals, campaign_totals_read, candidate_counts,
+    candidate_counts_read, candidate_votes, candidate_votes_read, challenges, challenges_read,
+    cohort_members, cohort_members_read, config, config_read, contributions, contributions_read,
+    disclosures, disclosures_read, labels, labels_read, last_action, last_action_read, leaderboard,
+    leaderboard_key, leaderboard_read, ledger, ledger_read, locks, locks_read, nft_votes,
+    nft_votes_read, participants, participants_read, processed_keys, processed_keys_read,
+    proposals, proposals_read, proxies, proxies_read, question_ballots, question_tally,
+    question_tally_read, referendum_ballots, referendum_ballots_read, referendum_outcome,
+    referendum_outcome_read, referendum_tally, referendum_tally_read, registered, registered_read,
+    relayers, relayers_read, result, result_read, reward_claims, reward_claims_read, roll_snapshot,
+    roll_snapshot_read, spawned, spawned_read, stats, stats_read, tally_proof, tally_proof_read,
+    tally_rounds, tally_rounds_read, templates, templates_read, voter_weights, voter_weights_read,
+    ArchivedResult, AuditEntry, BallotPayment, BlockBallots, CandidateCount, CandidateProposal,
+    CandidateTotal, Challenge, Cohort, DeadlineExtension, Disclosure, ElectionResult, FundBucket,
+    Ledger, LocalizedLabel, ProxyGrant, QuestionTally, ReferendumBallot, ReferendumOutcome,
+    ReferendumTally, Registration, RollSnapshot, Runoff, SpawnedElection, State, Stats,
+    SubAllocation, TallyProof, TallyRound, VoteInfo, VoterWeight, Winner,
+};
+use crate::tally::{borda, condorcet, highest_averages, stv, RankedBallot};
 use cosmwasm_std::{
-    to_binary, Api, Binary, Env, Extern, HandleResponse, HumanAddr, InitResponse, MessageInfo,
-    Querier, StdResult, Storage,
+    attr, coin, to_binary, to_vec, Api, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, Decimal,
+    Env, Extern, HandleResponse, HumanAddr, InitResponse, MessageInfo, Order, Querier, StdError,
+    StdResult, Storage, Uint128, WasmMsg,
 };
 
-use std::collections::HashMap;
+use cosmwasm_storage::Bucket;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+
+const TOKEN_PAGE_LIMIT: u32 = 30;
+const MAX_LABELS: usize = 10;
+const MAX_LOCALE_LENGTH: usize = 16;
+const MAX_LABEL_LENGTH: usize = 64;
+const MAX_BATCH_SIZE: usize = 100;
+const MAX_IDEMPOTENCY_KEY_LENGTH: usize = 64;
+const DEFAULT_SNAPSHOT_LIMIT: u32 = 30;
+const MAX_SNAPSHOT_LIMIT: u32 = 100;
+const HISTOGRAM_BUCKETS: u64 = 10;
+const MAX_MULTI_QUERIES: usize = 10;
+const MAX_BLOOM_HASHES: u32 = 32;
+const MAX_DISCLOSURE_LENGTH: usize = 256;
+const MAX_POLICY_LENGTH: usize = 64;
+const MAX_TEMPLATE_NAME_LENGTH: usize = 64;
+const MAX_TITLE_LENGTH: usize = 128;
+const MAX_DESCRIPTION_LENGTH: usize = 1024;
+const MAX_URL_LENGTH: usize = 256;
+const MAX_TAGS: usize = 8;
+const MAX_TAG_LENGTH: usize = 32;
 
-// Note, you can use StdResult in some functions where you do not
-// make use of the custom errors
 pub fn init<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
-    _env: Env,
-    _info: MessageInfo,
+    env: Env,
+    info: MessageInfo,
     msg: InitMsg,
-) -> StdResult<InitResponse> {
-    let state = State {
+) -> Result<InitResponse, ContractError> {
+    if msg.seats == 0 {
+        return Err(ContractError::InvalidSeats {});
+    }
+    if msg.end <= msg.start {
+        return Err(ContractError::InvalidPeriod {
+            start: msg.start,
+            end: msg.end,
+        });
+    }
+    if let Some(denom) = &msg.denom {
+        let configured = msg
+            .participation_reward
+            .iter()
+            .flat_map(|reward| &reward.pool)
+            .chain(msg.finalization_bounty.iter().flat_map(|b| &b.amount))
+            .chain(msg.dispute.as_ref().map(|d| &d.bond))
+            .chain(msg.registration.as_ref().and_then(|r| r.bond.as_ref()));
+        check_denom(Some(denom), info.sent_funds.iter().chain(configured))?;
+        if let Some(StakeWeight::Locked { denom: locked }) = &msg.stake_weight {
+            if locked != denom {
+                return Err(ContractError::WrongDenom {
+                    expected: denom.clone(),
+                    denom: locked.clone(),
+                });
+            }
+        }
+    }
+    let candidates = match &msg.referendum {
+        Some(referendum) => {
+            validate_referendum(referendum)?;
+            if !msg.candidates.is_empty() {
+                return Err(ContractError::InvalidReferendum {});
+            }
+            vec![]
+        }
+        // nominated candidates are added later
+        None if msg.nomination.is_some() && msg.candidates.is_empty() => vec![],
+        None => validate_candidates(
+            &deps.api,
+            msg.candidate_rules.as_ref(),
+            &info.sender,
+            &msg.candidates,
+        )?,
+    };
+    if let Some(nomination) = &msg.nomination {
+        if nomination.approvals == 0 || nomination.approvals as usize > nomination.committee.len() {
+            return Err(ContractError::InvalidNomination {});
+        }
+    }
+    if let Some(max) = msg.limits.max_candidates {
+        if candidates.len() > max as usize {
+            return Err(ContractError::TooManyCandidates { max });
+        }
+    }
+    if let Some(filter) = &msg.eligibility {
+        validate_eligibility(filter)?;
+    }
+    if let Some(roll) = &msg.merkle_roll {
+        if roll.root.len() != 32 {
+            return Err(ContractError::InvalidMerkleRoot {});
+        }
+    }
+    if let Some(council) = &msg.veto_council {
+        if council.threshold == 0 || council.threshold as usize > council.members.len() {
+            return Err(ContractError::InvalidVetoCouncil {});
+        }
+    }
+    if let Some(CandidateRules {
+        max_relay_depth: Some(0),
+        ..
+    }) = &msg.candidate_rules
+    {
+        return Err(ContractError::InvalidCandidateRules {});
+    }
+    match &msg.weight_decay {
+        Some(WeightDecay::Linear { floor }) if *floor > Decimal::one() => {
+            return Err(ContractError::InvalidWeightDecay {});
+        }
+        Some(WeightDecay::Exponential { half_life: 0 }) => {
+            return Err(ContractError::InvalidWeightDecay {});
+        }
+        _ => {}
+    }
+    if let Some(majority) = &msg.majority_runoff {
+        if msg.seats != 1
+            || msg.voting_system != VotingSystem::Plurality
+            || majority.threshold.is_zero()
+            || majority.threshold > Decimal::one()
+        {
+            return Err(ContractError::InvalidMajorityRunoff {});
+        }
+    }
+    if let Some(list) = &msg.party_list {
+        validate_party_list(&msg, list)?;
+    }
+    for (i, question) in msg.questions.iter().enumerate() {
+        let duplicate_option = question
+            .options
+            .iter()
+            .enumerate()
+            .any(|(j, option)| question.options[..j].contains(option));
+        if question.name.is_empty()
+            || question.options.len() < 2
+            || duplicate_option
+            || msg.questions[..i].iter().any(|q| q.name == question.name)
+        {
+            return Err(ContractError::InvalidQuestions {});
+        }
+    }
+    if let Some(rule) = &msg.anti_snipe {
+        if rule.threshold.is_zero()
+            || rule.threshold > Decimal::one()
+            || rule.window == 0
+            || rule.extension == 0
+            || rule.max_extension < rule.extension
+        {
+            return Err(ContractError::InvalidAntiSnipe {});
+        }
+    }
+    if let Some(fee) = &msg.ballot_fee {
+        if fee.amount.is_zero() {
+            return Err(ContractError::InvalidBallotFee {});
+        }
+        check_denom(msg.denom.as_deref(), std::iter::once(fee))?;
+    }
+    if let Some(metadata) = &msg.metadata {
+        validate_metadata(metadata)?;
+    }
+    if let Some(registry) = &msg.registry {
+        // every seat needs a key of its own
+        if registry.key_template.is_empty()
+            || (msg.seats > 1 && !registry.key_template.contains("{seat}"))
+        {
+            return Err(ContractError::InvalidKeyTemplate {});
+        }
+    }
+    let mut state = State {
+        owner: info.sender,
+        pending_owner: None,
         start: msg.start,
         end: msg.end,
-        candidates: msg.candidates,
+        seats: msg.seats,
+        voting_system: msg.voting_system,
+        tie_policy: msg.tie_policy,
+        withdrawal_policy: msg.withdrawal_policy,
+        withdrawn: vec![],
+        runoff: None,
+        tie_resolution: None,
+        stake_weight: msg.stake_weight,
+        oracle_fallback: msg.oracle_fallback,
+        oracle_compromised: false,
+        nft_gate: msg.nft_gate,
+        cohorts: vec![],
+        guardians: msg.guardians,
+        roles: msg.roles,
+        paused: false,
+        dispute: msg.dispute,
+        veto_council: msg.veto_council,
+        veto_approvals: vec![],
+        completion_hook: msg.completion_hook,
+        finalization_bounty: msg.finalization_bounty,
+        participation_reward: msg.participation_reward,
+        receipts: msg.receipts,
+        limits: msg.limits,
+        referendum: msg.referendum,
+        succession: msg.succession,
+        write_ins: msg.write_ins,
+        allow_contract_votes: msg.allow_contract_votes,
+        registry: msg.registry,
+        majority_runoff: msg.majority_runoff,
+        merkle_roll: msg.merkle_roll,
+        weight_decay: msg.weight_decay,
+        eligibility: msg.eligibility,
+        registration: msg.registration,
+        election_code_id: msg.election_code_id,
+        factory: msg.factory.clone(),
+        nomination: msg.nomination,
+        candidate_rules: msg.candidate_rules,
+        denom: msg.denom,
+        min_votes: msg.min_votes,
+        party_list: msg.party_list,
+        metadata: msg.metadata,
+        ballot_fee: msg.ballot_fee,
+        anti_snipe: msg.anti_snipe,
+        extensions: vec![],
+        history: msg.history,
+        questions: msg.questions,
+        voter_salt: if msg.pseudonymous {
+            let mut seed = env.contract.address.as_bytes().to_vec();
+            seed.extend_from_slice(&env.block.height.to_be_bytes());
+            seed.extend_from_slice(&env.block.time.to_be_bytes());
+            Some(Binary::from(sha256(&seed).to_vec()))
+        } else {
+            None
+        },
+        archived: false,
+        tally_cursor: None,
+        candidates,
         votes: Vec::new(),
+        stale_reported: false,
+        halted: false,
+        read_only: false,
     };
+    for cohort in msg.cohorts {
+        let invalid = cohort.start > cohort.end
+            || cohort.start < state.start
+            || cohort.end > state.end
+            || state.cohorts.iter().any(|c| c.name == cohort.name);
+        if invalid {
+            return Err(ContractError::InvalidCohort { name: cohort.name });
+        }
+        for member in &cohort.members {
+            let key = voter_key(&deps.api, &state, member)?;
+            if cohort_members_read(&deps.storage)
+                .may_load(key.as_slice())?
+                .is_some()
+            {
+                return Err(ContractError::DuplicateCohortMember {
+                    voter: member.clone(),
+                });
+            }
+            cohort_members(&mut deps.storage).save(key.as_slice(), &cohort.name)?;
+        }
+        state.cohorts.push(Cohort {
+            name: cohort.name,
+            start: cohort.start,
+            end: cohort.end,
+        });
+    }
+    // funds sent along with the instantiation are the prize for the winners,
+    // less the participation reward and the finalization bounty
+    if !info.sent_funds.is_empty()
+        || state.participation_reward.is_some()
+        || state.finalization_bounty.is_some()
+    {
+        let mut books = Ledger::default();
+        books.deposit(FundBucket::PrizePool, &info.sent_funds);
+        if let Some(reward) = &state.participation_reward {
+            books.transfer(FundBucket::PrizePool, FundBucket::RewardPool, &reward.pool)?;
+        }
+        if let Some(bounty) = &state.finalization_bounty {
+            books.transfer(FundBucket::PrizePool, FundBucket::Bounty, &bounty.amount)?;
+        }
+        ledger(&mut deps.storage).save(&books)?;
+    }
+    for entry in msg.labels {
+        validate_labels(&deps.api, &state, &entry)?;
+        let candidate = deps.api.canonical_address(&entry.candidate)?;
+        labels(&mut deps.storage).save(candidate.as_slice(), &entry.labels)?;
+    }
     config(&mut deps.storage).save(&state)?;
 
-    Ok(InitResponse::default())
+    // tell the factory where this election lives
+    let mut messages = vec![];
+    if let Some(factory) = msg.factory {
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: factory.address,
+                msg: to_binary(&HandleMsg::RegisterSpawned {
+                    spawn_id: factory.spawn_id,
+                })?,
+                send: vec![],
+            }
+            .into(),
+        );
+    }
+    Ok(InitResponse {
+        messages,
+        attributes: vec![],
+    })
+}
+
+fn validate_referendum(referendum: &Referendum) -> Result<(), ContractError> {
+    let invalid = referendum.electorate.is_zero()
+        || referendum.threshold > Decimal::one()
+        || referendum.quorum > Decimal::one()
+        || referendum.veto_threshold > Decimal::one();
+    if invalid {
+        return Err(ContractError::InvalidReferendum {});
+    }
+    Ok(())
+}
+
+fn validate_metadata(metadata: &Metadata) -> Result<(), ContractError> {
+    let too_long = |field: &Option<String>, max: usize| field.as_ref().map_or(0, String::len) > max;
+    if metadata.title.is_empty()
+        || metadata.title.len() > MAX_TITLE_LENGTH
+        || too_long(&metadata.description, MAX_DESCRIPTION_LENGTH)
+        || too_long(&metadata.url, MAX_URL_LENGTH)
+        || metadata.tags.len() > MAX_TAGS
+        || metadata
+            .tags
+            .iter()
+            .any(|tag| tag.is_empty() || tag.len() > MAX_TAG_LENGTH)
+    {
+        return Err(ContractError::InvalidMetadata { max_tags: MAX_TAGS });
+    }
+    Ok(())
+}
+
+/// validate_party_list requires every listed candidate to be registered and
+/// to stand on a single slate
+fn validate_party_list(msg: &InitMsg, list: &PartyList) -> Result<(), ContractError> {
+    if msg.voting_system != VotingSystem::Plurality
+        || msg.majority_runoff.is_some()
+        || list.slates.is_empty()
+    {
+        return Err(ContractError::InvalidPartyList {});
+    }
+    let mut names = HashSet::new();
+    let mut listed = HashSet::new();
+    for slate in &list.slates {
+        if slate.name.is_empty() || !names.insert(&slate.name) || slate.candidates.is_empty() {
+            return Err(ContractError::InvalidPartyList {});
+        }
+        for candidate in &slate.candidates {
+            if !msg.candidates.contains(candidate) || !listed.insert(candidate) {
+                return Err(ContractError::InvalidPartyList {});
+            }
+        }
+    }
+    Ok(())
+}
+
+/// validate_candidates canonicalizes the candidate list, so that two spellings
+/// of one address are caught as duplicates
+fn validate_candidates<A: Api>(
+    api: &A,
+    rules: Option<&CandidateRules>,
+    owner: &HumanAddr,
+    candidates: &[HumanAddr],
+) -> Result<Vec<CanonicalAddr>, ContractError> {
+    if candidates.is_empty() {
+        return Err(ContractError::NoCandidates {});
+    }
+    let mut canonical = vec![];
+    for candidate in candidates {
+        check_candidate(api, rules, owner, candidate)?;
+        let candidate_raw = api.canonical_address(candidate)?;
+        if canonical.contains(&candidate_raw) {
+            return Err(ContractError::DuplicateCandidate {
+                candidate: candidate.clone(),
+            });
+        }
+        canonical.push(candidate_raw);
+    }
+    Ok(canonical)
+}
+
+/// check_candidate applies the ban list and the owner rule of `rules`,
+/// comparing canonical addresses
+fn check_candidate<A: Api>(
+    api: &A,
+    rules: Option<&CandidateRules>,
+    owner: &HumanAddr,
+    candidate: &HumanAddr,
+) -> Result<(), ContractError> {
+    let rules = match rules {
+        Some(rules) => rules,
+        None => return Ok(()),
+    };
+    let candidate_raw = api.canonical_address(candidate)?;
+    if rules.no_owner && api.canonical_address(owner)? == candidate_raw {
+        return Err(ContractError::OwnerCandidate {
+            candidate: candidate.clone(),
+        });
+    }
+    for banned in &rules.banned {
+        if api.canonical_address(banned)? == candidate_raw {
+            return Err(ContractError::BannedCandidate {
+                candidate: candidate.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn validate_labels<A: Api>(
+    api: &A,
+    state: &State,
+    entry: &CandidateLabels,
+) -> Result<(), ContractError> {
+    let candidate = &entry.candidate;
+    if !state
+        .candidates
+        .contains(&api.canonical_address(candidate)?)
+    {
+        return Err(ContractError::UnknownCandidate {
+            candidate: candidate.clone(),
+        });
+    }
+    if entry.labels.len() > MAX_LABELS {
+        return Err(ContractError::TooManyLabels {
+            candidate: candidate.clone(),
+            max: MAX_LABELS,
+        });
+    }
+    for (i, label) in entry.labels.iter().enumerate() {
+        let invalid = label.locale.is_empty()
+            || label.locale.len() > MAX_LOCALE_LENGTH
+            || label.label.is_empty()
+            || label.label.len() > MAX_LABEL_LENGTH
+            || entry.labels[..i].iter().any(|l| l.locale == label.locale);
+        if invalid {
+            return Err(ContractError::InvalidLabel {
+                candidate: candidate.clone(),
+                locale: label.locale.clone(),
+            });
+        }
+    }
+    Ok(())
 }
 
 // And declare a custom Error variant for the ones where you will want to make use of it
@@ -34,117 +504,11526 @@ pub fn handle<S: Storage, A: Api, Q: Querier>(
     info: MessageInfo,
     msg: HandleMsg,
 ) -> Result<HandleResponse, ContractError> {
-    match msg {
-        HandleMsg::Vote { candidate } => try_vote(deps, env, info, candidate),
+    let toggles_read_only = matches!(msg, HandleMsg::SetReadOnly { .. });
+    let state = config_read(&deps.storage).load()?;
+    if !toggles_read_only && state.read_only {
+        return Err(ContractError::ReadOnly {});
+    }
+    let rate_limited = matches!(
+        msg,
+        HandleMsg::Vote { .. }
+            | HandleMsg::SlateVote { .. }
+            | HandleMsg::RankedVote { .. }
+            | HandleMsg::SplitVote { .. }
+            | HandleMsg::BatchVote { .. }
+            | HandleMsg::RegisterVoter {}
+            | HandleMsg::WithdrawCandidacy {}
+            | HandleMsg::SetRelayer { .. }
+            | HandleMsg::VoteAsContract { .. }
+            | HandleMsg::ProxyVote { .. }
+            | HandleMsg::ReferendumVote { .. }
+    );
+    let cooldown = match state.limits.cooldown {
+        Some(cooldown) if rate_limited => Some(cooldown),
+        _ => None,
+    };
+    let sender = match cooldown {
+        Some(cooldown) => {
+            let sender = voter_key(&deps.api, &state, &info.sender)?;
+            if let Some(last) = last_action_read(&deps.storage).may_load(sender.as_slice())? {
+                let retry_at = last + cooldown;
+                if env.block.height < retry_at {
+                    return Err(ContractError::TooFrequent { retry_at });
+                }
+            }
+            Some(sender)
+        }
+        None => None,
+    };
+    check_denom(state.denom.as_deref(), &info.sent_funds)?;
+    let ballot = matches!(
+        msg,
+        HandleMsg::Vote { .. }
+            | HandleMsg::SlateVote { .. }
+            | HandleMsg::RankedVote { .. }
+            | HandleMsg::SplitVote { .. }
+            | HandleMsg::BatchVote { .. }
+            | HandleMsg::VoteAsContract { .. }
+            | HandleMsg::ProxyVote { .. }
+            | HandleMsg::ReferendumVote { .. }
+    );
+    if ballot
+        && env.block.height >= state.start
+        && roll_snapshot_read(&deps.storage).may_load()?.is_none()
+    {
+        freeze_roll(deps, &state, env.block.height)?;
+    }
+    // every ballot this message casts is counted in the stats, the candidate
+    // ballots among them are added to the end of the votes
+    let fee_payment = match state.ballot_fee {
+        Some(fee) if ballot => {
+            let entries = match &msg {
+                HandleMsg::BatchVote { votes } => votes.len(),
+                _ => 1,
+            };
+            fee_paid(&fee, &info, entries)?;
+            let ballots = stats_read(&deps.storage)
+                .may_load()?
+                .unwrap_or_default()
+                .ballots;
+            Some((
+                fee,
+                (ballots, state.votes.len()),
+                info.clone(),
+                env.contract.address.clone(),
+            ))
+        }
+        _ => None,
+    };
+    let height = env.block.height;
+    let audited = match admin_action(&msg, &info.sender) {
+        Some(action) => Some((action, info.sender.clone(), to_vec(&msg)?)),
+        None => None,
+    };
+    let mut res = dispatch(deps, env, info, msg)?;
+    if let Some((fee, counted, payer, contract)) = fee_payment {
+        charge_ballot_fees(deps, contract, payer, &fee, counted, &mut res)?;
+    }
+    if let Some(sender) = sender {
+        last_action(&mut deps.storage).save(sender.as_slice(), &height)?;
+    }
+    if let Some((action, actor, payload)) = audited {
+        append_audit(&mut deps.storage, actor, height, action, &payload)?;
     }
+    Ok(res)
 }
 
-pub fn try_vote<S: Storage, A: Api, Q: Querier>(
+/// charge_ballot_fees books `fee` under `payer` for every ballot counted
+/// past `ballots`, records the payer of the candidate ballots past `votes`
+/// and returns the rest of the fee coin sent
+fn charge_ballot_fees<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    contract: HumanAddr,
+    payer: MessageInfo,
+    fee: &Coin,
+    (ballots, votes): (u64, usize),
+    res: &mut HandleResponse,
+) -> Result<(), ContractError> {
+    let counters = stats_read(&deps.storage).may_load()?.unwrap_or_default();
+    let cast = counters.ballots.saturating_sub(ballots);
+    let paid = fee_paid(fee, &payer, cast as usize)?;
+    let owed = Uint128(fee.amount.u128() * u128::from(cast));
+    if cast > 0 {
+        let state = config_read(&deps.storage).load()?;
+        let payer_raw = voter_key(&deps.api, &state, &payer.sender)?;
+        for vote in state.votes.get(votes..).unwrap_or_default() {
+            let payment = BallotPayment {
+                payer: payer_raw.clone(),
+                fee: fee.clone(),
+            };
+            ballot_payers(&mut deps.storage).save(vote.voter.as_slice(), &payment)?;
+        }
+        let mut held = ballot_fees_read(&deps.storage)
+            .may_load(payer_raw.as_slice())?
+            .unwrap_or_else(|| coin(0, &fee.denom));
+        held.amount += owed;
+        ballot_fees(&mut deps.storage).save(payer_raw.as_slice(), &held)?;
+        let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+        books.deposit(FundBucket::BallotFees, &[coin(owed.u128(), &fee.denom)]);
+        ledger(&mut deps.storage).save(&books)?;
+    }
+    let change = (paid - owed)?;
+    if !change.is_zero() {
+        res.messages.push(
+            BankMsg::Send {
+                from_address: contract,
+                to_address: payer.sender,
+                amount: vec![coin(change.u128(), &fee.denom)],
+            }
+            .into(),
+        );
+    }
+    Ok(())
+}
+
+/// fee_paid returns the amount of the fee coin sent, if it covers `ballots`
+fn fee_paid(fee: &Coin, info: &MessageInfo, ballots: usize) -> Result<Uint128, ContractError> {
+    let paid = info
+        .sent_funds
+        .iter()
+        .find(|c| c.denom == fee.denom)
+        .map_or(Uint128::zero(), |c| c.amount);
+    if paid.u128() < fee.amount.u128() * ballots as u128 {
+        return Err(ContractError::InsufficientBallotFee {
+            denom: fee.denom.clone(),
+            amount: fee.amount,
+        });
+    }
+    Ok(paid)
+}
+
+/// forfeit_ballot_fees moves the fees of discarded ballots out of reach
+fn forfeit_ballot_fees<S: Storage>(
+    storage: &mut S,
+    voters: &[CanonicalAddr],
+) -> Result<(), ContractError> {
+    let mut books = ledger_read(storage).may_load()?.unwrap_or_default();
+    for voter in voters {
+        let payment = match ballot_payers_read(storage).may_load(voter.as_slice())? {
+            Some(payment) => payment,
+            None => continue,
+        };
+        ballot_payers(storage).remove(voter.as_slice());
+        let payer = payment.payer.as_slice();
+        let mut held = ballot_fees_read(storage).load(payer)?;
+        held.amount = (held.amount - payment.fee.amount)?;
+        if held.amount.is_zero() {
+            ballot_fees(storage).remove(payer);
+        } else {
+            ballot_fees(storage).save(payer, &held)?;
+        }
+        books.transfer(FundBucket::BallotFees, FundBucket::Burned, &[payment.fee])?;
+    }
+    ledger(storage).save(&books)?;
+    Ok(())
+}
+
+/// admin_action names the messages that need a role, an ownership or a
+/// council seat, and the nominations, which go to the audit log. Candidates
+/// recording their own disclosures are not audited.
+fn admin_action(msg: &HandleMsg, sender: &HumanAddr) -> Option<&'static str> {
+    let action = match msg {
+        HandleMsg::ProposeNewOwner { .. } => "propose_new_owner",
+        HandleMsg::AcceptOwnership {} => "accept_ownership",
+        HandleMsg::ProposeCandidate { .. } => "propose_candidate",
+        HandleMsg::ApproveCandidate { .. } => "approve_candidate",
+        HandleMsg::GrantRole { .. } => "grant_role",
+        HandleMsg::RevokeRole { .. } => "revoke_role",
+        HandleMsg::ResolveTie { .. } => "resolve_tie",
+        HandleMsg::PromoteRunnerUp { .. } => "promote_runner_up",
+        HandleMsg::ResolveChallenge { .. } => "resolve_challenge",
+        HandleMsg::ApproveVeto {} => "approve_veto",
+        HandleMsg::SpawnElection { .. } => "spawn_election",
+        HandleMsg::SaveTemplate { .. } => "save_template",
+        HandleMsg::SpawnFromTemplate { .. } => "spawn_from_template",
+        HandleMsg::MarkOracleCompromised {} => "mark_oracle_compromised",
+        HandleMsg::ReplaceOracle { .. } => "replace_oracle",
+        HandleMsg::SetEligibilityFilter { .. } => "set_eligibility_filter",
+        HandleMsg::UpdatePeriod { .. } => "update_period",
+        HandleMsg::Pause {} => "pause",
+        HandleMsg::Unpause {} => "unpause",
+        HandleMsg::SetReadOnly { .. } => "set_read_only",
+        HandleMsg::RecordDisclosure { candidate, .. } if candidate != sender => "record_disclosure",
+        HandleMsg::Archive {} => "archive",
+        HandleMsg::Scrub { .. } => "scrub",
+        _ => return None,
+    };
+    Some(action)
+}
+
+fn append_audit<S: Storage>(
+    storage: &mut S,
+    actor: HumanAddr,
+    height: u64,
+    action: &str,
+    payload: &[u8],
+) -> StdResult<()> {
+    let id = match audit_log_read(storage)
+        .range(None, None, Order::Descending)
+        .next()
+    {
+        Some(last) => last?.1.id + 1,
+        None => 0,
+    };
+    let entry = AuditEntry {
+        id,
+        actor,
+        height,
+        action: action.to_string(),
+        payload_hash: Binary::from(sha256(payload).to_vec()),
+    };
+    audit_log(storage).save(&id.to_be_bytes(), &entry)
+}
+
+/// check_denom rejects coins in any denom other than `expected`
+fn check_denom<'a>(
+    expected: Option<&str>,
+    coins: impl IntoIterator<Item = &'a Coin>,
+) -> Result<(), ContractError> {
+    let expected = match expected {
+        Some(expected) => expected,
+        None => return Ok(()),
+    };
+    match coins.into_iter().find(|coin| coin.denom != expected) {
+        Some(coin) => Err(ContractError::WrongDenom {
+            expected: expected.to_string(),
+            denom: coin.denom.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn dispatch<S: Storage, A: Api, Q: Querier>(
     deps: &mut Extern<S, A, Q>,
     env: Env,
     info: MessageInfo,
-    candidate: HumanAddr,
+    msg: HandleMsg,
 ) -> Result<HandleResponse, ContractError> {
-    config(&mut deps.storage).update(|mut state| -> Result<_, ContractError> {
-        if env.block.height < state.start || env.block.height > state.end {
-            return Err(ContractError::NotAllowance {
-                begin: state.start,
-                end: state.end,
-            });
+    match msg {
+        HandleMsg::Vote {
+            candidate,
+            proof,
+            answers,
+        } => try_vote(deps, env, info, candidate, proof, answers),
+        HandleMsg::SlateVote { name } => try_slate_vote(deps, env, info, name),
+        HandleMsg::RankedVote { ranking } => try_vote_ranked(deps, env, info, ranking),
+        HandleMsg::SplitVote { shares } => try_vote_split(deps, env, info, shares),
+        HandleMsg::BatchVote { votes } => try_batch_vote(deps, env, info, votes),
+        HandleMsg::RegisterVoter {} => try_register_voter(deps, env, info),
+        HandleMsg::WithdrawCandidacy {} => try_withdraw_candidacy(deps, env, info),
+        HandleMsg::Archive {} => try_archive(deps, env, info),
+        HandleMsg::PublishResult {} => try_publish_result(deps, env),
+        HandleMsg::ProposeNewOwner { address } => try_propose_new_owner(deps, info, address),
+        HandleMsg::AcceptOwnership {} => try_accept_ownership(deps, info),
+        HandleMsg::Contribute { candidate } => try_contribute(deps, env, info, candidate),
+        HandleMsg::ReleaseContributions { candidate } => {
+            try_release_contributions(deps, env, candidate)
         }
-        state.votes.push(VoteInfo {
-            voter: info.sender,
-            candidate: candidate,
-        });
-        Ok(state)
-    })?;
-    Ok(HandleResponse::default())
+        HandleMsg::RefundContribution { candidate } => {
+            try_refund_contribution(deps, env, info, candidate)
+        }
+        HandleMsg::Snapshot {} => try_snapshot(deps, env),
+        HandleMsg::AuthorizeProxy { proxy, expires } => {
+            try_authorize_proxy(deps, env, info, proxy, expires)
+        }
+        HandleMsg::RevokeProxy {} => try_revoke_proxy(deps, info),
+        HandleMsg::ProxyVote { principal, ballot } => {
+            try_proxy_vote(deps, env, info, principal, ballot)
+        }
+        HandleMsg::Lock {} => try_lock(deps, env, info),
+        HandleMsg::Unlock {} => try_unlock(deps, env, info),
+        HandleMsg::ProposeCandidate { candidate } => {
+            try_propose_candidate(deps, env, info, candidate)
+        }
+        HandleMsg::ApproveCandidate { candidate } => {
+            try_approve_candidate(deps, env, info, candidate)
+        }
+        HandleMsg::GrantRole { address, role } => try_set_role(deps, info, address, role, true),
+        HandleMsg::RevokeRole { address, role } => try_set_role(deps, info, address, role, false),
+        HandleMsg::RecordArchive { spawn_id, summary } => {
+            try_record_archive(deps, info, spawn_id, summary)
+        }
+        HandleMsg::WithdrawBond {} => try_withdraw_bond(deps, env, info),
+        HandleMsg::ClaimBallotFee {} => try_claim_ballot_fee(deps, env, info),
+        HandleMsg::ClaimReward {} => try_claim_reward(deps, env, info),
+        HandleMsg::SetRelayer { relayer } => try_set_relayer(deps, info, relayer),
+        HandleMsg::VoteAsContract {
+            on_behalf_of_policy,
+            ballot,
+        } => try_vote_as_contract(deps, env, info, on_behalf_of_policy, ballot),
+        HandleMsg::Finalize { limit } => try_finalize(deps, env, info.sender, limit),
+        HandleMsg::CheckPhase {} => try_check_phase(deps, env, info.sender),
+        HandleMsg::PromoteRunnerUp { vacated } => try_promote_runner_up(deps, env, info, vacated),
+        HandleMsg::ResolveTie { winners } => try_resolve_tie(deps, env, info, winners),
+        HandleMsg::SpawnElection { label, params } => {
+            try_spawn_election(deps, env, info, label, *params)
+        }
+        HandleMsg::SaveTemplate { name, params } => try_save_template(deps, info, name, *params),
+        HandleMsg::SpawnFromTemplate {
+            name,
+            label,
+            candidates,
+        } => try_spawn_from_template(deps, env, info, name, label, candidates),
+        HandleMsg::RegisterSpawned { spawn_id } => try_register_spawned(deps, info, spawn_id),
+        HandleMsg::ChallengeResult {} => try_challenge_result(deps, env, info),
+        HandleMsg::Challenge { reason } => try_challenge(deps, env, info, reason),
+        HandleMsg::ResolveChallenge { id, upheld } => {
+            try_resolve_challenge(deps, env, info, id, upheld)
+        }
+        HandleMsg::ApproveVeto {} => try_approve_veto(deps, env, info),
+        HandleMsg::ReleasePrize {} => try_release_prize(deps, env),
+        HandleMsg::MarkOracleCompromised {} => try_mark_oracle_compromised(deps, info),
+        HandleMsg::ReplaceOracle { address } => try_replace_oracle(deps, info, address),
+        HandleMsg::SetEligibilityFilter { filter } => try_set_eligibility(deps, info, filter),
+        HandleMsg::ReferendumVote { choice } => try_referendum_vote(deps, env, info, choice),
+        HandleMsg::Scrub { limit } => try_scrub(deps, env, info, limit),
+        HandleMsg::RecordDisclosure {
+            candidate,
+            source,
+            amount,
+            memo,
+        } => try_record_disclosure(deps, env, info, candidate, source, amount, memo),
+        HandleMsg::UpdatePeriod { start, end } => try_update_period(deps, env, info, start, end),
+        HandleMsg::Pause {} => try_set_paused(deps, info, true),
+        HandleMsg::Unpause {} => try_set_paused(deps, info, false),
+        HandleMsg::SetReadOnly { read_only } => try_set_read_only(deps, info, read_only),
+    }
 }
 
-pub fn query<S: Storage, A: Api, Q: Querier>(
-    deps: &Extern<S, A, Q>,
-    _env: Env,
-    msg: QueryMsg,
-) -> StdResult<Binary> {
-    match msg {
-        QueryMsg::GetVoteInfo {} => to_binary(&query_vote_info(deps)?),
+/// sudo lets the chain's governance extend, halt and resume the election,
+/// and lets a chain hook advance its phase.
+///
+/// cosmwasm-std 0.11 has no sudo export, so this is not reachable on chain
+/// until the contract moves to a version whose entry points include it.
+pub fn sudo<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    msg: SudoMsg,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    let payload = to_vec(&msg)?;
+    let action = match msg {
+        SudoMsg::CheckPhase {} => return try_check_phase(deps, env, state.owner),
+        SudoMsg::Extend { end } => {
+            if end <= state.end {
+                return Err(ContractError::InvalidExtension { end: state.end });
+            }
+            if result_read(&deps.storage).may_load()?.is_some() {
+                return Err(ContractError::AlreadyFinalized {});
+            }
+            state.end = end;
+            // a late election that gets extended can go stale again
+            state.stale_reported = false;
+            "extend"
+        }
+        SudoMsg::Halt {} => {
+            state.halted = true;
+            "halt"
+        }
+        SudoMsg::Resume {} => {
+            state.halted = false;
+            "resume"
+        }
+        SudoMsg::SetReadOnly { read_only } => {
+            state.read_only = read_only;
+            "set_read_only"
+        }
+    };
+    config(&mut deps.storage).save(&state)?;
+    append_audit(
+        &mut deps.storage,
+        env.contract.address,
+        env.block.height,
+        action,
+        &payload,
+    )?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", action), attr("end", state.end)],
+        data: None,
+    })
+}
+
+pub fn try_set_read_only<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+    read_only: bool,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if !has_role(&state, &info.sender, Role::Admin) {
+        return Err(ContractError::Unauthorized {});
     }
+    state.read_only = read_only;
+    config(&mut deps.storage).save(&state)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "set_read_only"),
+            attr("read_only", read_only),
+        ],
+        data: None,
+    })
 }
 
-fn query_vote_info<S: Storage, A: Api, Q: Querier>(
-    deps: &Extern<S, A, Q>,
-) -> StdResult<VoteResponse> {
+/// try_snapshot freezes the voter roll once voting has started
+pub fn try_snapshot<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> Result<HandleResponse, ContractError> {
     let state = config_read(&deps.storage).load()?;
-    let mut vote_info = HashMap::new();
-    for vote in state.votes {
-        let count = vote_info.entry(vote.candidate).or_insert(0);
-        *count += 1;
+    if env.block.height < state.start {
+        return Err(ContractError::SnapshotTooEarly { start: state.start });
     }
-
-    let mut votes = Vec::new();
-    for (candidate, count) in vote_info {
-        votes.push(Vote {
-            candidate: candidate,
-            count: count,
+    if let Some(snapshot) = roll_snapshot_read(&deps.storage).may_load()? {
+        return Err(ContractError::SnapshotTaken {
+            height: snapshot.height,
         });
     }
-    Ok(VoteResponse { votes: votes, start: state.start, end: state.end })
+    let snapshot = freeze_roll(deps, &state, env.block.height)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "snapshot"),
+            attr("height", snapshot.height),
+            attr("registered", snapshot.registered),
+        ],
+        data: None,
+    })
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-    use cosmwasm_std::{coins, from_binary};
-
-    #[test]
-    fn proper_initialization() {
-        let mut deps = mock_dependencies(&[]);
-
-        let msg = InitMsg {
-            start: 10,
-            end: 100,
-            candidates: Vec::new(),
-        };
-        let info = mock_info("creator", &coins(1000, "earth"));
+fn freeze_roll<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    state: &State,
+    height: u64,
+) -> StdResult<RollSnapshot> {
+    let registered = stats_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+        .registered;
+    let snapshot = RollSnapshot {
+        height,
+        eligibility: state.eligibility.clone(),
+        registered,
+    };
+    roll_snapshot(&mut deps.storage).save(&snapshot)?;
+    Ok(snapshot)
+}
 
-        // we can just call .unwrap() to assert this was a success
-        let res = init(&mut deps, mock_env(), info, msg).unwrap();
-        assert_eq!(0, res.messages.len());
+pub fn try_set_eligibility<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+    filter: Option<EligibilityFilter>,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if !has_role(&state, &info.sender, Role::ElectionManager) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if let Some(filter) = &filter {
+        validate_eligibility(filter)?;
+    }
+    state.eligibility = filter;
+    config(&mut deps.storage).save(&state)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", "set_eligibility_filter")],
+        data: None,
+    })
+}
 
-        // it worked, let's query the state
-        let res = query(&deps, mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
-        let value: VoteResponse = from_binary(&res).unwrap();
-        assert_eq!(10, value.start);
-        assert_eq!(100, value.end);
+fn validate_eligibility(filter: &EligibilityFilter) -> Result<(), ContractError> {
+    if filter.bits.is_empty() || filter.hashes == 0 || filter.hashes > MAX_BLOOM_HASHES {
+        return Err(ContractError::InvalidEligibilityFilter {
+            max: MAX_BLOOM_HASHES,
+        });
     }
+    Ok(())
+}
 
-    #[test]
-    fn vote() {
-        let mut deps = mock_dependencies(&coins(2, "token"));
+/// prove_membership checks `proof` against the merkle roll and caches the
+/// voter's weight, which `voting_weight` then reads
+fn prove_membership<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    voter: &HumanAddr,
+    proof: &MerkleProof,
+) -> Result<(), ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let roll = match &state.merkle_roll {
+        Some(roll) => roll,
+        None => return Ok(()),
+    };
+    let mut data = voter.as_bytes().to_vec();
+    let weight = match (roll.weighted, proof.weight) {
+        (true, Some(weight)) => {
+            data.extend_from_slice(&weight.to_be_bytes());
+            weight
+        }
+        (false, None) => 1,
+        _ => {
+            return Err(ContractError::InvalidMerkleProof {
+                voter: voter.clone(),
+            })
+        }
+    };
+    if !merkle::verify(&roll.root, merkle::leaf(&data), &proof.path) {
+        return Err(ContractError::InvalidMerkleProof {
+            voter: voter.clone(),
+        });
+    }
+    if weight == 0 {
+        return Err(ContractError::NoVotingPower {
+            voter: voter.clone(),
+        });
+    }
+    voter_weights(&mut deps.storage).save(
+        voter_key(&deps.api, &state, voter)?.as_slice(),
+        &VoterWeight {
+            weight,
+            height: env.block.height,
+        },
+    )?;
+    Ok(())
+}
 
-        let mut candidates:Vec<HumanAddr> = Vec::new();
-        candidates.push("candidates1".into());
-        candidates.push("candidates2".into());
-        let msg = InitMsg {
-            start: 10_000,
-            end: 20_000,
-            candidates: Vec::new(),
-        };
-        let info = mock_info("creator", &coins(2, "token"));
-        let _res = init(&mut deps, mock_env(), info, msg).unwrap();
+/// check_eligibility tests `voter` against the eligibility filter, if any
+fn check_eligibility<A: Api>(
+    api: &A,
+    filter: Option<&EligibilityFilter>,
+    voter: &HumanAddr,
+) -> Result<(), ContractError> {
+    let filter = match filter {
+        Some(filter) => filter,
+        None => return Ok(()),
+    };
+    let voter_raw = api.canonical_address(voter)?;
+    if filter.excluded.contains(voter) || !bloom::contains(&filter.bits, filter.hashes, &voter_raw)
+    {
+        return Err(ContractError::NotEligible {
+            voter: voter.clone(),
+        });
+    }
+    Ok(())
+}
 
-        // beneficiary can release it
-        let info = mock_info("voter1", &coins(2, "token"));
-        let msg = HandleMsg::Vote {candidate:"candidates1".into()};
-        let _res = handle(&mut deps, mock_env(), info, msg).unwrap();
+pub fn try_record_disclosure<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    candidate: HumanAddr,
+    source: String,
+    amount: Coin,
+    memo: String,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    if info.sender != candidate && !has_role(&state, &info.sender, Role::ElectionManager) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !state
+        .candidates
+        .contains(&deps.api.canonical_address(&candidate)?)
+    {
+        return Err(ContractError::UnknownCandidate { candidate });
+    }
+    if source.len() > MAX_DISCLOSURE_LENGTH || memo.len() > MAX_DISCLOSURE_LENGTH {
+        return Err(ContractError::DisclosureTooLong {
+            max: MAX_DISCLOSURE_LENGTH,
+        });
+    }
+    let id = match disclosures_read(&deps.storage, &candidate)
+        .range(None, None, Order::Descending)
+        .next()
+    {
+        Some(last) => last?.1.id + 1,
+        None => 0,
+    };
+    let disclosure = Disclosure {
+        id,
+        source,
+        amount,
+        memo,
+        recorded_by: info.sender,
+        height: env.block.height,
+    };
+    disclosures(&mut deps.storage, &candidate).save(&id.to_be_bytes(), &disclosure)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "record_disclosure"),
+            attr("candidate", candidate),
+            attr("id", id),
+        ],
+        data: None,
+    })
+}
 
-        // should increase counter by 1
-        let res = query(&deps, mock_env(), QueryMsg::GetVoteInfo {}).unwrap();
-        let value: VoteResponse = from_binary(&res).unwrap();
-        assert_eq!(10_000, value.start);
-        assert_eq!(20_000, value.end);
-        assert_eq!("candidates1", value.votes[0].candidate);
-        assert_eq!(1, value.votes[0].count);
+pub fn try_spawn_election<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    label: String,
+    mut params: InitMsg,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let code_id = state
+        .election_code_id
+        .ok_or(ContractError::FactoryDisabled {})?;
+    if !has_role(&state, &info.sender, Role::ElectionManager) {
+        return Err(ContractError::Unauthorized {});
     }
-}
\ No newline at end of file
+    let mut elections = spawned_read(&deps.storage).may_load()?.unwrap_or_default();
+    let spawn_id = elections.len() as u64 + 1;
+    params.factory = Some(FactoryLink {
+        address: env.contract.address,
+        spawn_id,
+    });
+    elections.push(SpawnedElection {
+        spawn_id,
+        label: label.clone(),
+        address: None,
+        metadata: params.metadata.clone(),
+        start: params.start,
+        end: params.end,
+    });
+    spawned(&mut deps.storage).save(&elections)?;
+    Ok(HandleResponse {
+        messages: vec![WasmMsg::Instantiate {
+            code_id,
+            msg: to_binary(&params)?,
+            send: info.sent_funds,
+            label: Some(label),
+        }
+        .into()],
+        attributes: vec![attr("action", "spawn_election"), attr("spawn_id", spawn_id)],
+        data: None,
+    })
+}
+
+pub fn try_save_template<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+    name: String,
+    params: InitMsg,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    if state.election_code_id.is_none() {
+        return Err(ContractError::FactoryDisabled {});
+    }
+    if !has_role(&state, &info.sender, Role::Admin) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if name.is_empty() || name.len() > MAX_TEMPLATE_NAME_LENGTH {
+        return Err(ContractError::InvalidTemplateName {
+            max: MAX_TEMPLATE_NAME_LENGTH,
+        });
+    }
+    if params.start >= params.end {
+        return Err(ContractError::InvalidPeriod {
+            start: params.start,
+            end: params.end,
+        });
+    }
+    templates(&mut deps.storage).save(name.as_bytes(), &params)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", "save_template"), attr("name", name)],
+        data: None,
+    })
+}
+
+/// try_spawn_from_template spawns the template `name` with its window
+/// starting at the current height
+pub fn try_spawn_from_template<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+    label: String,
+    candidates: Vec<HumanAddr>,
+) -> Result<HandleResponse, ContractError> {
+    let template = templates_read(&deps.storage)
+        .may_load(name.as_bytes())?
+        .ok_or_else(|| ContractError::UnknownTemplate { name: name.clone() })?;
+    let params = InitMsg {
+        start: env.block.height + template.start,
+        end: env.block.height + template.end,
+        candidates,
+        ..template
+    };
+    let mut res = try_spawn_election(deps, env, info, label, params)?;
+    res.attributes.push(attr("template", name));
+    Ok(res)
+}
+
+/// try_register_spawned records the address of a spawned election. The child
+/// registers from its own init, in the same transaction as the spawn, so
+/// nobody else can claim the pending id.
+pub fn try_register_spawned<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+    spawn_id: u64,
+) -> Result<HandleResponse, ContractError> {
+    let mut elections = spawned_read(&deps.storage).may_load()?.unwrap_or_default();
+    let election = elections
+        .iter_mut()
+        .find(|e| e.spawn_id == spawn_id && e.address.is_none())
+        .ok_or(ContractError::UnknownSpawn { spawn_id })?;
+    election.address = Some(info.sender.clone());
+    spawned(&mut deps.storage).save(&elections)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "register_spawned"),
+            attr("spawn_id", spawn_id),
+            attr("address", info.sender),
+        ],
+        data: None,
+    })
+}
+
+/// try_contribute escrows the sent funds for a standing candidate while
+/// voting is open
+pub fn try_contribute<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    candidate: HumanAddr,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let end = voting_end(&state);
+    if env.block.height > end {
+        return Err(ContractError::ContributionsClosed { end });
+    }
+    let candidate_raw = deps.api.canonical_address(&candidate)?;
+    if !state.candidates.contains(&candidate_raw) {
+        return Err(ContractError::UnknownCandidate { candidate });
+    }
+    if state.withdrawn.contains(&candidate_raw) {
+        return Err(ContractError::CandidateWithdrawn { candidate });
+    }
+    if info.sent_funds.is_empty() {
+        return Err(ContractError::NoContribution { candidate });
+    }
+
+    let contributor = voter_key(&deps.api, &state, &info.sender)?;
+    let mut given = contributions_read(&deps.storage, &candidate_raw)
+        .may_load(contributor.as_slice())?
+        .unwrap_or_default();
+    let mut total = campaign_totals_read(&deps.storage)
+        .may_load(candidate_raw.as_slice())?
+        .unwrap_or_default();
+    for coin in &info.sent_funds {
+        add_coin(&mut given, coin);
+        add_coin(&mut total, coin);
+    }
+    contributions(&mut deps.storage, &candidate_raw).save(contributor.as_slice(), &given)?;
+    campaign_totals(&mut deps.storage).save(candidate_raw.as_slice(), &total)?;
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    books.deposit(FundBucket::Campaigns, &info.sent_funds);
+    ledger(&mut deps.storage).save(&books)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "contribute"),
+            attr("candidate", candidate),
+            attr("contributor", info.sender),
+        ],
+        data: None,
+    })
+}
+
+/// campaign_won reports whether `candidate` won a final result. It fails
+/// while a result electing the candidate can still be vetoed or disputed.
+fn campaign_won<S: Storage, A: Api>(
+    storage: &S,
+    api: &A,
+    env: &Env,
+    candidate: &CanonicalAddr,
+) -> Result<bool, ContractError> {
+    let stored = result_read(storage)
+        .may_load()?
+        .ok_or(ContractError::NotFinalized {})?;
+    let mut won = false;
+    for winner in &stored.winners {
+        won |= &api.canonical_address(&winner.candidate)? == candidate;
+    }
+    if !won {
+        return Ok(false);
+    }
+    let state = config_read(storage).load()?;
+    match result_status(storage, &state, &stored, env.block.height)? {
+        ResultStatus::Final => Ok(true),
+        ResultStatus::Provisional | ResultStatus::Challenged => {
+            Err(ContractError::ResultNotFinal {})
+        }
+        _ => Ok(false),
+    }
+}
+
+pub fn try_release_contributions<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    candidate: HumanAddr,
+) -> Result<HandleResponse, ContractError> {
+    let candidate_raw = deps.api.canonical_address(&candidate)?;
+    if !campaign_won(&deps.storage, &deps.api, &env, &candidate_raw)? {
+        return Err(ContractError::NotAWinner { candidate });
+    }
+    let total = campaign_totals_read(&deps.storage)
+        .may_load(candidate_raw.as_slice())?
+        .unwrap_or_default();
+    if total.is_empty() {
+        return Err(ContractError::NoContribution { candidate });
+    }
+    campaign_totals(&mut deps.storage).remove(candidate_raw.as_slice());
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    books.withdraw(FundBucket::Campaigns, &total)?;
+    ledger(&mut deps.storage).save(&books)?;
+    Ok(HandleResponse {
+        messages: vec![BankMsg::Send {
+            from_address: env.contract.address,
+            to_address: candidate.clone(),
+            amount: total,
+        }
+        .into()],
+        attributes: vec![
+            attr("action", "release_contributions"),
+            attr("candidate", candidate),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_refund_contribution<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    candidate: HumanAddr,
+) -> Result<HandleResponse, ContractError> {
+    let candidate_raw = deps.api.canonical_address(&candidate)?;
+    if campaign_won(&deps.storage, &deps.api, &env, &candidate_raw)? {
+        return Err(ContractError::CandidateWon { candidate });
+    }
+    let state = config_read(&deps.storage).load()?;
+    let contributor = voter_key(&deps.api, &state, &info.sender)?;
+    let given = contributions_read(&deps.storage, &candidate_raw)
+        .may_load(contributor.as_slice())?
+        .ok_or_else(|| ContractError::NoContribution {
+            candidate: candidate.clone(),
+        })?;
+    contributions(&mut deps.storage, &candidate_raw).remove(contributor.as_slice());
+    let mut total = campaign_totals_read(&deps.storage)
+        .may_load(candidate_raw.as_slice())?
+        .unwrap_or_default();
+    for coin in &mut total {
+        let refunded: Uint128 = given
+            .iter()
+            .filter(|g| g.denom == coin.denom)
+            .map(|g| g.amount)
+            .sum();
+        coin.amount = (coin.amount - refunded)?;
+    }
+    total.retain(|coin| !coin.amount.is_zero());
+    campaign_totals(&mut deps.storage).save(candidate_raw.as_slice(), &total)?;
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    books.withdraw(FundBucket::Campaigns, &given)?;
+    ledger(&mut deps.storage).save(&books)?;
+    Ok(HandleResponse {
+        messages: vec![BankMsg::Send {
+            from_address: env.contract.address,
+            to_address: info.sender.clone(),
+            amount: given,
+        }
+        .into()],
+        attributes: vec![
+            attr("action", "refund_contribution"),
+            attr("candidate", candidate),
+            attr("contributor", info.sender),
+        ],
+        data: None,
+    })
+}
+
+/// try_slate_vote casts a ballot for the first candidate of the slate, which
+/// counts for the whole slate
+pub fn try_slate_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    name: String,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let list = state
+        .party_list
+        .ok_or(ContractError::PartyListDisabled {})?;
+    let head = list
+        .slates
+        .into_iter()
+        .find(|slate| slate.name == name)
+        .map(|slate| slate.candidates[0].clone())
+        .ok_or_else(|| ContractError::UnknownSlate { name: name.clone() })?;
+    let mut res = try_vote(deps, env, info, head, None, None)?;
+    res.attributes.push(attr("slate", name));
+    Ok(res)
+}
+
+pub fn try_propose_candidate<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    candidate: HumanAddr,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    if state.nomination.is_none() {
+        return Err(ContractError::NominationDisabled {});
+    }
+    if env.block.height >= state.start {
+        return Err(ContractError::NominationClosed { start: state.start });
+    }
+    // one spelling per account, so that proposals cannot be split
+    let candidate_raw = deps.api.canonical_address(&candidate)?;
+    let candidate = deps.api.human_address(&candidate_raw)?;
+    check_candidate(
+        &deps.api,
+        state.candidate_rules.as_ref(),
+        &state.owner,
+        &candidate,
+    )?;
+    if state.candidates.contains(&candidate_raw) {
+        return Err(ContractError::DuplicateCandidate { candidate });
+    }
+    if proposals_read(&deps.storage)
+        .may_load(candidate.as_bytes())?
+        .is_some()
+    {
+        return Err(ContractError::AlreadyProposed { candidate });
+    }
+    let proposal = CandidateProposal {
+        candidate: candidate.clone(),
+        proposer: info.sender,
+        approvals: vec![],
+        height: env.block.height,
+    };
+    proposals(&mut deps.storage).save(candidate.as_bytes(), &proposal)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "propose_candidate"),
+            attr("candidate", candidate),
+        ],
+        data: None,
+    })
+}
+
+/// try_approve_candidate adds the sender's approval and, with enough of
+/// them, the candidate
+pub fn try_approve_candidate<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    candidate: HumanAddr,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    let nomination = state
+        .nomination
+        .clone()
+        .ok_or(ContractError::NominationDisabled {})?;
+    if !nomination.committee.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if env.block.height >= state.start {
+        return Err(ContractError::NominationClosed { start: state.start });
+    }
+    let candidate = deps
+        .api
+        .human_address(&deps.api.canonical_address(&candidate)?)?;
+    let mut proposal = proposals_read(&deps.storage)
+        .may_load(candidate.as_bytes())?
+        .ok_or_else(|| ContractError::UnknownProposal {
+            candidate: candidate.clone(),
+        })?;
+    if proposal.approvals.contains(&info.sender) {
+        return Err(ContractError::AlreadyApproved {
+            member: info.sender,
+        });
+    }
+    proposal.approvals.push(info.sender.clone());
+
+    let added = proposal.approvals.len() >= nomination.approvals as usize;
+    if added {
+        if let Some(max) = state.limits.max_candidates {
+            if state.candidates.len() >= max as usize {
+                return Err(ContractError::TooManyCandidates { max });
+            }
+        }
+        state
+            .candidates
+            .push(deps.api.canonical_address(&candidate)?);
+        config(&mut deps.storage).save(&state)?;
+        proposals(&mut deps.storage).remove(candidate.as_bytes());
+    } else {
+        proposals(&mut deps.storage).save(candidate.as_bytes(), &proposal)?;
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "approve_candidate"),
+            attr("candidate", candidate),
+            attr("member", info.sender),
+            attr("added", added),
+        ],
+        data: None,
+    })
+}
+
+/// has_role reports whether `sender` may act as `role`. The owner holds every
+/// role and admins hold the others as well.
+fn has_role(state: &State, sender: &HumanAddr, role: Role) -> bool {
+    *sender == state.owner
+        || state.roles.iter().any(|grant| {
+            grant.address == *sender && (grant.role == role || grant.role == Role::Admin)
+        })
+}
+
+pub fn try_propose_new_owner<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+    address: HumanAddr,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if info.sender != state.owner {
+        return Err(ContractError::Unauthorized {});
+    }
+    deps.api.canonical_address(&address)?;
+    state.pending_owner = Some(address.clone());
+    config(&mut deps.storage).save(&state)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "propose_new_owner"),
+            attr("address", address),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_accept_ownership<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if state.pending_owner.as_ref() != Some(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    state.owner = info.sender.clone();
+    state.pending_owner = None;
+    config(&mut deps.storage).save(&state)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "accept_ownership"),
+            attr("owner", info.sender),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_set_role<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+    address: HumanAddr,
+    role: Role,
+    granted: bool,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if !has_role(&state, &info.sender, Role::Admin) {
+        return Err(ContractError::Unauthorized {});
+    }
+    state
+        .roles
+        .retain(|grant| grant.address != address || grant.role != role);
+    if granted {
+        state.roles.push(RoleGrant {
+            address: address.clone(),
+            role,
+        });
+    }
+    config(&mut deps.storage).save(&state)?;
+    let action = if granted { "grant_role" } else { "revoke_role" };
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", action), attr("address", address)],
+        data: None,
+    })
+}
+
+/// try_archive stores the summary of a finalized election and prunes the
+/// ballots, their index and the tally rounds. A spawned election hands the
+/// summary to its factory as well.
+pub fn try_archive<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if !has_role(&state, &info.sender, Role::ElectionManager) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if state.archived {
+        return Err(ContractError::AlreadyArchived {});
+    }
+    let stored = result_read(&deps.storage)
+        .may_load()?
+        .ok_or(ContractError::NotFinalized {})?;
+    if let Some(dispute) = &state.dispute {
+        let end = stored.finalized_at + dispute.window;
+        if env.block.height <= end {
+            return Err(ContractError::DisputeWindowOpen { end });
+        }
+    }
+    // claims are paid against the ballots, so whatever the voters left
+    // unclaimed goes back to the owner before the ballots are dropped
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    let unclaimed = books.bucket(FundBucket::RewardPool);
+    if let Some(reward) = &state.participation_reward {
+        let claimed = reward_claims_read(&deps.storage)
+            .range(None, None, Order::Ascending)
+            .count();
+        let expired = reward.claim_window.map_or(false, |window| {
+            env.block.height > stored.finalized_at + window
+        });
+        if !unclaimed.is_empty() && claimed < state.votes.len() && !expired {
+            return Err(ContractError::RewardsUnclaimed {});
+        }
+    }
+    let mut messages = vec![];
+    if !unclaimed.is_empty() {
+        books.withdraw(FundBucket::RewardPool, &unclaimed)?;
+        ledger(&mut deps.storage).save(&books)?;
+        messages.push(
+            BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: state.owner.clone(),
+                amount: unclaimed,
+            }
+            .into(),
+        );
+    }
+
+    let counters = stats_read(&deps.storage).may_load()?.unwrap_or_default();
+    let summary = ArchivedResult {
+        winners: stored.winners,
+        standings: stored.standings,
+        ballots: counters.ballots,
+        unique_voters: counters.unique_voters,
+        finalized_at: stored.finalized_at,
+        archived_at: env.block.height,
+    };
+    for vote in std::mem::take(&mut state.votes) {
+        index_vote(&mut deps.storage, &vote, false)?;
+    }
+    if let Some(runoff) = state.runoff.as_mut() {
+        runoff.votes.clear();
+    }
+    tally_rounds(&mut deps.storage).remove();
+    state.archived = true;
+    config(&mut deps.storage).save(&state)?;
+    archives(&mut deps.storage).save(&0u64.to_be_bytes(), &summary)?;
+
+    if let Some(factory) = &state.factory {
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: factory.address.clone(),
+                msg: to_binary(&HandleMsg::RecordArchive {
+                    spawn_id: factory.spawn_id,
+                    summary,
+                })?,
+                send: vec![],
+            }
+            .into(),
+        );
+    }
+    Ok(HandleResponse {
+        messages,
+        attributes: vec![attr("action", "archive")],
+        data: None,
+    })
+}
+
+/// record_key is the key of a voter's ballot records, `raw` being the key of
+/// an election that is not pseudonymous
+fn record_key(state: &State, raw: &[u8]) -> Vec<u8> {
+    match &state.voter_salt {
+        Some(salt) => {
+            let mut preimage = salt.to_vec();
+            preimage.extend_from_slice(raw);
+            sha256(&preimage).to_vec()
+        }
+        None => raw.to_vec(),
+    }
+}
+
+/// voter_key is the key `voter` is stored under in the ballots and the
+/// records naming their voter
+fn voter_key<A: Api>(api: &A, state: &State, voter: &HumanAddr) -> StdResult<CanonicalAddr> {
+    let raw = api.canonical_address(voter)?;
+    Ok(CanonicalAddr(Binary(record_key(state, raw.as_slice()))))
+}
+
+pub fn try_scrub<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    limit: Option<u32>,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    if !has_role(&state, &info.sender, Role::ElectionManager) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !state.archived {
+        return Err(ContractError::NotArchived {});
+    }
+    let limit = limit.map_or(usize::MAX, |limit| limit as usize);
+    // contributions to a campaign that won are paid out with its escrow,
+    // the others stay until they are refunded
+    let mut won = vec![];
+    for candidate in &state.candidates {
+        if let Ok(true) = campaign_won(&deps.storage, &deps.api, &env, candidate) {
+            won.push(candidate);
+        }
+    }
+    let storage = &mut deps.storage;
+    // the roster and the voter history stay, as `GetHistory` answers from them
+    // for the history rules of later elections, and so do locks until unlocked
+    let mut removed = scrub(relayers(storage), limit, |_| false)?;
+    removed += scrub(proxies(storage), limit - removed, |_| false)?;
+    removed += scrub(cohort_members(storage), limit - removed, |_| false)?;
+    removed += scrub(voter_weights(storage), limit - removed, |_| false)?;
+    removed += scrub(reward_claims(storage), limit - removed, |_| false)?;
+    removed += scrub(last_action(storage), limit - removed, |_| false)?;
+    removed += scrub(nft_votes(storage), limit - removed, |_| false)?;
+    removed += scrub(ballot_payers(storage), limit - removed, |_| false)?;
+    removed += scrub(question_ballots(storage), limit - removed, |_| false)?;
+    removed += scrub(referendum_ballots(storage), limit - removed, |_| false)?;
+    for candidate in &won {
+        removed += scrub(contributions(storage, candidate), limit - removed, |_| {
+            false
+        })?;
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", "scrub"), attr("removed", removed)],
+        data: None,
+    })
+}
+
+/// scrub removes up to `limit` entries of `bucket` that are not kept
+fn scrub<S: Storage, T: Serialize + DeserializeOwned>(
+    mut bucket: Bucket<'_, S, T>,
+    limit: usize,
+    keep: impl Fn(&T) -> bool,
+) -> StdResult<usize> {
+    let keys = bucket
+        .range(None, None, Order::Ascending)
+        .filter_map(|item| match item {
+            Ok((key, value)) if !keep(&value) => Some(Ok(key)),
+            Ok(_) => None,
+            Err(err) => Some(Err(err)),
+        })
+        .take(limit)
+        .collect::<StdResult<Vec<_>>>()?;
+    for key in &keys {
+        bucket.remove(key);
+    }
+    Ok(keys.len())
+}
+
+/// try_record_archive stores the summary reported by a spawned election
+pub fn try_record_archive<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+    spawn_id: u64,
+    summary: ArchivedResult,
+) -> Result<HandleResponse, ContractError> {
+    let elections = spawned_read(&deps.storage).may_load()?.unwrap_or_default();
+    if !elections
+        .iter()
+        .any(|e| e.spawn_id == spawn_id && e.address.as_ref() == Some(&info.sender))
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+    archives(&mut deps.storage).save(&spawn_id.to_be_bytes(), &summary)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", "record_archive"), attr("spawn_id", spawn_id)],
+        data: None,
+    })
+}
+
+pub fn try_mark_oracle_compromised<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if !has_role(&state, &info.sender, Role::Pauser) && !state.guardians.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if oracle_address(&state).is_none() {
+        return Err(ContractError::NoOracle {});
+    }
+    state.oracle_compromised = true;
+    config(&mut deps.storage).save(&state)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "mark_oracle_compromised"),
+            attr("sender", info.sender),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_replace_oracle<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+    address: HumanAddr,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if !has_role(&state, &info.sender, Role::Admin) {
+        return Err(ContractError::Unauthorized {});
+    }
+    match &mut state.stake_weight {
+        Some(StakeWeight::Contract {
+            address: oracle, ..
+        })
+        | Some(StakeWeight::Group {
+            address: oracle, ..
+        })
+        | Some(StakeWeight::Cw20 { address: oracle })
+        | Some(StakeWeight::Adapter { address: oracle }) => *oracle = address.clone(),
+        _ => return Err(ContractError::NoOracle {}),
+    }
+    state.oracle_compromised = false;
+    config(&mut deps.storage).save(&state)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", "replace_oracle"), attr("oracle", address)],
+        data: None,
+    })
+}
+
+/// locked_denom is the denom voters lock, if ballots are weighted by locked tokens
+fn locked_denom(state: &State) -> Result<&str, ContractError> {
+    match &state.stake_weight {
+        Some(StakeWeight::Locked { denom }) => Ok(denom),
+        _ => Err(ContractError::LockingDisabled {}),
+    }
+}
+
+pub fn try_lock<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let denom = locked_denom(&state)?;
+    let end = voting_end(&state);
+    if env.block.height > end {
+        return Err(ContractError::LockingClosed { end });
+    }
+    let amount: Uint128 = info
+        .sent_funds
+        .iter()
+        .filter(|coin| coin.denom == denom)
+        .map(|coin| coin.amount)
+        .sum();
+    if amount.is_zero() {
+        return Err(ContractError::NothingToLock {
+            denom: denom.to_string(),
+        });
+    }
+    let key = voter_key(&deps.api, &state, &info.sender)?;
+    let locked = locks_read(&deps.storage)
+        .may_load(key.as_slice())?
+        .unwrap_or_default();
+    locks(&mut deps.storage).save(key.as_slice(), &(locked + amount))?;
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    books.deposit(FundBucket::Locked, &[coin(amount.u128(), denom)]);
+    ledger(&mut deps.storage).save(&books)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "lock"),
+            attr("voter", info.sender),
+            attr("amount", amount),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_unlock<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let denom = locked_denom(&state)?;
+    let end = voting_end(&state);
+    if env.block.height <= end {
+        return Err(ContractError::ElectionNotEnded { end });
+    }
+    let key = voter_key(&deps.api, &state, &info.sender)?;
+    let locked = locks_read(&deps.storage)
+        .may_load(key.as_slice())?
+        .ok_or_else(|| ContractError::NothingLocked {
+            voter: info.sender.clone(),
+        })?;
+    locks(&mut deps.storage).remove(key.as_slice());
+    let amount = vec![coin(locked.u128(), denom)];
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    books.withdraw(FundBucket::Locked, &amount)?;
+    ledger(&mut deps.storage).save(&books)?;
+    Ok(HandleResponse {
+        messages: vec![BankMsg::Send {
+            from_address: env.contract.address,
+            to_address: info.sender.clone(),
+            amount,
+        }
+        .into()],
+        attributes: vec![
+            attr("action", "unlock"),
+            attr("voter", info.sender),
+            attr("amount", locked),
+        ],
+        data: None,
+    })
+}
+
+fn query_locked_balance<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    voter: HumanAddr,
+) -> StdResult<LockedBalanceResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let denom = match &state.stake_weight {
+        Some(StakeWeight::Locked { denom }) => denom,
+        _ => return Err(StdError::not_found("locked stake")),
+    };
+    let locked = locks_read(&deps.storage)
+        .may_load(voter_key(&deps.api, &state, &voter)?.as_slice())?
+        .unwrap_or_default();
+    Ok(LockedBalanceResponse {
+        voter,
+        locked: coin(locked.u128(), denom),
+        until: voting_end(&state),
+    })
+}
+
+fn query_proxy<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    principal: HumanAddr,
+) -> StdResult<ProxyResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let grant = proxies_read(&deps.storage)
+        .may_load(voter_key(&deps.api, &state, &principal)?.as_slice())?
+        .ok_or_else(|| StdError::not_found("proxy"))?;
+    Ok(ProxyResponse {
+        principal,
+        proxy: grant.proxy,
+        expires: grant.expires,
+    })
+}
+
+/// oracle_address is the contract ballots are weighted by, if any
+fn oracle_address(state: &State) -> Option<&HumanAddr> {
+    match &state.stake_weight {
+        Some(StakeWeight::Contract { address, .. })
+        | Some(StakeWeight::Group { address, .. })
+        | Some(StakeWeight::Cw20 { address })
+        | Some(StakeWeight::Adapter { address }) => Some(address),
+        _ => None,
+    }
+}
+
+pub fn try_update_period<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    start: u64,
+    end: u64,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if !has_role(&state, &info.sender, Role::ElectionManager) {
+        return Err(ContractError::Unauthorized {});
+    }
+    if env.block.height >= state.start {
+        return Err(ContractError::ElectionStarted { start: state.start });
+    }
+    if start <= env.block.height || end <= start {
+        return Err(ContractError::InvalidPeriod { start, end });
+    }
+    // cohort windows have to stay inside the voting period
+    if let Some(cohort) = state
+        .cohorts
+        .iter()
+        .find(|c| c.start < start || c.end > end)
+    {
+        return Err(ContractError::InvalidCohort {
+            name: cohort.name.clone(),
+        });
+    }
+    state.start = start;
+    state.end = end;
+    config(&mut deps.storage).save(&state)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "update_period"),
+            attr("start", start),
+            attr("end", end),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_set_paused<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+    paused: bool,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if !has_role(&state, &info.sender, Role::Pauser) && !state.guardians.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    state.paused = paused;
+    config(&mut deps.storage).save(&state)?;
+    let action = if paused { "pause" } else { "unpause" };
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", action), attr("sender", info.sender)],
+        data: None,
+    })
+}
+
+pub fn try_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    candidate: HumanAddr,
+    proof: Option<MerkleProof>,
+    answers: Option<Vec<Answer>>,
+) -> Result<HandleResponse, ContractError> {
+    if let Some(res) = report_stale(deps, &env)? {
+        return Ok(res);
+    }
+    if let Some(proof) = proof {
+        prove_membership(deps, &env, &info.sender, &proof)?;
+    }
+    apply_ballot(deps, env, info.sender, Ballot::Vote { candidate }, answers)
+}
+
+pub fn try_vote_as_contract<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    policy: String,
+    ballot: Ballot,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    if !state.allow_contract_votes {
+        return Err(ContractError::ContractVotesDisabled {});
+    }
+    if policy.is_empty() || policy.len() > MAX_POLICY_LENGTH {
+        return Err(ContractError::InvalidPolicy {
+            max: MAX_POLICY_LENGTH,
+        });
+    }
+    if let Some(res) = report_stale(deps, &env)? {
+        return Ok(res);
+    }
+    let mut res = apply_ballot(deps, env, info.sender.clone(), ballot, None)?;
+    res.attributes.extend(vec![
+        attr("action", "vote_as_contract"),
+        attr("voter", info.sender),
+        attr("on_behalf_of_policy", policy),
+    ]);
+    Ok(res)
+}
+
+pub fn try_vote_ranked<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    ranking: Vec<HumanAddr>,
+) -> Result<HandleResponse, ContractError> {
+    if let Some(res) = report_stale(deps, &env)? {
+        return Ok(res);
+    }
+    apply_ballot(deps, env, info.sender, Ballot::RankedVote { ranking }, None)
+}
+
+pub fn try_vote_split<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    shares: Vec<Share>,
+) -> Result<HandleResponse, ContractError> {
+    if let Some(res) = report_stale(deps, &env)? {
+        return Ok(res);
+    }
+    apply_ballot(deps, env, info.sender, Ballot::SplitVote { shares }, None)
+}
+
+pub fn try_batch_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    votes: Vec<BallotEntry>,
+) -> Result<HandleResponse, ContractError> {
+    if votes.len() > MAX_BATCH_SIZE {
+        return Err(ContractError::BatchTooLarge {
+            max: MAX_BATCH_SIZE,
+        });
+    }
+    if let Some(res) = report_stale(deps, &env)? {
+        return Ok(res);
+    }
+    let mut attributes = vec![attr("action", "batch_vote")];
+    let mut messages = vec![];
+    let mut accepted = 0;
+    for (i, entry) in votes.into_iter().enumerate() {
+        let status = match batch_entry(deps, &env, &info.sender, entry) {
+            Ok(Some(res)) => {
+                accepted += 1;
+                messages.extend(res.messages);
+                "ok".to_string()
+            }
+            Ok(None) => "duplicate".to_string(),
+            Err(err) => format!("failed: {}", err),
+        };
+        attributes.push(attr(format!("entry_{}", i), status));
+    }
+    attributes.insert(1, attr("accepted", accepted));
+    Ok(HandleResponse {
+        messages,
+        attributes,
+        data: None,
+    })
+}
+
+/// batch_entry casts one ballot of a batch. It returns none without casting
+/// if the entry's idempotency key was already processed for `sender`.
+fn batch_entry<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    sender: &HumanAddr,
+    entry: BallotEntry,
+) -> Result<Option<HandleResponse>, ContractError> {
+    if let Some(key) = &entry.idempotency_key {
+        if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LENGTH {
+            return Err(ContractError::InvalidIdempotencyKey {
+                max: MAX_IDEMPOTENCY_KEY_LENGTH,
+            });
+        }
+    }
+    let state = config_read(&deps.storage).load()?;
+    let sender_key = voter_key(&deps.api, &state, sender)?;
+    if let Some(key) = &entry.idempotency_key {
+        if processed_keys_read(&deps.storage, &sender_key)
+            .may_load(key.as_bytes())?
+            .is_some()
+        {
+            return Ok(None);
+        }
+    }
+    if &entry.voter != sender
+        && !relays_for(&deps.storage, &deps.api, &state, &entry.voter, sender)?
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+    let res = apply_ballot(deps, env.clone(), entry.voter, entry.ballot, None)?;
+    if let Some(key) = &entry.idempotency_key {
+        processed_keys(&mut deps.storage, &sender_key).save(key.as_bytes(), &env.block.height)?;
+    }
+    Ok(Some(res))
+}
+
+pub fn try_register_voter<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let config = match state.registration.clone() {
+        Some(config) => config,
+        None => return Err(ContractError::Unauthorized {}),
+    };
+    if env.block.height < config.start || env.block.height > config.end {
+        return Err(ContractError::RegistrationClosed {
+            begin: config.start,
+            end: config.end,
+        });
+    }
+    if let Some(snapshot) = roll_snapshot_read(&deps.storage).may_load()? {
+        return Err(ContractError::SnapshotTaken {
+            height: snapshot.height,
+        });
+    }
+    let voter = info.sender;
+    let key = voter_key(&deps.api, &state, &voter)?;
+    if registered_read(&deps.storage)
+        .may_load(key.as_slice())?
+        .is_some()
+    {
+        return Err(ContractError::AlreadyRegistered { voter });
+    }
+    let bond = match config.bond {
+        Some(required) => {
+            let paid = info
+                .sent_funds
+                .iter()
+                .find(|c| c.denom == required.denom)
+                .map_or(Uint128::zero(), |c| c.amount);
+            if paid < required.amount {
+                return Err(ContractError::InsufficientBond {
+                    denom: required.denom,
+                    amount: required.amount,
+                });
+            }
+            let bond = vec![Coin {
+                denom: required.denom,
+                amount: paid,
+            }];
+            let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+            books.deposit(FundBucket::Deposits, &bond);
+            ledger(&mut deps.storage).save(&books)?;
+            bond
+        }
+        None => vec![],
+    };
+    registered(&mut deps.storage).save(
+        key.as_slice(),
+        &Registration {
+            height: env.block.height,
+            bond,
+        },
+    )?;
+    let mut counters = stats_read(&deps.storage).may_load()?.unwrap_or_default();
+    counters.registered += 1;
+    stats(&mut deps.storage).save(&counters)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![attr("action", "register_voter"), attr("voter", voter)],
+        data: None,
+    })
+}
+
+pub fn try_withdraw_candidacy<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    let end = voting_end(&state);
+    if env.block.height > end {
+        return Err(ContractError::WithdrawalClosed { end });
+    }
+    let candidate = deps.api.canonical_address(&info.sender)?;
+    if !state.candidates.contains(&candidate) {
+        return Err(ContractError::UnknownCandidate {
+            candidate: info.sender,
+        });
+    }
+    if state.withdrawn.contains(&candidate) {
+        return Err(ContractError::CandidateWithdrawn {
+            candidate: info.sender,
+        });
+    }
+
+    let redirect = state.withdrawal_policy == WithdrawalPolicy::Redirect;
+    // a split ballot gives the withdrawn share to its other candidates,
+    // cumulative points stay where the voter put them
+    let split = !matches!(state.voting_system, VotingSystem::Cumulative { .. });
+    let mut discarded = vec![];
+    for mut vote in std::mem::take(&mut state.votes) {
+        index_vote(&mut deps.storage, &vote, false)?;
+        vote.ranking.retain(|c| c != &candidate);
+        vote.allocations.retain(|a| a.candidate != candidate);
+        if split {
+            apportion(vote.weight, &mut vote.allocations);
+        }
+        if vote.candidate == candidate {
+            let next = match vote.allocations.first() {
+                Some(allocation) => Some(allocation.candidate.clone()),
+                None if redirect => vote.ranking.first().cloned(),
+                None => None,
+            };
+            match next {
+                Some(next) => vote.candidate = next,
+                None => {
+                    discarded.push(vote.voter);
+                    continue;
+                }
+            }
+        }
+        index_vote(&mut deps.storage, &vote, true)?;
+        state.votes.push(vote);
+    }
+    state.withdrawn.push(candidate);
+    forfeit_ballot_fees(&mut deps.storage, &discarded)?;
+
+    let mut counters = stats_read(&deps.storage).may_load()?.unwrap_or_default();
+    counters.unique_voters = counters
+        .unique_voters
+        .checked_sub(discarded.len() as u64)
+        .ok_or_else(|| StdError::generic_err("voter count underflow"))?;
+    counters.totals = vec![];
+    for vote in &state.votes {
+        add_vote_totals(&mut counters, vote)?;
+    }
+    stats(&mut deps.storage).save(&counters)?;
+    config(&mut deps.storage).save(&state)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "withdraw_candidacy"),
+            attr("candidate", info.sender),
+            attr("discarded", discarded.len()),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_withdraw_bond<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    if result_read(&deps.storage).may_load()?.is_none() {
+        return Err(ContractError::NotFinalized {});
+    }
+    let state = config_read(&deps.storage).load()?;
+    let key = voter_key(&deps.api, &state, &info.sender)?;
+    let mut registration = registered_read(&deps.storage)
+        .may_load(key.as_slice())?
+        .ok_or_else(|| ContractError::NotRegistered {
+            voter: info.sender.clone(),
+        })?;
+    let bond = std::mem::take(&mut registration.bond);
+    if bond.is_empty() {
+        return Ok(HandleResponse::default());
+    }
+    registered(&mut deps.storage).save(key.as_slice(), &registration)?;
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    books.withdraw(FundBucket::Deposits, &bond)?;
+    ledger(&mut deps.storage).save(&books)?;
+    Ok(HandleResponse {
+        messages: vec![BankMsg::Send {
+            from_address: env.contract.address,
+            to_address: info.sender,
+            amount: bond,
+        }
+        .into()],
+        attributes: vec![attr("action", "withdraw_bond")],
+        data: None,
+    })
+}
+
+pub fn try_claim_ballot_fee<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    if result_read(&deps.storage).may_load()?.is_none() {
+        return Err(ContractError::NotFinalized {});
+    }
+    let state = config_read(&deps.storage).load()?;
+    let voter = voter_key(&deps.api, &state, &info.sender)?;
+    let fee = ballot_fees_read(&deps.storage)
+        .may_load(voter.as_slice())?
+        .ok_or_else(|| ContractError::NoBallotFee {
+            voter: info.sender.clone(),
+        })?;
+    ballot_fees(&mut deps.storage).remove(voter.as_slice());
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    books.withdraw(FundBucket::BallotFees, std::slice::from_ref(&fee))?;
+    ledger(&mut deps.storage).save(&books)?;
+    Ok(HandleResponse {
+        messages: vec![BankMsg::Send {
+            from_address: env.contract.address,
+            to_address: info.sender,
+            amount: vec![fee],
+        }
+        .into()],
+        attributes: vec![attr("action", "claim_ballot_fee")],
+        data: None,
+    })
+}
+
+pub fn try_claim_reward<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let reward = state
+        .participation_reward
+        .as_ref()
+        .ok_or(ContractError::NoParticipationReward {})?;
+    match result_read(&deps.storage).may_load()? {
+        None => return Err(ContractError::NotFinalized {}),
+        Some(stored) if stored.vetoed_at.is_some() => return Err(ContractError::Vetoed {}),
+        Some(stored) if stored.invalid => return Err(ContractError::ElectionInvalid {}),
+        Some(stored) => {
+            if let Some(window) = reward.claim_window {
+                let end = stored.finalized_at + window;
+                if env.block.height > end {
+                    return Err(ContractError::ClaimWindowClosed { end });
+                }
+            }
+        }
+    }
+    let voter = info.sender;
+    let voter_raw = voter_key(&deps.api, &state, &voter)?;
+    if reward_claims_read(&deps.storage)
+        .may_load(voter_raw.as_slice())?
+        .is_some()
+    {
+        return Err(ContractError::RewardAlreadyClaimed { voter });
+    }
+    let ballot = state
+        .votes
+        .iter()
+        .find(|vote| vote.voter == voter_raw)
+        .ok_or_else(|| ContractError::NoRewardShare {
+            voter: voter.clone(),
+        })?;
+    // shares are taken from the pool as funded, so the order of claims does not matter
+    let (share, total) = match reward.split {
+        RewardSplit::Equal => (1, state.votes.len() as u128),
+        RewardSplit::Weight => (
+            u128::from(ballot.weight),
+            state.votes.iter().map(|vote| u128::from(vote.weight)).sum(),
+        ),
+    };
+    let amount: Vec<Coin> = reward
+        .pool
+        .iter()
+        .map(|coin| Coin {
+            denom: coin.denom.clone(),
+            amount: coin.amount.multiply_ratio(share, total),
+        })
+        .filter(|coin| !coin.amount.is_zero())
+        .collect();
+    reward_claims(&mut deps.storage).save(voter_raw.as_slice(), &env.block.height)?;
+    let mut messages = vec![];
+    if !amount.is_empty() {
+        let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+        books.withdraw(FundBucket::RewardPool, &amount)?;
+        ledger(&mut deps.storage).save(&books)?;
+        messages.push(
+            BankMsg::Send {
+                from_address: env.contract.address,
+                to_address: voter.clone(),
+                amount,
+            }
+            .into(),
+        );
+    }
+    Ok(HandleResponse {
+        messages,
+        attributes: vec![attr("action", "claim_reward"), attr("voter", voter)],
+        data: None,
+    })
+}
+
+/// relay_depth is the longest relayer chain a batch entry may follow
+fn relay_depth(state: &State) -> u32 {
+    state
+        .candidate_rules
+        .as_ref()
+        .and_then(|rules| rules.max_relay_depth)
+        .unwrap_or(1)
+}
+
+/// relays_for reports whether `sender` is reached within `depth` hops of
+/// `voter`'s relayer chain
+fn relays_for<S: Storage, A: Api>(
+    storage: &S,
+    api: &A,
+    state: &State,
+    voter: &HumanAddr,
+    sender: &HumanAddr,
+) -> StdResult<bool> {
+    let mut current = voter.clone();
+    for _ in 0..relay_depth(state) {
+        let key = voter_key(api, state, &current)?;
+        match relayers_read(storage).may_load(key.as_slice())? {
+            Some(relayer) if &relayer == sender => return Ok(true),
+            Some(relayer) => current = relayer,
+            None => return Ok(false),
+        }
+    }
+    Ok(false)
+}
+
+pub fn try_set_relayer<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+    relayer: Option<HumanAddr>,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    if let (Some(rules), Some(relayer)) = (&state.candidate_rules, &relayer) {
+        if rules.no_candidate_relayers
+            && state
+                .candidates
+                .contains(&deps.api.canonical_address(relayer)?)
+        {
+            return Err(ContractError::CandidateRelayer {
+                relayer: relayer.clone(),
+            });
+        }
+        // the new link plus the relayer's own chain must fit the depth
+        let max = relay_depth(&state);
+        let mut hops = 1;
+        let mut current = relayer.clone();
+        while let Some(next) = relayers_read(&deps.storage)
+            .may_load(voter_key(&deps.api, &state, &current)?.as_slice())?
+        {
+            hops += 1;
+            if hops > max || next == info.sender {
+                return Err(ContractError::RelayDepthExceeded { max });
+            }
+            current = next;
+        }
+    }
+    let key = voter_key(&deps.api, &state, &info.sender)?;
+    match relayer {
+        Some(relayer) => relayers(&mut deps.storage).save(key.as_slice(), &relayer)?,
+        None => relayers(&mut deps.storage).remove(key.as_slice()),
+    }
+    Ok(HandleResponse::default())
+}
+
+pub fn try_authorize_proxy<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    proxy: HumanAddr,
+    expires: u64,
+) -> Result<HandleResponse, ContractError> {
+    if proxy == info.sender {
+        return Err(ContractError::InvalidProxy {});
+    }
+    if expires < env.block.height {
+        return Err(ContractError::ProxyExpired { expires });
+    }
+    let grant = ProxyGrant {
+        proxy: proxy.clone(),
+        expires,
+    };
+    let state = config_read(&deps.storage).load()?;
+    let key = voter_key(&deps.api, &state, &info.sender)?;
+    proxies(&mut deps.storage).save(key.as_slice(), &grant)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "authorize_proxy"),
+            attr("principal", info.sender),
+            attr("proxy", proxy),
+            attr("expires", expires),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_revoke_proxy<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let key = voter_key(&deps.api, &state, &info.sender)?;
+    let grant = proxies_read(&deps.storage)
+        .may_load(key.as_slice())?
+        .ok_or_else(|| ContractError::NotProxy {
+            principal: info.sender.clone(),
+        })?;
+    proxies(&mut deps.storage).remove(key.as_slice());
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "revoke_proxy"),
+            attr("principal", info.sender),
+            attr("proxy", grant.proxy),
+        ],
+        data: None,
+    })
+}
+
+/// try_proxy_vote casts the principal's ballot and uses up the grant
+pub fn try_proxy_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    principal: HumanAddr,
+    ballot: Ballot,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let key = voter_key(&deps.api, &state, &principal)?;
+    let grant = match proxies_read(&deps.storage).may_load(key.as_slice())? {
+        Some(grant) if grant.proxy == info.sender => grant,
+        _ => return Err(ContractError::NotProxy { principal }),
+    };
+    if env.block.height > grant.expires {
+        return Err(ContractError::ProxyExpired {
+            expires: grant.expires,
+        });
+    }
+    if let Some(res) = report_stale(deps, &env)? {
+        return Ok(res);
+    }
+    let mut res = apply_ballot(deps, env, principal.clone(), ballot, None)?;
+    proxies(&mut deps.storage).remove(key.as_slice());
+    res.attributes.extend(vec![
+        attr("action", "proxy_vote"),
+        attr("principal", principal),
+        attr("proxy", info.sender),
+    ]);
+    Ok(res)
+}
+
+/// apply_ballot checks the shape of `ballot` against the voting system and
+/// casts it for `voter` together with its `answers` to the questions.
+/// Nothing is written unless the ballot is accepted.
+fn apply_ballot<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    voter: HumanAddr,
+    ballot: Ballot,
+    answers: Option<Vec<Answer>>,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    if state.referendum.is_some() {
+        return Err(ContractError::ReferendumBallotRequired {});
+    }
+    if state.runoff.is_some() {
+        return match ballot {
+            Ballot::Vote { .. } if answers.is_some() => {
+                Err(ContractError::RunoffBallotNotSupported {})
+            }
+            Ballot::Vote { candidate } => cast_runoff_ballot(deps, env, state, voter, candidate),
+            _ => Err(ContractError::RunoffBallotNotSupported {}),
+        };
+    }
+    match ballot {
+        Ballot::Vote { candidate } => {
+            if let VotingSystem::Cumulative { points } = state.voting_system {
+                let shares = vec![Share {
+                    candidate: candidate.clone(),
+                    share: u64::from(points),
+                }];
+                return cast_ballot(deps, env, voter, candidate, vec![], shares, answers);
+            }
+            // a single choice is the shortest possible ranking
+            let ranking = if state.voting_system.is_ranked() {
+                vec![candidate.clone()]
+            } else {
+                vec![]
+            };
+            cast_ballot(deps, env, voter, candidate, ranking, vec![], answers)
+        }
+        Ballot::RankedVote { ranking } => {
+            if !state.voting_system.is_ranked() {
+                return Err(ContractError::RankedBallotNotSupported {});
+            }
+            let candidate = ranking
+                .first()
+                .cloned()
+                .ok_or(ContractError::EmptyRanking {})?;
+            cast_ballot(deps, env, voter, candidate, ranking, vec![], answers)
+        }
+        Ballot::SplitVote { shares } => {
+            if state.voting_system.is_ranked() {
+                return Err(ContractError::SplitBallotNotSupported {});
+            }
+            let shares: Vec<Share> = shares.into_iter().filter(|s| s.share > 0).collect();
+            if let VotingSystem::Cumulative { points } = state.voting_system {
+                let total: u128 = shares.iter().map(|s| u128::from(s.share)).sum();
+                if total > u128::from(points) {
+                    return Err(ContractError::PointsExceeded { budget: points });
+                }
+            }
+            for (i, share) in shares.iter().enumerate() {
+                if shares[..i].iter().any(|s| s.candidate == share.candidate) {
+                    return Err(ContractError::DuplicateShare {
+                        candidate: share.candidate.clone(),
+                    });
+                }
+            }
+            let candidate = shares
+                .first()
+                .map(|s| s.candidate.clone())
+                .ok_or(ContractError::EmptyShares {})?;
+            cast_ballot(deps, env, voter, candidate, vec![], shares, answers)
+        }
+    }
+}
+
+fn cast_ballot<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    voter: HumanAddr,
+    candidate: HumanAddr,
+    ranking: Vec<HumanAddr>,
+    shares: Vec<Share>,
+    answers: Option<Vec<Answer>>,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    check_voter(deps, &state, &env, &voter)?;
+    if let Some(CandidateRules {
+        no_self_vote: true, ..
+    }) = &state.candidate_rules
+    {
+        let mut named = std::iter::once(&candidate)
+            .chain(&ranking)
+            .chain(shares.iter().map(|s| &s.candidate));
+        if named.any(|c| c == &voter) {
+            return Err(ContractError::SelfVote { voter });
+        }
+    }
+    let ranking = if state.voting_system.is_ranked() {
+        validate_ranking(&deps.api, &state, &ranking)?
+    } else {
+        canonicalize_addrs(&deps.api, &ranking)?
+    };
+    if !state.voting_system.is_ranked() && state.write_ins.is_none() {
+        for candidate in std::iter::once(&candidate).chain(shares.iter().map(|s| &s.candidate)) {
+            if !state
+                .candidates
+                .contains(&deps.api.canonical_address(candidate)?)
+            {
+                return Err(ContractError::UnknownCandidate {
+                    candidate: candidate.clone(),
+                });
+            }
+        }
+    }
+    for candidate in std::iter::once(&candidate).chain(shares.iter().map(|s| &s.candidate)) {
+        if state
+            .withdrawn
+            .contains(&deps.api.canonical_address(candidate)?)
+        {
+            return Err(ContractError::CandidateWithdrawn {
+                candidate: candidate.clone(),
+            });
+        }
+    }
+    let voter_raw = voter_key(&deps.api, &state, &voter)?;
+    if state.votes.iter().any(|vote| vote.voter == voter_raw) {
+        return Err(ContractError::AlreadyVoted { voter });
+    }
+    // every check runs before the first write, so that a rejected ballot
+    // leaves no trace, in particular no used token
+    let looked_up = lookup_weight(deps, &state, &voter)?;
+    let weight = decayed_weight(&state, env.block.height, looked_up.0)?;
+    let token_id = unused_token(deps, &state, &voter)?;
+    let answered = match answers {
+        Some(answers) => Some((
+            count_answers(&deps.storage, &state, &answers, weight)?,
+            answers,
+        )),
+        None => None,
+    };
+    let vote = VoteInfo {
+        voter: voter_raw,
+        candidate: deps.api.canonical_address(&candidate)?,
+        ranking,
+        weight,
+        allocations: match state.voting_system {
+            VotingSystem::Cumulative { .. } => allot_points(&deps.api, weight, &shares)?,
+            _ => split_weight(&deps.api, weight, &shares)?,
+        },
+        token_id,
+    };
+    let mut counters = stats_read(&deps.storage).may_load()?.unwrap_or_default();
+    count_ballot(&mut counters, &state.limits, env.block.height)?;
+    count_voter(&state, &mut counters, env.block.height);
+    add_vote_totals(&mut counters, &vote)?;
+    if let Some(max) = state.limits.max_candidates {
+        let write_ins = counters
+            .totals
+            .iter()
+            .filter(|total| !state.candidates.contains(&total.candidate))
+            .count();
+        if state.candidates.len() + write_ins > max as usize {
+            return Err(ContractError::TooManyCandidates { max });
+        }
+    }
+    let mut attributes = vec![];
+    if let Some(end) = extend_deadline(&mut state, &mut counters, env.block.height) {
+        attributes.push(attr("extended_end", end));
+    }
+    cache_weight(&mut deps.storage, &env, &vote.voter, looked_up)?;
+    claim_token(&mut deps.storage, &vote.token_id, &vote.voter)?;
+    stats(&mut deps.storage).save(&counters)?;
+    index_vote(&mut deps.storage, &vote, true)?;
+    if let Some((tally, answers)) = answered {
+        question_tally(&mut deps.storage).save(&tally)?;
+        question_ballots(&mut deps.storage).save(vote.voter.as_slice(), &answers)?;
+    }
+    participants(&mut deps.storage).save(vote.voter.as_slice(), &env.block.height)?;
+    state.votes.push(vote);
+    config(&mut deps.storage).save(&state)?;
+    Ok(HandleResponse {
+        messages: receipt(&state, &env, voter, counters.ballots)?,
+        attributes,
+        data: None,
+    })
+}
+
+/// check_voter checks that `voter` may cast a ballot right now
+fn check_voter<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    state: &State,
+    env: &Env,
+    voter: &HumanAddr,
+) -> Result<(), ContractError> {
+    if state.halted {
+        return Err(ContractError::Halted {});
+    }
+    if state.paused {
+        return Err(ContractError::Paused {});
+    }
+    let key = voter_key(&deps.api, state, voter)?;
+    check_voting_period(&deps.storage, state, env, &key)?;
+    if state.registration.is_some()
+        && registered_read(&deps.storage)
+            .may_load(key.as_slice())?
+            .is_none()
+    {
+        return Err(ContractError::NotRegistered {
+            voter: voter.clone(),
+        });
+    }
+    match roll_snapshot_read(&deps.storage).may_load()? {
+        Some(snapshot) => check_eligibility(&deps.api, snapshot.eligibility.as_ref(), voter)?,
+        None => check_eligibility(&deps.api, state.eligibility.as_ref(), voter)?,
+    }
+    match &state.history {
+        Some(rule) => check_history(deps, env, rule, voter),
+        None => Ok(()),
+    }
+}
+
+/// check_history asks the earlier election named by `rule` about `voter`
+fn check_history<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+    rule: &HistoryRule,
+    voter: &HumanAddr,
+) -> Result<(), ContractError> {
+    let election = match rule {
+        HistoryRule::VotedIn { election } | HistoryRule::Registered { election } => election,
+    };
+    let record = if election == &env.contract.address {
+        query_history(deps, voter.clone())?
+    } else {
+        let msg = QueryMsg::GetHistory {
+            voter: voter.clone(),
+        };
+        deps.querier.query_wasm_smart(election, &msg)?
+    };
+    let found = match rule {
+        HistoryRule::VotedIn { .. } => record.voted_at.is_some(),
+        HistoryRule::Registered { .. } => record.registered,
+    };
+    if !found {
+        return Err(ContractError::NoHistory {
+            voter: voter.clone(),
+        });
+    }
+    Ok(())
+}
+
+pub fn try_referendum_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    choice: ReferendumChoice,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    if state.referendum.is_none() {
+        return Err(ContractError::NotAReferendum {});
+    }
+    let voter = info.sender;
+    check_voter(deps, &state, &env, &voter)?;
+    let ballot_key = voter_key(&deps.api, &state, &voter)?;
+    if referendum_ballots_read(&deps.storage)
+        .may_load(&ballot_key)?
+        .is_some()
+    {
+        return Err(ContractError::AlreadyVoted { voter });
+    }
+    let looked_up = lookup_weight(deps, &state, &voter)?;
+    let weight = decayed_weight(&state, env.block.height, looked_up.0)?;
+    let token_id = unused_token(deps, &state, &voter)?;
+    let mut counters = stats_read(&deps.storage).may_load()?.unwrap_or_default();
+    count_ballot(&mut counters, &state.limits, env.block.height)?;
+    count_voter(&state, &mut counters, env.block.height);
+    let mut tally = referendum_tally_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let total = match choice {
+        ReferendumChoice::Yes => &mut tally.yes,
+        ReferendumChoice::No => &mut tally.no,
+        ReferendumChoice::NoWithVeto => &mut tally.no_with_veto,
+        ReferendumChoice::Abstain => &mut tally.abstain,
+    };
+    *total = checked_add(*total, weight)?;
+    cache_weight(&mut deps.storage, &env, &ballot_key, looked_up)?;
+    claim_token(&mut deps.storage, &token_id, &ballot_key)?;
+    stats(&mut deps.storage).save(&counters)?;
+    referendum_tally(&mut deps.storage).save(&tally)?;
+    referendum_ballots(&mut deps.storage)
+        .save(&ballot_key, &ReferendumBallot { choice, weight })?;
+    participants(&mut deps.storage).save(ballot_key.as_slice(), &env.block.height)?;
+    Ok(HandleResponse {
+        messages: receipt(&state, &env, voter, counters.ballots)?,
+        attributes: vec![],
+        data: None,
+    })
+}
+
+/// count_answers checks `answers` against the election's questions and
+/// returns the question tally with them added at `weight`
+fn count_answers<S: Storage>(
+    storage: &S,
+    state: &State,
+    answers: &[Answer],
+    weight: u64,
+) -> Result<Vec<QuestionTally>, ContractError> {
+    if state.questions.is_empty() {
+        return Err(ContractError::NoQuestions {});
+    }
+    if answers.is_empty() {
+        return Err(ContractError::NoAnswers {});
+    }
+    let mut picks = vec![];
+    for (i, answer) in answers.iter().enumerate() {
+        let invalid = || ContractError::InvalidAnswer {
+            question: answer.question.clone(),
+        };
+        let index = state
+            .questions
+            .iter()
+            .position(|q| q.name == answer.question)
+            .ok_or_else(invalid)?;
+        let question = &state.questions[index];
+        let choices = answer
+            .choices
+            .iter()
+            .map(|choice| question.options.iter().position(|o| o == choice))
+            .collect::<Option<Vec<usize>>>()
+            .ok_or_else(invalid)?;
+        let count_ok = match question.method {
+            QuestionMethod::Plurality => choices.len() == 1,
+            QuestionMethod::Approval | QuestionMethod::Borda => !choices.is_empty(),
+        };
+        let repeated = choices
+            .iter()
+            .enumerate()
+            .any(|(j, choice)| choices[..j].contains(choice));
+        if !count_ok || repeated || answers[..i].iter().any(|a| a.question == answer.question) {
+            return Err(invalid());
+        }
+        picks.push((index, choices));
+    }
+
+    let weight = u128::from(weight);
+    let mut tally = question_tally_read(storage).may_load()?.unwrap_or_else(|| {
+        state
+            .questions
+            .iter()
+            .map(|q| QuestionTally {
+                question: q.name.clone(),
+                weights: vec![Uint128::zero(); q.options.len()],
+            })
+            .collect()
+    });
+    for (index, choices) in picks {
+        let question = &state.questions[index];
+        let n = question.options.len() as u128;
+        for (position, choice) in choices.into_iter().enumerate() {
+            let points = match question.method {
+                QuestionMethod::Borda => n - 1 - position as u128,
+                _ => 1,
+            };
+            let total = &mut tally[index].weights[choice];
+            *total = total
+                .u128()
+                .checked_add(points * weight)
+                .map(Uint128)
+                .ok_or_else(|| StdError::generic_err("tally overflow"))?;
+        }
+    }
+    Ok(tally)
+}
+
+/// question_outcomes ranks the options of every question by their weight
+fn question_outcomes<S: Storage>(
+    storage: &S,
+    questions: &[Question],
+) -> StdResult<Vec<QuestionOutcome>> {
+    let tally = question_tally_read(storage).may_load()?.unwrap_or_default();
+    let mut outcomes = vec![];
+    for question in questions {
+        let weights = tally
+            .iter()
+            .find(|t| t.question == question.name)
+            .map(|t| t.weights.clone())
+            .unwrap_or_default();
+        let mut totals: Vec<OptionTotal> = question
+            .options
+            .iter()
+            .enumerate()
+            .map(|(i, option)| OptionTotal {
+                option: option.clone(),
+                weight: weights.get(i).copied().unwrap_or_else(Uint128::zero),
+            })
+            .collect();
+        totals.sort_by_key(|total| std::cmp::Reverse(total.weight));
+        let winner = match &totals[..] {
+            [first, second, ..] if first.weight > second.weight => Some(first.option.clone()),
+            _ => None,
+        };
+        outcomes.push(QuestionOutcome {
+            question: question.name.clone(),
+            winner,
+            totals,
+        });
+    }
+    Ok(outcomes)
+}
+
+/// histogram_width is the number of blocks per histogram bucket
+fn histogram_width(state: &State) -> u64 {
+    let span = state.end.saturating_sub(state.start) + 1;
+    (span + HISTOGRAM_BUCKETS - 1) / HISTOGRAM_BUCKETS
+}
+
+/// extend_deadline counts a ballot at `height` against the anti-sniping rule
+/// and returns the new `end` if the ballot triggered an extension
+fn extend_deadline(state: &mut State, counters: &mut Stats, height: u64) -> Option<u64> {
+    let rule = state.anti_snipe.clone()?;
+    let window_start = state.end.saturating_sub(rule.window);
+    if height <= window_start {
+        return None;
+    }
+    counters
+        .late_blocks
+        .retain(|block| block.height > window_start);
+    match counters.late_blocks.last_mut() {
+        Some(block) if block.height == height => block.ballots += 1,
+        _ => counters
+            .late_blocks
+            .push(BlockBallots { height, ballots: 1 }),
+    }
+    let late: u64 = counters.late_blocks.iter().map(|block| block.ballots).sum();
+    let extended: u64 = state
+        .extensions
+        .iter()
+        .map(|e| e.end - e.previous_end)
+        .sum();
+    if Decimal::from_ratio(late, counters.ballots) <= rule.threshold
+        || extended + rule.extension > rule.max_extension
+    {
+        return None;
+    }
+    let previous_end = state.end;
+    state.end += rule.extension;
+    state.extensions.push(DeadlineExtension {
+        height,
+        previous_end,
+        end: state.end,
+    });
+    Some(state.end)
+}
+
+fn histogram_bucket(state: &State, height: u64) -> usize {
+    (height.saturating_sub(state.start) / histogram_width(state)) as usize
+}
+
+/// receipt mints the receipt of ballot number `ballot` to `voter`. It names
+/// the election and the height, not the choice.
+fn receipt(state: &State, env: &Env, voter: HumanAddr, ballot: u64) -> StdResult<Vec<CosmosMsg>> {
+    let collection = match &state.receipts {
+        Some(collection) => collection,
+        None => return Ok(vec![]),
+    };
+    let election = &env.contract.address;
+    let msg = Cw721HandleMsg::Mint(MintMsg {
+        token_id: format!("{}/{}", election, ballot),
+        owner: voter,
+        name: "Vote receipt".to_string(),
+        description: Some(format!(
+            "Voted in election {} at height {}",
+            election, env.block.height
+        )),
+        image: None,
+    });
+    Ok(vec![WasmMsg::Execute {
+        contract_addr: collection.clone(),
+        msg: to_binary(&msg)?,
+        send: vec![],
+    }
+    .into()])
+}
+
+/// count_ballot counts one more ballot cast at `height` within `limits`
+fn count_ballot(counters: &mut Stats, limits: &Limits, height: u64) -> Result<(), ContractError> {
+    if let Some(max) = limits.max_total_votes {
+        if counters.ballots >= max {
+            return Err(ContractError::BallotLimitReached { max });
+        }
+    }
+    if counters.block_height != height {
+        counters.block_height = height;
+        counters.block_ballots = 0;
+    }
+    if let Some(max) = limits.max_votes_per_block {
+        if counters.block_ballots >= max {
+            return Err(ContractError::BlockBallotLimitReached { max });
+        }
+    }
+    counters.ballots += 1;
+    counters.block_ballots += 1;
+    Ok(())
+}
+
+/// count_voter adds a first ballot of a voter to the turnout counters
+fn count_voter(state: &State, counters: &mut Stats, height: u64) {
+    counters.unique_voters += 1;
+    let bucket = histogram_bucket(state, height);
+    if counters.histogram.len() <= bucket {
+        counters.histogram.resize(bucket + 1, 0);
+    }
+    counters.histogram[bucket] += 1;
+}
+
+/// index_vote adds `vote` to or removes it from the candidate_votes index,
+/// the candidate counts and the leaderboard
+fn index_vote<S: Storage>(storage: &mut S, vote: &VoteInfo, add: bool) -> StdResult<()> {
+    let mut entries = vec![(&vote.candidate, vote.weight)];
+    if !vote.allocations.is_empty() {
+        entries = vote
+            .allocations
+            .iter()
+            .map(|allocation| (&allocation.candidate, allocation.weight))
+            .collect();
+    }
+    for (candidate, weight) in entries {
+        let mut index = candidate_votes(storage, candidate);
+        if add {
+            index.save(vote.voter.as_slice(), &weight)?;
+        } else {
+            index.remove(vote.voter.as_slice());
+        }
+        let mut counted = candidate_counts_read(storage)
+            .may_load(candidate.as_slice())?
+            .unwrap_or_default();
+        leaderboard(storage).remove(&leaderboard_key(counted.count, candidate));
+        if add {
+            counted.count += 1;
+            counted.weight = checked_add(counted.weight, weight)?;
+        } else {
+            counted.count = counted.count.saturating_sub(1);
+            counted.weight = (counted.weight - Uint128::from(weight))?;
+        }
+        candidate_counts(storage).save(candidate.as_slice(), &counted)?;
+        if counted.count > 0 {
+            leaderboard(storage).save(&leaderboard_key(counted.count, candidate), &counted)?;
+        }
+    }
+    Ok(())
+}
+
+fn add_vote_totals(counters: &mut Stats, vote: &VoteInfo) -> StdResult<()> {
+    if vote.allocations.is_empty() {
+        return add_total(counters, &vote.candidate, vote.weight);
+    }
+    for allocation in &vote.allocations {
+        add_total(counters, &allocation.candidate, allocation.weight)?;
+    }
+    Ok(())
+}
+
+fn add_total(counters: &mut Stats, candidate: &CanonicalAddr, weight: u64) -> StdResult<()> {
+    match counters
+        .totals
+        .iter_mut()
+        .find(|t| &t.candidate == candidate)
+    {
+        Some(total) => total.weight = checked_add(total.weight, weight)?,
+        None => counters.totals.push(CandidateTotal {
+            candidate: candidate.clone(),
+            weight: Uint128::from(weight),
+        }),
+    }
+    Ok(())
+}
+
+/// checked_add adds to a tally, failing instead of wrapping around
+fn checked_add(tally: Uint128, amount: u64) -> StdResult<Uint128> {
+    tally
+        .u128()
+        .checked_add(u128::from(amount))
+        .map(Uint128)
+        .ok_or_else(|| StdError::generic_err("tally overflow"))
+}
+
+/// cast_runoff_ballot records a vote in the runoff. Only voters of the first
+/// round take part, with the weight of their first ballot.
+fn cast_runoff_ballot<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    mut state: State,
+    voter: HumanAddr,
+    candidate: HumanAddr,
+) -> Result<HandleResponse, ContractError> {
+    if state.halted {
+        return Err(ContractError::Halted {});
+    }
+    if state.paused {
+        return Err(ContractError::Paused {});
+    }
+    let voter_raw = voter_key(&deps.api, &state, &voter)?;
+    let candidate_raw = deps.api.canonical_address(&candidate)?;
+    let weight = state
+        .votes
+        .iter()
+        .find(|vote| vote.voter == voter_raw)
+        .map(|vote| vote.weight)
+        .ok_or_else(|| ContractError::NotEligibleForRunoff {
+            voter: voter.clone(),
+        })?;
+    let runoff = state.runoff.as_ref().unwrap();
+    if env.block.height < runoff.start || env.block.height > runoff.end {
+        return Err(ContractError::NotAllowance {
+            begin: runoff.start,
+            end: runoff.end,
+        });
+    }
+    if !runoff.candidates.contains(&candidate_raw) {
+        return Err(ContractError::UnknownCandidate { candidate });
+    }
+    if runoff.votes.iter().any(|vote| vote.voter == voter_raw) {
+        return Err(ContractError::AlreadyVoted { voter });
+    }
+    let mut counters = stats_read(&deps.storage).may_load()?.unwrap_or_default();
+    count_ballot(&mut counters, &state.limits, env.block.height)?;
+    stats(&mut deps.storage).save(&counters)?;
+    state.runoff.as_mut().unwrap().votes.push(VoteInfo {
+        voter: voter_raw,
+        candidate: candidate_raw,
+        ranking: vec![],
+        weight,
+        allocations: vec![],
+        token_id: None,
+    });
+    config(&mut deps.storage).save(&state)?;
+    Ok(HandleResponse::default())
+}
+
+/// unused_token finds a token of the gating collection held by `voter` that
+/// has not voted yet, `claim_token` marks it as used. A token that changes
+/// hands after voting cannot be used again by its new holder.
+fn unused_token<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    state: &State,
+    voter: &HumanAddr,
+) -> Result<Option<String>, ContractError> {
+    let collection = match &state.nft_gate {
+        Some(collection) => collection,
+        None => return Ok(None),
+    };
+    let mut start_after = None;
+    let mut held_any = false;
+    loop {
+        let query = Cw721QueryMsg::Tokens {
+            owner: voter.clone(),
+            start_after,
+            limit: Some(TOKEN_PAGE_LIMIT),
+        };
+        let res: TokensResponse = deps.querier.query_wasm_smart(collection, &query)?;
+        held_any |= !res.tokens.is_empty();
+        for token_id in &res.tokens {
+            if nft_votes_read(&deps.storage)
+                .may_load(token_id.as_bytes())?
+                .is_none()
+            {
+                return Ok(Some(token_id.clone()));
+            }
+        }
+        if res.tokens.len() < TOKEN_PAGE_LIMIT as usize {
+            break;
+        }
+        start_after = res.tokens.last().cloned();
+    }
+    if held_any {
+        Err(ContractError::TokenAlreadyUsed {
+            voter: voter.clone(),
+        })
+    } else {
+        Err(ContractError::NotTokenHolder {
+            voter: voter.clone(),
+        })
+    }
+}
+
+fn claim_token<S: Storage>(
+    storage: &mut S,
+    token_id: &Option<String>,
+    voter: &CanonicalAddr,
+) -> StdResult<()> {
+    match token_id {
+        Some(token_id) => nft_votes(storage).save(token_id.as_bytes(), voter),
+        None => Ok(()),
+    }
+}
+
+/// split_weight divides `weight` in proportion to `shares`. Whatever is lost
+/// to rounding goes to the largest fractional parts, earlier shares first, so
+/// the allocations always add up to `weight`.
+fn split_weight<A: Api>(api: &A, weight: u64, shares: &[Share]) -> StdResult<Vec<SubAllocation>> {
+    if shares.iter().all(|s| s.share == 0) {
+        return Ok(vec![]);
+    }
+    let mut allocations = shares
+        .iter()
+        .map(|share| {
+            Ok(SubAllocation {
+                candidate: api.canonical_address(&share.candidate)?,
+                share: share.share,
+                weight: 0,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+    apportion(weight, &mut allocations);
+    Ok(allocations)
+}
+
+/// apportion sets the weights of `allocations` to the split of `weight` by
+/// their shares, see `split_weight`
+fn apportion(weight: u64, allocations: &mut [SubAllocation]) {
+    let total: u128 = allocations.iter().map(|a| u128::from(a.share)).sum();
+    if total == 0 {
+        return;
+    }
+    let mut remainders: Vec<(u128, usize)> = vec![];
+    for (i, allocation) in allocations.iter_mut().enumerate() {
+        let exact = u128::from(weight) * u128::from(allocation.share);
+        allocation.weight = (exact / total) as u64;
+        remainders.push((exact % total, i));
+    }
+    let assigned: u64 = allocations.iter().map(|a| a.weight).sum();
+    remainders.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, i) in remainders.into_iter().take((weight - assigned) as usize) {
+        allocations[i].weight += 1;
+    }
+}
+
+/// allot_points gives each candidate of a cumulative ballot `weight` per point
+fn allot_points<A: Api>(api: &A, weight: u64, shares: &[Share]) -> StdResult<Vec<SubAllocation>> {
+    shares
+        .iter()
+        .map(|share| {
+            Ok(SubAllocation {
+                candidate: api.canonical_address(&share.candidate)?,
+                share: share.share,
+                weight: weight.saturating_mul(share.share),
+            })
+        })
+        .collect()
+}
+
+/// cache_weight stores a weight `lookup_weight` just read from the stake, so
+/// that re-delegating during the election cannot change it
+fn cache_weight<S: Storage>(
+    storage: &mut S,
+    env: &Env,
+    voter: &CanonicalAddr,
+    (weight, fresh): (u64, bool),
+) -> StdResult<()> {
+    if !fresh {
+        return Ok(());
+    }
+    voter_weights(storage).save(
+        voter.as_slice(),
+        &VoterWeight {
+            weight,
+            height: env.block.height,
+        },
+    )
+}
+
+/// lookup_weight returns the weight `voter` would vote with and whether it
+/// was just read from the stake and still has to be cached
+fn lookup_weight<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    state: &State,
+    voter: &HumanAddr,
+) -> Result<(u64, bool), ContractError> {
+    let key = voter_key(&deps.api, state, voter)?;
+    if state.merkle_roll.is_some() {
+        // set by a `Vote` carrying a valid proof
+        return match voter_weights_read(&deps.storage).may_load(key.as_slice())? {
+            Some(cached) => Ok((cached.weight, false)),
+            None => Err(ContractError::NotEligible {
+                voter: voter.clone(),
+            }),
+        };
+    }
+    let stake_weight = match &state.stake_weight {
+        Some(stake_weight) => stake_weight,
+        None => return Ok((1, false)),
+    };
+    if let Some(cached) = voter_weights_read(&deps.storage).may_load(key.as_slice())? {
+        return Ok((cached.weight, false));
+    }
+    if state.oracle_compromised {
+        return match state.oracle_fallback {
+            OracleFallback::Pause => Err(ContractError::OracleCompromised {}),
+            OracleFallback::EqualWeight => Ok((1, false)),
+        };
+    }
+
+    let stake = match stake_weight {
+        StakeWeight::Native {} => {
+            let denom = deps.querier.query_bonded_denom()?;
+            deps.querier
+                .query_all_delegations(voter)?
+                .into_iter()
+                .filter(|delegation| delegation.amount.denom == denom)
+                .map(|delegation| delegation.amount.amount.u128())
+                .sum()
+        }
+        StakeWeight::Contract {
+            address,
+            snapshot_height,
+        } => {
+            let query = StakingQueryMsg::StakedBalanceAtHeight {
+                address: voter.clone(),
+                height: Some(snapshot_height.unwrap_or(state.start)),
+            };
+            let res: StakedBalanceAtHeightResponse =
+                deps.querier.query_wasm_smart(address, &query)?;
+            res.balance.u128()
+        }
+        StakeWeight::Group { address, snapshot } => {
+            let frozen = roll_snapshot_read(&deps.storage).may_load()?;
+            let query = Cw4QueryMsg::Member {
+                addr: voter.clone(),
+                at_height: if *snapshot {
+                    Some(state.start)
+                } else {
+                    frozen.map(|roll| roll.height)
+                },
+            };
+            let res: MemberResponse = deps.querier.query_wasm_smart(address, &query)?;
+            match res.weight {
+                Some(weight) => u128::from(weight),
+                None => {
+                    return Err(ContractError::NotGroupMember {
+                        voter: voter.clone(),
+                    })
+                }
+            }
+        }
+        StakeWeight::Locked { .. } => locks_read(&deps.storage)
+            .may_load(key.as_slice())?
+            .unwrap_or_default()
+            .u128(),
+        StakeWeight::Cw20 { address } => {
+            let query = Cw20QueryMsg::Balance {
+                address: voter.clone(),
+            };
+            let res: BalanceResponse = deps.querier.query_wasm_smart(address, &query)?;
+            res.balance.u128()
+        }
+        StakeWeight::Fixed { weight } => u128::from(*weight),
+        StakeWeight::Adapter { address } => {
+            let query = VotingPowerQueryMsg::VotingPower {
+                voter: voter.clone(),
+                height: state.start,
+            };
+            let res: VotingPowerResponse = deps.querier.query_wasm_smart(address, &query)?;
+            res.power.u128()
+        }
+    };
+    let weight = u64::try_from(stake)
+        .map_err(|_| StdError::generic_err(format!("Stake of {} exceeds u64", voter)))?;
+    if weight == 0 {
+        return Err(ContractError::NoVotingPower {
+            voter: voter.clone(),
+        });
+    }
+    Ok((weight, true))
+}
+
+/// report_stale turns the first vote attempt after `end` on an unfinalized
+/// election into a `stale_election` event so keepers can pick it up. The
+/// ballot is not counted; later attempts fail with `NotAllowance` as usual.
+fn report_stale<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+) -> Result<Option<HandleResponse>, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    let end = voting_end(&state);
+    if env.block.height <= end
+        || state.stale_reported
+        || result_read(&deps.storage).may_load()?.is_some()
+    {
+        return Ok(None);
+    }
+    state.stale_reported = true;
+    config(&mut deps.storage).save(&state)?;
+    Ok(Some(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "stale_election"),
+            attr("end", end),
+            attr("stale_blocks", env.block.height - end),
+        ],
+        data: None,
+    }))
+}
+
+/// decayed_weight applies the weight decay at `height`, in thousandths of
+/// `weight`
+fn decayed_weight(state: &State, height: u64, weight: u64) -> StdResult<u64> {
+    let decay = match &state.weight_decay {
+        Some(decay) => decay,
+        None => return Ok(weight),
+    };
+    // value * num / denom in two parts, as the product can exceed u128
+    let scale =
+        |value: u128, num: u128, denom: u128| value / denom * num + value % denom * num / denom;
+    let full = u128::from(weight) * 1000;
+    let elapsed = u128::from(height.saturating_sub(state.start));
+    let decayed = match decay {
+        WeightDecay::Linear { floor } => {
+            let span = u128::from(state.end.saturating_sub(state.start)).max(1);
+            let one = 1_000_000_000_000_000_000u128;
+            let floor = scale(full, (Uint128(one) * *floor).u128(), one);
+            full - scale(full - floor, elapsed.min(span), span)
+        }
+        WeightDecay::Exponential { half_life } => {
+            let half_life = u128::from(*half_life);
+            let halvings = elapsed / half_life;
+            let at = if halvings >= 128 { 0 } else { full >> halvings };
+            at - scale(at / 2, elapsed % half_life, half_life)
+        }
+    };
+    u64::try_from(decayed).map_err(|_| StdError::generic_err("decayed weight exceeds u64"))
+}
+
+/// weight_unit is the ballot weight of a whole unit of voting power, ballots
+/// being weighed in thousandths once their weight decays
+fn weight_unit(state: &State) -> u128 {
+    match state.weight_decay {
+        Some(_) => 1000,
+        None => 1,
+    }
+}
+
+/// voting_end is the last height at which ballots are accepted, the end of
+/// the runoff if one is open
+fn voting_end(state: &State) -> u64 {
+    state.runoff.as_ref().map_or(state.end, |runoff| runoff.end)
+}
+
+/// check_voting_period enforces the window of the voter's cohort, or the
+/// election window for voters outside any cohort
+fn check_voting_period<S: Storage>(
+    storage: &S,
+    state: &State,
+    env: &Env,
+    voter: &CanonicalAddr,
+) -> Result<(), ContractError> {
+    let (start, end) = match cohort_members_read(storage).may_load(voter.as_slice())? {
+        Some(name) => state
+            .cohorts
+            .iter()
+            .find(|c| c.name == name)
+            .map(|c| (c.start, c.end))
+            .ok_or_else(|| StdError::not_found("cohort"))?,
+        None => (state.start, state.end),
+    };
+    if env.block.height < start || env.block.height > end {
+        return Err(ContractError::NotAllowance { begin: start, end });
+    }
+    Ok(())
+}
+
+/// validate_ranking checks `ranking` and returns it in canonical form
+fn validate_ranking<A: Api>(
+    api: &A,
+    state: &State,
+    ranking: &[HumanAddr],
+) -> Result<Vec<CanonicalAddr>, ContractError> {
+    if ranking.is_empty() {
+        return Err(ContractError::EmptyRanking {});
+    }
+    let mut canonical = vec![];
+    for candidate in ranking {
+        let candidate_raw = api.canonical_address(candidate)?;
+        if !state.candidates.contains(&candidate_raw) {
+            return Err(ContractError::UnknownCandidate {
+                candidate: candidate.clone(),
+            });
+        }
+        if state.withdrawn.contains(&candidate_raw) {
+            return Err(ContractError::CandidateWithdrawn {
+                candidate: candidate.clone(),
+            });
+        }
+        if canonical.contains(&candidate_raw) {
+            return Err(ContractError::DuplicateRanking {
+                candidate: candidate.clone(),
+            });
+        }
+        canonical.push(candidate_raw);
+    }
+    Ok(canonical)
+}
+
+pub fn try_finalize<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    keeper: HumanAddr,
+    limit: Option<u32>,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if state.halted {
+        return Err(ContractError::Halted {});
+    }
+    let end = voting_end(&state);
+    if env.block.height <= end {
+        return Err(ContractError::ElectionNotEnded { end });
+    }
+    if let Some(referendum) = &state.referendum {
+        return finalize_referendum(deps, env, &state, referendum, keeper);
+    }
+    if result_read(&deps.storage).may_load()?.is_some() {
+        return Err(ContractError::AlreadyFinalized {});
+    }
+    let ballots = stats_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+        .ballots;
+    if state.min_votes.map_or(false, |min| ballots < min) {
+        return finalize_invalid(deps, env, &state, keeper, ballots);
+    }
+    if let Some(limit) = limit {
+        let remaining = advance_tally(&deps.api, &mut state, limit)?;
+        config(&mut deps.storage).save(&state)?;
+        if remaining > 0 {
+            return Ok(HandleResponse {
+                messages: vec![],
+                attributes: vec![
+                    attr("action", "finalize_progress"),
+                    attr(
+                        "counted",
+                        state.tally_cursor.as_ref().map_or(0, |c| c.position),
+                    ),
+                    attr("remaining", remaining),
+                ],
+                data: None,
+            });
+        }
+    }
+
+    let Count {
+        winners,
+        tied,
+        rounds,
+    } = count_winners(&deps.api, &state)?;
+    if let (Some(majority), None) = (&state.majority_runoff, &state.runoff) {
+        let ranked = rank_candidates(&deps.api, &state)?;
+        let total: u128 = ranked.iter().map(|vote| vote.weight.u128()).sum();
+        if total > 0
+            && ranked.len() > 1
+            && Decimal::from_ratio(ranked[0].weight, total) < majority.threshold
+        {
+            let top_two: Vec<HumanAddr> = ranked
+                .into_iter()
+                .take(2)
+                .map(|vote| vote.candidate)
+                .collect();
+            let runoff = Runoff {
+                seats: 1,
+                candidates: canonicalize_addrs(&deps.api, &top_two)?,
+                elected: vec![],
+                start: env.block.height,
+                end: env.block.height + majority.duration,
+                votes: vec![],
+            };
+            return open_runoff(deps, state, runoff);
+        }
+    }
+    if let TiePolicy::Runoff { duration } = state.tie_policy {
+        if state.runoff.is_none() && !tied.is_empty() {
+            let runoff = Runoff {
+                seats: state.seats - winners.len() as u32,
+                candidates: canonicalize_addrs(&deps.api, &tied)?,
+                elected: winners,
+                start: env.block.height,
+                end: env.block.height + duration,
+                votes: vec![],
+            };
+            return open_runoff(deps, state, runoff);
+        }
+    }
+    if state.tie_policy == TiePolicy::Manual && !tied.is_empty() && state.tie_resolution.is_none() {
+        let mut attributes = vec![attr("action", "tie_pending")];
+        for candidate in &tied {
+            attributes.push(attr("tied", candidate));
+        }
+        return Ok(HandleResponse {
+            messages: vec![],
+            attributes,
+            data: None,
+        });
+    }
+    if let Some(rounds) = rounds {
+        tally_rounds(&mut deps.storage).save(&rounds)?;
+    }
+    let mut attributes = vec![attr("action", "finalize")];
+    for winner in &winners {
+        attributes.push(attr("winner", &winner.candidate));
+    }
+
+    // a result that can still be vetoed or disputed holds the prize and the
+    // announcements until `ReleasePrize`
+    let released = state.veto_council.is_none() && state.dispute.is_none();
+    let mut messages = vec![];
+    if released {
+        messages = pay_prize(deps, &env, &winners)?;
+    }
+    messages.extend(pay_bounty(deps, &env, &state, keeper)?);
+
+    let stored = ElectionResult {
+        standings: standings(&deps.api, &state, &winners)?,
+        winners,
+        finalized_at: env.block.height,
+        tie_policy: state.tie_policy.clone(),
+        tied,
+        vacated: vec![],
+        vetoed_at: None,
+        voting_system: state.voting_system,
+        invalid: false,
+        released,
+    };
+    if released {
+        let turnout = stats_read(&deps.storage)
+            .may_load()?
+            .unwrap_or_default()
+            .ballots;
+        messages.extend(announce(&state, &env.contract.address, &stored, turnout)?);
+    }
+    result(&mut deps.storage).save(&stored)?;
+    tally_proof(&mut deps.storage).save(&prove_tally(&deps.api, &state, &stored)?)?;
+
+    Ok(HandleResponse {
+        messages,
+        attributes,
+        data: None,
+    })
+}
+
+/// finalize_invalid stores a result without winners and refunds the pools,
+/// the keeper still earns the bounty
+fn finalize_invalid<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    state: &State,
+    keeper: HumanAddr,
+    ballots: u64,
+) -> Result<HandleResponse, ContractError> {
+    let mut messages = refund_pools(deps, &env, &state.owner)?;
+    messages.extend(pay_bounty(deps, &env, state, keeper)?);
+    result(&mut deps.storage).save(&ElectionResult {
+        winners: vec![],
+        finalized_at: env.block.height,
+        tie_policy: state.tie_policy.clone(),
+        tied: vec![],
+        standings: vec![],
+        vacated: vec![],
+        vetoed_at: None,
+        voting_system: state.voting_system,
+        invalid: true,
+        released: false,
+    })?;
+    Ok(HandleResponse {
+        messages,
+        attributes: vec![
+            attr("action", "finalize"),
+            attr("status", "invalid"),
+            attr("ballots", ballots),
+        ],
+        data: None,
+    })
+}
+
+/// try_check_phase finalizes the election once voting has ended and
+/// otherwise only reports the phase, so keepers can call it every block
+pub fn try_check_phase<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    keeper: HumanAddr,
+) -> Result<HandleResponse, ContractError> {
+    let PhaseResponse { phase, .. } = query_phase(deps, &env)?;
+    if phase == Phase::Tallying {
+        let mut res = try_finalize(deps, env, keeper, None)?;
+        res.attributes.push(attr("phase", "finalized"));
+        return Ok(res);
+    }
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "check_phase"),
+            attr("phase", format!("{:?}", phase).to_lowercase()),
+        ],
+        data: None,
+    })
+}
+
+fn query_phase<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+) -> StdResult<PhaseResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let height = env.block.height;
+    let end = voting_end(&state);
+    let finalized = result_read(&deps.storage).may_load()?.is_some()
+        || referendum_outcome_read(&deps.storage).may_load()?.is_some();
+    let registration = state
+        .registration
+        .as_ref()
+        .filter(|r| r.end >= height && r.start < state.start);
+    let (phase, next_at) = if finalized {
+        (Phase::Finalized, None)
+    } else if height > end {
+        (Phase::Tallying, None)
+    } else if height >= state.start {
+        (Phase::Voting, Some(end + 1))
+    } else {
+        match registration {
+            Some(r) if height >= r.start => (Phase::Registration, Some(state.start)),
+            Some(r) => (Phase::Pending, Some(r.start)),
+            None => (Phase::Pending, Some(state.start)),
+        }
+    };
+    Ok(PhaseResponse { phase, next_at })
+}
+
+fn finalize_referendum<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    state: &State,
+    referendum: &Referendum,
+    keeper: HumanAddr,
+) -> Result<HandleResponse, ContractError> {
+    if referendum_outcome_read(&deps.storage).may_load()?.is_some() {
+        return Err(ContractError::AlreadyFinalized {});
+    }
+    let tally = referendum_tally_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    let status = referendum_status(state, referendum, &tally);
+    referendum_outcome(&mut deps.storage).save(&ReferendumOutcome {
+        status,
+        finalized_at: env.block.height,
+    })?;
+    let status = match status {
+        ReferendumStatus::Passed => "passed",
+        ReferendumStatus::Rejected => "rejected",
+        ReferendumStatus::Vetoed => "vetoed",
+        ReferendumStatus::QuorumNotMet => "quorum_not_met",
+    };
+    Ok(HandleResponse {
+        messages: pay_bounty(deps, &env, state, keeper)?,
+        attributes: vec![attr("action", "finalize"), attr("status", status)],
+        data: None,
+    })
+}
+
+/// pay_prize splits the prize pool among `winners`
+fn pay_prize<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    winners: &[Winner],
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    let mut messages: Vec<CosmosMsg> = vec![];
+    for (candidate, prize) in split_prize(&books.bucket(FundBucket::PrizePool), winners) {
+        books.withdraw(FundBucket::PrizePool, &prize)?;
+        messages.push(
+            BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: candidate,
+                amount: prize,
+            }
+            .into(),
+        );
+    }
+    if !messages.is_empty() {
+        ledger(&mut deps.storage).save(&books)?;
+    }
+    Ok(messages)
+}
+
+/// pay_bounty empties the bounty bucket to `keeper`, or to the owner once
+/// the bounty window has passed
+fn pay_bounty<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    state: &State,
+    keeper: HumanAddr,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let bounty = match &state.finalization_bounty {
+        Some(bounty) => bounty,
+        None => return Ok(vec![]),
+    };
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    let amount = books.bucket(FundBucket::Bounty);
+    if amount.is_empty() {
+        return Ok(vec![]);
+    }
+    books.withdraw(FundBucket::Bounty, &amount)?;
+    ledger(&mut deps.storage).save(&books)?;
+    let to_address = if env.block.height <= voting_end(state) + bounty.window {
+        keeper
+    } else {
+        state.owner.clone()
+    };
+    Ok(vec![BankMsg::Send {
+        from_address: env.contract.address.clone(),
+        to_address,
+        amount,
+    }
+    .into()])
+}
+
+/// referendum_status evaluates the tally the way on-chain governance does
+fn referendum_status(
+    state: &State,
+    referendum: &Referendum,
+    tally: &ReferendumTally,
+) -> ReferendumStatus {
+    let total = tally.total();
+    let electorate = referendum
+        .electorate
+        .u128()
+        .saturating_mul(weight_unit(state));
+    if Decimal::from_ratio(total, electorate) < referendum.quorum {
+        return ReferendumStatus::QuorumNotMet;
+    }
+    let voted = total - tally.abstain.u128();
+    if voted == 0 {
+        return ReferendumStatus::Rejected;
+    }
+    if Decimal::from_ratio(tally.no_with_veto, total) > referendum.veto_threshold {
+        return ReferendumStatus::Vetoed;
+    }
+    if Decimal::from_ratio(tally.yes, voted) > referendum.threshold {
+        ReferendumStatus::Passed
+    } else {
+        ReferendumStatus::Rejected
+    }
+}
+
+/// standings ranks every candidate: the winners in order, then everybody
+/// else by their ballots
+fn standings<A: Api>(api: &A, state: &State, winners: &[Winner]) -> StdResult<Vec<Winner>> {
+    let mut standings = winners.to_vec();
+    for vote in rank_candidates(api, state)? {
+        if !winners.iter().any(|w| w.candidate == vote.candidate) {
+            standings.push(to_winner(vote));
+        }
+    }
+    Ok(standings)
+}
+
+/// prove_tally commits to the ballots of the first round and the standings of `stored`
+fn prove_tally<A: Api>(api: &A, state: &State, stored: &ElectionResult) -> StdResult<TallyProof> {
+    let mut ballots = humanize_votes(api, state, &state.votes)?;
+    ballots.sort_by(|a, b| a.voter.as_str().cmp(b.voter.as_str()));
+    let leaves: Vec<[u8; 32]> = ballots
+        .iter()
+        .map(|ballot| merkle::leaf(&encode_ballot(ballot)))
+        .collect();
+    let tally = encode_tally(&stored.standings);
+    Ok(TallyProof {
+        schema_version: SCHEMA_VERSION,
+        ballots: ballots.len() as u32,
+        ballots_root: Binary::from(merkle::root(&leaves).to_vec()),
+        tally_hash: Binary::from(sha256(&tally).to_vec()),
+        tally: Binary(tally),
+        finalized_at: stored.finalized_at,
+    })
+}
+
+/// announce notifies the completion hook and, unless it is deferred, the
+/// registry of `stored`
+fn announce(
+    state: &State,
+    election: &HumanAddr,
+    stored: &ElectionResult,
+    turnout: u64,
+) -> StdResult<Vec<CosmosMsg>> {
+    let mut messages = vec![];
+    if let Some(hook) = &state.completion_hook {
+        let msg = CompletionHookMsg::ElectionFinalized {
+            election: election.clone(),
+            finalized_at: stored.finalized_at,
+            winners: stored.winners.clone(),
+        };
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: hook.clone(),
+                msg: to_binary(&msg)?,
+                send: vec![],
+            }
+            .into(),
+        );
+    }
+    if let Some(registry) = state.registry.as_ref().filter(|r| !r.deferred) {
+        messages.extend(publish_result(registry, election, stored, turnout)?);
+    }
+    Ok(messages)
+}
+
+pub fn try_publish_result<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let registry = state.registry.ok_or(ContractError::RegistryDisabled {})?;
+    let stored = result_read(&deps.storage)
+        .may_load()?
+        .ok_or(ContractError::NotFinalized {})?;
+    if stored.invalid {
+        return Err(ContractError::ElectionInvalid {});
+    }
+    if !stored.released {
+        return Err(ContractError::ResultNotFinal {});
+    }
+    let turnout = stats_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+        .ballots;
+    Ok(HandleResponse {
+        messages: publish_result(&registry, &env.contract.address, &stored, turnout)?,
+        attributes: vec![attr("action", "publish_result")],
+        data: None,
+    })
+}
+
+pub fn try_promote_runner_up<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    vacated: HumanAddr,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let succession = state
+        .succession
+        .clone()
+        .ok_or(ContractError::SuccessionDisabled {})?;
+    if !has_role(&state, &info.sender, Role::ElectionManager)
+        && Some(&info.sender) != succession.arbiter.as_ref()
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut stored = result_read(&deps.storage)
+        .may_load()?
+        .ok_or(ContractError::NotFinalized {})?;
+    let end = stored.finalized_at + succession.window;
+    if env.block.height > end {
+        return Err(ContractError::SuccessionWindowClosed { end });
+    }
+    let seat = stored
+        .winners
+        .iter()
+        .position(|w| w.candidate == vacated)
+        .ok_or_else(|| ContractError::NotAWinner {
+            candidate: vacated.clone(),
+        })?;
+    let runner_up = stored
+        .standings
+        .iter()
+        .find(|s| {
+            !stored.winners.iter().any(|w| w.candidate == s.candidate)
+                && !stored.vacated.contains(&s.candidate)
+        })
+        .cloned()
+        .ok_or(ContractError::NoRunnerUp {})?;
+    let promoted = runner_up.candidate.clone();
+    stored.winners[seat] = runner_up;
+    stored.vacated.push(vacated.clone());
+    result(&mut deps.storage).save(&stored)?;
+    // a held result is announced with the new winners on release
+    let mut messages = vec![];
+    if stored.released {
+        let turnout = stats_read(&deps.storage)
+            .may_load()?
+            .unwrap_or_default()
+            .ballots;
+        messages = announce(&state, &env.contract.address, &stored, turnout)?;
+    }
+    Ok(HandleResponse {
+        messages,
+        attributes: vec![
+            attr("action", "promote_runner_up"),
+            attr("vacated", vacated),
+            attr("promoted", promoted),
+        ],
+        data: None,
+    })
+}
+
+fn open_runoff<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    mut state: State,
+    runoff: Runoff,
+) -> Result<HandleResponse, ContractError> {
+    let attributes = vec![
+        attr("action", "start_runoff"),
+        attr("seats", runoff.seats),
+        attr("end", runoff.end),
+    ];
+    state.runoff = Some(runoff);
+    config(&mut deps.storage).save(&state)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes,
+        data: None,
+    })
+}
+
+/// round is the voting round an election is in
+fn round(state: &State) -> u32 {
+    if state.runoff.is_some() {
+        2
+    } else {
+        1
+    }
+}
+
+/// try_resolve_tie records the owner's choice among the tied candidates and
+/// finalizes the election with it
+pub fn try_resolve_tie<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    winners: Vec<HumanAddr>,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    if !has_role(&state, &info.sender, Role::ElectionManager) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let end = voting_end(&state);
+    if env.block.height <= end {
+        return Err(ContractError::ElectionNotEnded { end });
+    }
+    if state.tie_policy != TiePolicy::Manual
+        || state.tie_resolution.is_some()
+        || result_read(&deps.storage).may_load()?.is_some()
+    {
+        return Err(ContractError::NoPendingTie {});
+    }
+    let count = count_winners(&deps.api, &state)?;
+    if count.tied.is_empty() {
+        return Err(ContractError::NoPendingTie {});
+    }
+    let seats = state.seats - count.winners.len() as u32;
+    let valid = winners.len() == seats as usize
+        && winners
+            .iter()
+            .enumerate()
+            .all(|(i, winner)| count.tied.contains(winner) && !winners[..i].contains(winner));
+    if !valid {
+        return Err(ContractError::InvalidTieResolution { seats });
+    }
+    state.tie_resolution = Some(canonicalize_addrs(&deps.api, &winners)?);
+    config(&mut deps.storage).save(&state)?;
+    try_finalize(deps, env, info.sender, None)
+}
+
+/// publish_result writes one registry entry per seat. The messages are plain
+/// executes, a failing registry fails the message that sent them.
+fn publish_result(
+    registry: &RegistryConfig,
+    election: &HumanAddr,
+    stored: &ElectionResult,
+    turnout: u64,
+) -> StdResult<Vec<CosmosMsg>> {
+    let result_hash = Binary::from(sha256(&to_vec(stored)?).to_vec());
+    let counts_hash = Binary::from(sha256(&encode_tally(&stored.standings)).to_vec());
+    let mut messages = vec![];
+    for (i, winner) in stored.winners.iter().enumerate() {
+        let seat = i as u32 + 1;
+        let key = registry
+            .key_template
+            .replace("{election}", election.as_str())
+            .replace("{seat}", &seat.to_string());
+        let value = PublishedWinner {
+            election: election.clone(),
+            seat,
+            winner: winner.candidate.clone(),
+            finalized_at: stored.finalized_at,
+            result_hash: result_hash.clone(),
+            counts_hash: counts_hash.clone(),
+            turnout,
+        };
+        let msg = RegistryMsg::Set {
+            key,
+            value: to_binary(&value)?,
+        };
+        messages.push(
+            WasmMsg::Execute {
+                contract_addr: registry.address.clone(),
+                msg: to_binary(&msg)?,
+                send: vec![],
+            }
+            .into(),
+        );
+    }
+    Ok(messages)
+}
+
+/// Count is the outcome of counting the ballots. `tied` lists the candidates
+/// tied across the last seat, `rounds` is the round record of STV counts.
+struct Count {
+    winners: Vec<Winner>,
+    tied: Vec<HumanAddr>,
+    rounds: Option<Vec<TallyRound>>,
+}
+
+fn to_winner(vote: Vote) -> Winner {
+    Winner {
+        candidate: vote.candidate,
+        count: vote.count,
+        weight: vote.weight,
+    }
+}
+
+/// count_party_list splits the seats between the slates by their summed
+/// weight and fills them in list order, tie policies do not apply
+fn count_party_list<A: Api>(api: &A, state: &State, list: &PartyList) -> StdResult<Count> {
+    let ranked = rank_candidates(api, state)?;
+    let mut slates: Vec<Vec<HumanAddr>> = list
+        .slates
+        .iter()
+        .map(|slate| slate.candidates.clone())
+        .collect();
+    for vote in &ranked {
+        if !slates.iter().any(|slate| slate.contains(&vote.candidate)) {
+            slates.push(vec![vote.candidate.clone()]);
+        }
+    }
+    // withdrawn candidates are not ranked and give up their place on the list
+    let slates: Vec<Vec<&Vote>> = slates
+        .iter()
+        .map(|slate| {
+            slate
+                .iter()
+                .filter_map(|c| ranked.iter().find(|vote| &vote.candidate == c))
+                .collect()
+        })
+        .collect();
+    let totals: Vec<u128> = slates
+        .iter()
+        .map(|slate| slate.iter().map(|vote| vote.weight.u128()).sum())
+        .collect();
+    let capacity: Vec<usize> = slates.iter().map(|slate| slate.len()).collect();
+    let mut filled = vec![0usize; slates.len()];
+    let mut winners = vec![];
+    for slate in highest_averages(&totals, &capacity, state.seats, list.allocation) {
+        winners.push(to_winner(slates[slate][filled[slate]].clone()));
+        filled[slate] += 1;
+    }
+    Ok(Count {
+        winners,
+        tied: vec![],
+        rounds: None,
+    })
+}
+
+/// count_winners counts the ballots and selects the winners, applying the tie
+/// policy to plurality elections
+fn count_winners<A: Api>(api: &A, state: &State) -> StdResult<Count> {
+    match state.voting_system {
+        VotingSystem::Plurality | VotingSystem::Cumulative { .. } => {
+            if let Some(list) = &state.party_list {
+                return count_party_list(api, state, list);
+            }
+            if let Some(runoff) = &state.runoff {
+                let mut winners = runoff.elected.clone();
+                let mut ranked = count_votes(&humanize_votes(api, state, &runoff.votes)?)?;
+                let candidates = humanize_addrs(api, &runoff.candidates)?;
+                for candidate in &candidates {
+                    ranked.entry(candidate.clone()).or_insert_with(|| Vote {
+                        candidate: candidate.clone(),
+                        count: Uint128::zero(),
+                        weight: Uint128::zero(),
+                        label: None,
+                    });
+                }
+                let mut ranked: Vec<Vote> = ranked.into_iter().map(|(_, vote)| vote).collect();
+                ranked.sort_by(|a, b| b.weight.cmp(&a.weight).then(a.candidate.cmp(&b.candidate)));
+                winners.extend(
+                    ranked
+                        .into_iter()
+                        .take(runoff.seats as usize)
+                        .map(to_winner),
+                );
+                return Ok(Count {
+                    winners,
+                    tied: candidates,
+                    rounds: None,
+                });
+            }
+
+            let ranked = rank_candidates(api, state)?;
+            let seats = state.seats as usize;
+            let boundary_tie =
+                ranked.len() > seats && ranked[seats - 1].weight == ranked[seats].weight;
+            if !boundary_tie {
+                return Ok(Count {
+                    winners: ranked.into_iter().take(seats).map(to_winner).collect(),
+                    tied: vec![],
+                    rounds: None,
+                });
+            }
+            let tie_weight = ranked[seats].weight;
+            let tied: Vec<HumanAddr> = ranked
+                .iter()
+                .filter(|vote| vote.weight == tie_weight)
+                .map(|vote| vote.candidate.clone())
+                .collect();
+            let winners = match state.tie_policy {
+                TiePolicy::TieBreak => ranked.into_iter().take(seats).map(to_winner).collect(),
+                TiePolicy::Expand => ranked
+                    .into_iter()
+                    .take_while(|vote| vote.weight >= tie_weight)
+                    .map(to_winner)
+                    .collect(),
+                TiePolicy::Runoff { .. } => ranked
+                    .into_iter()
+                    .take_while(|vote| vote.weight > tie_weight)
+                    .map(to_winner)
+                    .collect(),
+                TiePolicy::EarliestCandidate | TiePolicy::Manual => {
+                    let (mut winners, mut contenders): (Vec<Vote>, Vec<Vote>) = ranked
+                        .into_iter()
+                        .filter(|vote| vote.weight >= tie_weight)
+                        .partition(|vote| vote.weight > tie_weight);
+                    let open = seats - winners.len();
+                    match state.tie_policy {
+                        TiePolicy::EarliestCandidate => {
+                            let candidates = humanize_addrs(api, &state.candidates)?;
+                            contenders.sort_by_key(|vote| {
+                                candidates
+                                    .iter()
+                                    .position(|c| c == &vote.candidate)
+                                    .unwrap_or(usize::MAX)
+                            });
+                        }
+                        _ => {
+                            // unresolved manual ties elect nobody, `Finalize` stops before saving
+                            let chosen = humanize_addrs(
+                                api,
+                                state.tie_resolution.as_deref().unwrap_or_default(),
+                            )?;
+                            contenders = chosen
+                                .iter()
+                                .filter_map(|c| contenders.iter().find(|v| &v.candidate == c))
+                                .cloned()
+                                .collect();
+                        }
+                    }
+                    winners.extend(contenders.into_iter().take(open));
+                    winners.into_iter().map(to_winner).collect()
+                }
+            };
+            Ok(Count {
+                winners,
+                tied,
+                rounds: None,
+            })
+        }
+        VotingSystem::Stv => {
+            let votes = humanize_votes(api, state, &state.votes)?;
+            let ballots: Vec<RankedBallot> = votes
+                .iter()
+                .map(|vote| RankedBallot {
+                    ranking: &vote.ranking,
+                    weight: vote.weight,
+                })
+                .collect();
+            let candidates = humanize_addrs(api, &standing_candidates(state))?;
+            let outcome = stv(&candidates, &ballots, state.seats);
+            let first_preferences = count_votes(&votes)?;
+            let winners = outcome
+                .elected
+                .into_iter()
+                .map(|(candidate, value)| Winner {
+                    count: first_preferences
+                        .get(&candidate)
+                        .map_or_else(Uint128::zero, |vote| vote.count),
+                    weight: Uint128(value),
+                    candidate,
+                })
+                .collect();
+            Ok(Count {
+                winners,
+                tied: vec![],
+                rounds: Some(outcome.rounds),
+            })
+        }
+        VotingSystem::Borda | VotingSystem::Condorcet { .. } => {
+            let votes = humanize_votes(api, state, &state.votes)?;
+            let ballots: Vec<RankedBallot> = votes
+                .iter()
+                .map(|vote| RankedBallot {
+                    ranking: &vote.ranking,
+                    weight: vote.weight,
+                })
+                .collect();
+            let candidates = humanize_addrs(api, &standing_candidates(state))?;
+            let order = match state.voting_system {
+                VotingSystem::Condorcet { fallback } => condorcet(&candidates, &ballots, fallback),
+                _ => borda(&candidates, &ballots),
+            };
+            let first_preferences = count_votes(&votes)?;
+            let winners = order
+                .into_iter()
+                .take(state.seats as usize)
+                .map(|(candidate, score)| Winner {
+                    count: first_preferences
+                        .get(&candidate)
+                        .map_or_else(Uint128::zero, |vote| vote.count),
+                    weight: Uint128(score),
+                    candidate,
+                })
+                .collect();
+            Ok(Count {
+                winners,
+                tied: vec![],
+                rounds: None,
+            })
+        }
+    }
+}
+
+/// try_challenge_result recounts the ballots and checks the standings
+/// against the tally committed at finalization. A discrepancy corrects the
+/// result and refunds the bond, otherwise the bond goes to the treasury.
+pub fn try_challenge_result<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let dispute = state
+        .dispute
+        .clone()
+        .ok_or(ContractError::DisputesDisabled {})?;
+    let mut stored = result_read(&deps.storage)
+        .may_load()?
+        .ok_or(ContractError::NotFinalized {})?;
+    let end = stored.finalized_at + dispute.window;
+    if env.block.height > end {
+        return Err(ContractError::DisputeWindowClosed { end });
+    }
+    if stored.vetoed_at.is_some() {
+        return Err(ContractError::Vetoed {});
+    }
+    if stored.invalid {
+        return Err(ContractError::ElectionInvalid {});
+    }
+    // a recount would undo the succession
+    if !stored.vacated.is_empty() {
+        return Err(ContractError::SuccessionApplied {});
+    }
+    let bond = take_bond(&info, &dispute)?;
+
+    let Count {
+        winners, rounds, ..
+    } = count_winners(&deps.api, &state)?;
+    let recount = standings(&deps.api, &state, &winners)?;
+    let committed = tally_proof_read(&deps.storage).load()?;
+    let discrepancy = encode_tally(&recount) != committed.tally.as_slice();
+    let to_address = if discrepancy {
+        stored.standings = recount;
+        stored.winners = winners;
+        result(&mut deps.storage).save(&stored)?;
+        tally_proof(&mut deps.storage).save(&prove_tally(&deps.api, &state, &stored)?)?;
+        if let Some(rounds) = rounds {
+            tally_rounds(&mut deps.storage).save(&rounds)?;
+        }
+        info.sender.clone()
+    } else {
+        dispute.treasury
+    };
+
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    books.deposit(FundBucket::Deposits, &bond);
+    books.withdraw(FundBucket::Deposits, &bond)?;
+    ledger(&mut deps.storage).save(&books)?;
+    Ok(HandleResponse {
+        messages: vec![BankMsg::Send {
+            from_address: env.contract.address,
+            to_address,
+            amount: bond,
+        }
+        .into()],
+        attributes: vec![
+            attr("action", "challenge_result"),
+            attr("challenger", info.sender),
+            attr("discrepancy", discrepancy),
+        ],
+        data: None,
+    })
+}
+
+/// take_bond returns the dispute bond attached to the message
+fn take_bond(info: &MessageInfo, dispute: &DisputeConfig) -> Result<Vec<Coin>, ContractError> {
+    let bond: Vec<Coin> = info
+        .sent_funds
+        .iter()
+        .filter(|c| c.denom == dispute.bond.denom)
+        .cloned()
+        .collect();
+    if bond.iter().map(|c| c.amount.u128()).sum::<u128>() < dispute.bond.amount.u128() {
+        return Err(ContractError::InsufficientBond {
+            denom: dispute.bond.denom.clone(),
+            amount: dispute.bond.amount,
+        });
+    }
+    Ok(bond)
+}
+
+/// try_challenge holds the bond and records the challenge for the owner or
+/// the guardians to resolve
+pub fn try_challenge<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    reason: String,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let dispute = state
+        .dispute
+        .clone()
+        .ok_or(ContractError::DisputesDisabled {})?;
+    let stored = result_read(&deps.storage)
+        .may_load()?
+        .ok_or(ContractError::NotFinalized {})?;
+    let end = stored.finalized_at + dispute.window;
+    if env.block.height > end {
+        return Err(ContractError::DisputeWindowClosed { end });
+    }
+    let bond = take_bond(&info, &dispute)?;
+
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    books.deposit(FundBucket::Deposits, &bond);
+    ledger(&mut deps.storage).save(&books)?;
+    let id = challenges_read(&deps.storage)
+        .range(None, None, Order::Ascending)
+        .count() as u64;
+    let challenge = Challenge {
+        id,
+        challenger: info.sender.clone(),
+        reason,
+        bond,
+        height: env.block.height,
+        upheld: None,
+    };
+    challenges(&mut deps.storage).save(&id.to_be_bytes(), &challenge)?;
+    Ok(HandleResponse {
+        messages: vec![],
+        attributes: vec![
+            attr("action", "challenge"),
+            attr("challenger", info.sender),
+            attr("id", id),
+        ],
+        data: None,
+    })
+}
+
+pub fn try_resolve_challenge<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+    id: u64,
+    upheld: bool,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    if !has_role(&state, &info.sender, Role::ElectionManager)
+        && !state.guardians.contains(&info.sender)
+    {
+        return Err(ContractError::Unauthorized {});
+    }
+    let dispute = state
+        .dispute
+        .clone()
+        .ok_or(ContractError::DisputesDisabled {})?;
+    let mut challenge = match challenges_read(&deps.storage).may_load(&id.to_be_bytes())? {
+        Some(challenge) if challenge.upheld.is_none() => challenge,
+        _ => return Err(ContractError::UnknownChallenge { id }),
+    };
+    challenge.upheld = Some(upheld);
+    challenges(&mut deps.storage).save(&id.to_be_bytes(), &challenge)?;
+
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    books.withdraw(FundBucket::Deposits, &challenge.bond)?;
+    ledger(&mut deps.storage).save(&books)?;
+    let to_address = if upheld {
+        challenge.challenger
+    } else {
+        dispute.treasury
+    };
+    Ok(HandleResponse {
+        messages: vec![BankMsg::Send {
+            from_address: env.contract.address,
+            to_address,
+            amount: challenge.bond,
+        }
+        .into()],
+        attributes: vec![
+            attr("action", "resolve_challenge"),
+            attr("id", id),
+            attr("upheld", upheld),
+        ],
+        data: None,
+    })
+}
+
+/// result_status derives the dispute status of the stored result at `height`
+fn result_status<S: Storage>(
+    storage: &S,
+    state: &State,
+    stored: &ElectionResult,
+    height: u64,
+) -> StdResult<ResultStatus> {
+    if stored.vetoed_at.is_some() {
+        return Ok(ResultStatus::Vetoed);
+    }
+    if stored.invalid {
+        return Ok(ResultStatus::Invalid);
+    }
+    let mut open = false;
+    for item in challenges_read(storage).range(None, None, Order::Ascending) {
+        match item?.1.upheld {
+            Some(true) => return Ok(ResultStatus::Overturned),
+            Some(false) => {}
+            None => open = true,
+        }
+    }
+    if open {
+        return Ok(ResultStatus::Challenged);
+    }
+    let window = std::cmp::max(
+        state.dispute.as_ref().map_or(0, |dispute| dispute.window),
+        state
+            .veto_council
+            .as_ref()
+            .map_or(0, |council| council.window),
+    );
+    if height <= stored.finalized_at + window {
+        return Ok(ResultStatus::Provisional);
+    }
+    Ok(ResultStatus::Final)
+}
+
+/// try_approve_veto records the sender's approval. The approval reaching
+/// the threshold marks the result vetoed and refunds the held prize and the
+/// unclaimed participation reward to the owner.
+pub fn try_approve_veto<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<HandleResponse, ContractError> {
+    let mut state = config_read(&deps.storage).load()?;
+    let council = state
+        .veto_council
+        .clone()
+        .ok_or(ContractError::VetoDisabled {})?;
+    if !council.members.contains(&info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
+    let mut stored = result_read(&deps.storage)
+        .may_load()?
+        .ok_or(ContractError::NotFinalized {})?;
+    if stored.vetoed_at.is_some() {
+        return Err(ContractError::Vetoed {});
+    }
+    let end = stored.finalized_at + council.window;
+    if env.block.height > end {
+        return Err(ContractError::VetoWindowClosed { end });
+    }
+    if state.veto_approvals.contains(&info.sender) {
+        return Err(ContractError::AlreadyApproved {
+            member: info.sender,
+        });
+    }
+    state.veto_approvals.push(info.sender.clone());
+    config(&mut deps.storage).save(&state)?;
+
+    let vetoed = state.veto_approvals.len() >= council.threshold as usize;
+    let mut messages = vec![];
+    if vetoed {
+        stored.vetoed_at = Some(env.block.height);
+        result(&mut deps.storage).save(&stored)?;
+        messages = refund_pools(deps, &env, &state.owner)?;
+    }
+    Ok(HandleResponse {
+        messages,
+        attributes: vec![
+            attr("action", "approve_veto"),
+            attr("member", info.sender),
+            attr("vetoed", vetoed),
+        ],
+        data: None,
+    })
+}
+
+/// refund_pools returns the prize and the participation reward to `owner`
+fn refund_pools<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: &Env,
+    owner: &HumanAddr,
+) -> Result<Vec<CosmosMsg>, ContractError> {
+    let mut books = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    let mut refund = vec![];
+    for bucket in &[FundBucket::PrizePool, FundBucket::RewardPool] {
+        let amount = books.bucket(*bucket);
+        books.withdraw(*bucket, &amount)?;
+        refund.extend(amount);
+    }
+    ledger(&mut deps.storage).save(&books)?;
+    if refund.is_empty() {
+        return Ok(vec![]);
+    }
+    Ok(vec![BankMsg::Send {
+        from_address: env.contract.address.clone(),
+        to_address: owner.clone(),
+        amount: refund,
+    }
+    .into()])
+}
+
+/// try_release_prize pays the held prize and announces the result once it
+/// is final
+pub fn try_release_prize<S: Storage, A: Api, Q: Querier>(
+    deps: &mut Extern<S, A, Q>,
+    env: Env,
+) -> Result<HandleResponse, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    let mut stored = result_read(&deps.storage)
+        .may_load()?
+        .ok_or(ContractError::NotFinalized {})?;
+    if stored.released {
+        return Err(ContractError::PrizeReleased {});
+    }
+    if let Some(council) = &state.veto_council {
+        let end = stored.finalized_at + council.window;
+        if stored.vetoed_at.is_none() && env.block.height <= end {
+            return Err(ContractError::VetoWindowOpen { end });
+        }
+    }
+    match result_status(&deps.storage, &state, &stored, env.block.height)? {
+        ResultStatus::Final => {}
+        ResultStatus::Vetoed => return Err(ContractError::Vetoed {}),
+        ResultStatus::Invalid => return Err(ContractError::ElectionInvalid {}),
+        ResultStatus::Overturned => return Err(ContractError::ResultOverturned {}),
+        ResultStatus::Provisional | ResultStatus::Challenged => {
+            return Err(ContractError::ResultNotFinal {})
+        }
+    }
+    stored.released = true;
+    result(&mut deps.storage).save(&stored)?;
+    let turnout = stats_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+        .ballots;
+    let mut messages = pay_prize(deps, &env, &stored.winners)?;
+    messages.extend(announce(&state, &env.contract.address, &stored, turnout)?);
+    Ok(HandleResponse {
+        messages,
+        attributes: vec![attr("action", "release_prize")],
+        data: None,
+    })
+}
+
+/// split_prize divides the prize pool among the winners in proportion to
+/// their vote weight, or evenly if none of them has any. Rounding dust goes
+/// to the first winner.
+fn split_prize(pool: &[Coin], winners: &[Winner]) -> Vec<(HumanAddr, Vec<Coin>)> {
+    if winners.is_empty() {
+        return vec![];
+    }
+    let total: u128 = winners.iter().map(|w| w.weight.u128()).sum();
+    let share_of = |winner: &Winner| {
+        if total == 0 {
+            1
+        } else {
+            winner.weight.u128()
+        }
+    };
+    let shares_total = if total == 0 {
+        winners.len() as u128
+    } else {
+        total
+    };
+
+    let mut payouts: Vec<(HumanAddr, Vec<Coin>)> = winners
+        .iter()
+        .map(|w| (w.candidate.clone(), vec![]))
+        .collect();
+    for coin in pool {
+        let mut paid = 0u128;
+        for (i, winner) in winners.iter().enumerate() {
+            let amount = coin.amount.multiply_ratio(share_of(winner), shares_total);
+            paid += amount.u128();
+            payouts[i].1.push(Coin {
+                denom: coin.denom.clone(),
+                amount,
+            });
+        }
+        payouts[0].1.last_mut().unwrap().amount += Uint128(coin.amount.u128() - paid);
+    }
+    for (_, coins) in payouts.iter_mut() {
+        coins.retain(|c| !c.amount.is_zero());
+    }
+    payouts.retain(|(_, coins)| !coins.is_empty());
+    payouts
+}
+
+// Note, you can use StdResult in some functions where you do not
+// make use of the custom errors
+pub fn query<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: Env,
+    msg: QueryMsg,
+) -> StdResult<Binary> {
+    match msg {
+        QueryMsg::GetVoteInfo { locale, sort } => to_binary(&query_vote_info(deps, locale, sort)?),
+        QueryMsg::GetVoteInfoLegacy { locale, sort } => to_binary(&LegacyVoteResponse::from(
+            query_vote_info(deps, locale, sort)?,
+        )),
+        QueryMsg::GetPhase {} => to_binary(&query_phase(deps, &env)?),
+        QueryMsg::GetResult { locale } => to_binary(&query_result(deps, env, locale)?),
+        QueryMsg::GetTallyRounds { locale } => to_binary(&query_tally_rounds(deps, locale)?),
+        QueryMsg::ListStaleElections {} => to_binary(&query_stale_elections(deps, env)?),
+        QueryMsg::GetLedger {} => to_binary(&query_ledger(deps, env)?),
+        QueryMsg::GetBallot { voter } => to_binary(&query_ballot(deps, voter)?),
+        QueryMsg::GetParticipation {} => to_binary(&query_participation(deps)?),
+        QueryMsg::GetHistory { voter } => to_binary(&query_history(deps, voter)?),
+        QueryMsg::VoterSetDiff { from, to } => {
+            to_binary(&query_voter_set_diff(deps, env, from, to)?)
+        }
+        QueryMsg::GetTemplate { name } => to_binary(&TemplateResponse {
+            params: templates_read(&deps.storage).load(name.as_bytes())?,
+            name,
+        }),
+        QueryMsg::ListSpawnedElections {} => to_binary(&query_spawned_elections(deps)?),
+        QueryMsg::ListElections {
+            status_filter,
+            tag_filter,
+            start_after,
+            limit,
+        } => to_binary(&query_elections(
+            deps,
+            env,
+            status_filter,
+            tag_filter,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ListVoters { start_after, limit } => {
+            to_binary(&query_voters(deps, start_after, limit)?)
+        }
+        QueryMsg::GetVoterRoll {} => to_binary(&query_voter_roll(deps)?),
+        QueryMsg::GetStats {} => to_binary(&query_stats(deps)?),
+        QueryMsg::ExportSnapshot { start_after, limit } => {
+            to_binary(&query_snapshot(deps, start_after, limit)?)
+        }
+        QueryMsg::ListCandidateProposals { start_after, limit } => {
+            to_binary(&query_candidate_proposals(deps, start_after, limit)?)
+        }
+        QueryMsg::GetContributions { candidate } => to_binary(&ContributionsResponse {
+            total: campaign_totals_read(&deps.storage)
+                .may_load(deps.api.canonical_address(&candidate)?.as_slice())?
+                .unwrap_or_default(),
+            candidate,
+        }),
+        QueryMsg::GetLockedBalance { voter } => to_binary(&query_locked_balance(deps, voter)?),
+        QueryMsg::GetAuditLog { start_after, limit } => {
+            to_binary(&query_audit_log(deps, start_after, limit)?)
+        }
+        QueryMsg::GetProxy { principal } => to_binary(&query_proxy(deps, principal)?),
+        QueryMsg::ListChallenges {} => to_binary(&query_challenges(deps)?),
+        QueryMsg::ListDisclosures {
+            candidate,
+            start_after,
+            limit,
+        } => to_binary(&query_disclosures(deps, candidate, start_after, limit)?),
+        QueryMsg::GetCandidateVotes {
+            candidate,
+            start_after,
+            limit,
+        } => to_binary(&query_candidate_votes(deps, candidate, start_after, limit)?),
+        QueryMsg::GetCandidateCount { candidate } => {
+            to_binary(&query_candidate_count(deps, candidate)?)
+        }
+        QueryMsg::GetLeaderboard { limit } => to_binary(&query_leaderboard(deps, limit)?),
+        QueryMsg::GetExtensions {} => to_binary(&query_extensions(deps)?),
+        QueryMsg::CanVote { voter, candidate } => {
+            to_binary(&query_can_vote(deps, &env, voter, candidate)?)
+        }
+        QueryMsg::GetConfig {} => to_binary(&query_config(deps)?),
+        QueryMsg::GetTallyProof {} => to_binary(&tally_proof_read(&deps.storage).load()?),
+        QueryMsg::SimulateTally { method } => to_binary(&query_simulate_tally(deps, method)?),
+        QueryMsg::GetReferendum {} => to_binary(&query_referendum(deps)?),
+        QueryMsg::GetArchivedResult { election_id } => {
+            to_binary(&archives_read(&deps.storage).load(&election_id.to_be_bytes())?)
+        }
+        QueryMsg::Multi { queries } => to_binary(&query_multi(deps, env, queries)?),
+    }
+}
+
+fn query_referendum<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ReferendumResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let referendum = state
+        .referendum
+        .ok_or_else(|| StdError::not_found("referendum"))?;
+    Ok(ReferendumResponse {
+        title: referendum.title,
+        description: referendum.description,
+        tally: referendum_tally_read(&deps.storage)
+            .may_load()?
+            .unwrap_or_default(),
+        status: referendum_outcome_read(&deps.storage)
+            .may_load()?
+            .map(|outcome| outcome.status),
+    })
+}
+
+fn query_can_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+    voter: HumanAddr,
+    candidate: HumanAddr,
+) -> StdResult<CanVoteResponse> {
+    let check = match check_vote(deps, env, &voter, &candidate) {
+        Ok(weight) => {
+            return Ok(CanVoteResponse {
+                check: VoteCheck::Eligible,
+                weight,
+            })
+        }
+        Err(ContractError::NotAllowance { begin, end }) => VoteCheck::OutsideWindow { begin, end },
+        Err(ContractError::NotRegistered { .. }) => VoteCheck::NotRegistered,
+        Err(ContractError::NotEligible { .. }) => VoteCheck::NotWhitelisted,
+        Err(ContractError::UnknownCandidate { .. }) => VoteCheck::UnknownCandidate,
+        Err(ContractError::CandidateWithdrawn { .. }) => VoteCheck::CandidateWithdrawn,
+        Err(ContractError::AlreadyVoted { .. }) => VoteCheck::AlreadyVoted,
+        Err(ContractError::Std(err)) => return Err(err),
+        Err(err) => VoteCheck::Rejected {
+            reason: err.to_string(),
+        },
+    };
+    Ok(CanVoteResponse { check, weight: 0 })
+}
+
+/// check_vote runs the checks of a first round `Vote` and returns the
+/// weight it would be counted with
+fn check_vote<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+    voter: &HumanAddr,
+    candidate: &HumanAddr,
+) -> Result<u64, ContractError> {
+    let state = config_read(&deps.storage).load()?;
+    if state.referendum.is_some() {
+        return Err(ContractError::ReferendumBallotRequired {});
+    }
+    check_voter(deps, &state, env, voter)?;
+    let candidate_raw = deps.api.canonical_address(candidate)?;
+    if state.write_ins.is_none() && !state.candidates.contains(&candidate_raw) {
+        return Err(ContractError::UnknownCandidate {
+            candidate: candidate.clone(),
+        });
+    }
+    if state.withdrawn.contains(&candidate_raw) {
+        return Err(ContractError::CandidateWithdrawn {
+            candidate: candidate.clone(),
+        });
+    }
+    if let Some(CandidateRules {
+        no_self_vote: true, ..
+    }) = &state.candidate_rules
+    {
+        if candidate == voter {
+            return Err(ContractError::SelfVote {
+                voter: voter.clone(),
+            });
+        }
+    }
+    let voter_raw = voter_key(&deps.api, &state, voter)?;
+    if state.votes.iter().any(|vote| vote.voter == voter_raw) {
+        return Err(ContractError::AlreadyVoted {
+            voter: voter.clone(),
+        });
+    }
+    let (weight, _) = lookup_weight(deps, &state, voter)?;
+    Ok(decayed_weight(&state, env.block.height, weight)?)
+}
+
+/// query_candidate_count only loads the election config, to tell a
+/// candidate without ballots from an unknown address, if nothing was counted
+fn query_candidate_count<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    candidate: HumanAddr,
+) -> StdResult<CandidateCountResponse> {
+    let candidate_raw = deps.api.canonical_address(&candidate)?;
+    let counted = match candidate_counts_read(&deps.storage).may_load(candidate_raw.as_slice())? {
+        Some(counted) => counted,
+        None if config_read(&deps.storage)
+            .load()?
+            .candidates
+            .contains(&candidate_raw) =>
+        {
+            CandidateCount::default()
+        }
+        None => return Err(StdError::not_found("candidate")),
+    };
+    Ok(CandidateCountResponse {
+        candidate,
+        count: counted.count,
+        weight: counted.weight,
+    })
+}
+
+fn query_leaderboard<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    limit: Option<u32>,
+) -> StdResult<LeaderboardResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_SNAPSHOT_LIMIT)
+        .min(MAX_SNAPSHOT_LIMIT) as usize;
+    let candidates = leaderboard_read(&deps.storage)
+        .range(None, None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            let (key, counted) = item?;
+            Ok(CandidateCountResponse {
+                candidate: deps.api.human_address(&CanonicalAddr::from(&key[8..]))?,
+                count: counted.count,
+                weight: counted.weight,
+            })
+        })
+        .collect::<StdResult<_>>()?;
+    Ok(LeaderboardResponse { candidates })
+}
+
+fn query_config<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ConfigResponse> {
+    let state = config_read(&deps.storage).load()?;
+    Ok(ConfigResponse {
+        owner: state.owner,
+        pending_owner: state.pending_owner,
+        roles: state.roles,
+        start: state.start,
+        end: state.end,
+        seats: state.seats,
+        voting_system: state.voting_system,
+        eligibility: state.eligibility.map(eligibility_params),
+        denom: state.denom,
+        min_votes: state.min_votes,
+        party_list: state.party_list,
+        metadata: state.metadata,
+        ballot_fee: state.ballot_fee,
+        anti_snipe: state.anti_snipe,
+        history: state.history,
+        questions: state.questions,
+        pseudonymous: state.voter_salt.is_some(),
+    })
+}
+
+fn query_extensions<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ExtensionsResponse> {
+    let state = config_read(&deps.storage).load()?;
+    Ok(ExtensionsResponse {
+        end: state.end,
+        extensions: state.extensions,
+    })
+}
+
+fn eligibility_params(filter: EligibilityFilter) -> EligibilityParams {
+    EligibilityParams {
+        bits: filter.bits.len() as u64 * 8,
+        hashes: filter.hashes,
+        members: filter.members,
+        excluded: filter.excluded.len() as u32,
+    }
+}
+
+/// query_multi answers each query in turn. A failing query fails the whole
+/// batch, and batches may not be nested.
+fn query_multi<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: Env,
+    queries: Vec<QueryMsg>,
+) -> StdResult<MultiResponse> {
+    if queries.len() > MAX_MULTI_QUERIES {
+        return Err(StdError::generic_err(format!(
+            "at most {} queries per batch",
+            MAX_MULTI_QUERIES
+        )));
+    }
+    let mut responses = vec![];
+    for msg in queries {
+        if let QueryMsg::Multi { .. } = msg {
+            return Err(StdError::generic_err("nested multi query"));
+        }
+        responses.push(query(deps, env.clone(), msg)?);
+    }
+    Ok(MultiResponse { responses })
+}
+
+/// canonicalize_addrs converts addresses to the form they are stored in
+fn canonicalize_addrs<A: Api>(api: &A, addrs: &[HumanAddr]) -> StdResult<Vec<CanonicalAddr>> {
+    addrs
+        .iter()
+        .map(|addr| api.canonical_address(addr))
+        .collect()
+}
+
+fn humanize_addrs<A: Api>(api: &A, addrs: &[CanonicalAddr]) -> StdResult<Vec<HumanAddr>> {
+    addrs.iter().map(|addr| api.human_address(addr)).collect()
+}
+
+/// humanize_voter is the address clients see for a stored voter key, the hex
+/// of the key if the election is pseudonymous
+fn humanize_voter<A: Api>(api: &A, state: &State, voter: &CanonicalAddr) -> StdResult<HumanAddr> {
+    match state.voter_salt {
+        Some(_) => Ok(voter
+            .as_slice()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect::<String>()
+            .into()),
+        None => api.human_address(voter),
+    }
+}
+
+/// cursor_key is the stored key of a voter as a query returned it, undoing
+/// `humanize_voter`
+fn cursor_key<A: Api>(api: &A, state: &State, voter: &HumanAddr) -> StdResult<CanonicalAddr> {
+    if state.voter_salt.is_none() {
+        return api.canonical_address(voter);
+    }
+    let hex = voter.as_str();
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| {
+            hex.get(i..i + 2)
+                .and_then(|b| u8::from_str_radix(b, 16).ok())
+        })
+        .collect::<Option<Vec<u8>>>()
+        .map(|key| CanonicalAddr(Binary(key)))
+        .ok_or_else(|| StdError::generic_err("voter is not a pseudonym"))
+}
+
+/// humanize_vote converts a stored ballot to the addresses clients see
+fn humanize_vote<A: Api>(api: &A, state: &State, vote: &VoteInfo) -> StdResult<BallotResponse> {
+    let mut allocations = vec![];
+    for allocation in &vote.allocations {
+        allocations.push(Allocation {
+            candidate: api.human_address(&allocation.candidate)?,
+            share: allocation.share,
+            weight: allocation.weight,
+        });
+    }
+    Ok(BallotResponse {
+        voter: humanize_voter(api, state, &vote.voter)?,
+        candidate: api.human_address(&vote.candidate)?,
+        ranking: humanize_addrs(api, &vote.ranking)?,
+        weight: vote.weight,
+        allocations,
+        token_id: vote.token_id.clone(),
+    })
+}
+
+fn humanize_votes<A: Api>(
+    api: &A,
+    state: &State,
+    votes: &[VoteInfo],
+) -> StdResult<Vec<BallotResponse>> {
+    votes
+        .iter()
+        .map(|vote| humanize_vote(api, state, vote))
+        .collect()
+}
+
+/// advance_tally adds up to `limit` more ballots to the tally cursor and
+/// returns how many are left
+fn advance_tally<A: Api>(api: &A, state: &mut State, limit: u32) -> StdResult<usize> {
+    let mut cursor = state.tally_cursor.take().unwrap_or_default();
+    let start = cursor.position as usize;
+    let end = (start + limit as usize).min(state.votes.len());
+    let mut counts: HashMap<HumanAddr, Vote> = cursor
+        .counts
+        .into_iter()
+        .map(|vote| (vote.candidate.clone(), vote))
+        .collect();
+    for (candidate, batch) in count_votes(&humanize_votes(api, state, &state.votes[start..end])?)? {
+        let entry = counts.entry(candidate).or_insert_with(|| Vote {
+            weight: Uint128::zero(),
+            count: Uint128::zero(),
+            ..batch.clone()
+        });
+        entry.count += batch.count;
+        entry.weight = entry
+            .weight
+            .u128()
+            .checked_add(batch.weight.u128())
+            .map(Uint128)
+            .ok_or_else(|| StdError::generic_err("tally overflow"))?;
+    }
+    let mut counts: Vec<Vote> = counts.into_iter().map(|(_, vote)| vote).collect();
+    counts.sort_by(|a, b| a.candidate.cmp(&b.candidate));
+    cursor.position = end as u64;
+    cursor.counts = counts;
+    state.tally_cursor = Some(cursor);
+    Ok(state.votes.len() - end)
+}
+
+fn count_votes(votes: &[BallotResponse]) -> StdResult<HashMap<HumanAddr, Vote>> {
+    let mut vote_info = HashMap::new();
+    let mut add = |candidate: &HumanAddr, weight: u64| -> StdResult<()> {
+        let entry = vote_info.entry(candidate.clone()).or_insert_with(|| Vote {
+            candidate: candidate.clone(),
+            count: Uint128::zero(),
+            weight: Uint128::zero(),
+            label: None,
+        });
+        entry.count = checked_add(entry.count, 1)?;
+        entry.weight = checked_add(entry.weight, weight)?;
+        Ok(())
+    };
+    for vote in votes {
+        if vote.allocations.is_empty() {
+            add(&vote.candidate, vote.weight)?;
+        }
+        for allocation in &vote.allocations {
+            add(&allocation.candidate, allocation.weight)?;
+        }
+    }
+    Ok(vote_info)
+}
+
+/// rank_candidates orders every registered or voted-for candidate by weight,
+/// highest first. Ties are broken by candidate address so that every node
+/// selects the same winners.
+/// standing_candidates are the candidates that did not withdraw
+fn standing_candidates(state: &State) -> Vec<CanonicalAddr> {
+    state
+        .candidates
+        .iter()
+        .filter(|c| !state.withdrawn.contains(c))
+        .cloned()
+        .collect()
+}
+
+fn rank_candidates<A: Api>(api: &A, state: &State) -> StdResult<Vec<Vote>> {
+    // a finished batched count stands in for counting every ballot again
+    let mut counts = match &state.tally_cursor {
+        Some(cursor) if cursor.position as usize == state.votes.len() => cursor
+            .counts
+            .iter()
+            .map(|vote| (vote.candidate.clone(), vote.clone()))
+            .collect(),
+        _ => count_votes(&humanize_votes(api, state, &state.votes)?)?,
+    };
+    for candidate in humanize_addrs(api, &standing_candidates(state))? {
+        counts.entry(candidate.clone()).or_insert_with(|| Vote {
+            candidate: candidate.clone(),
+            count: Uint128::zero(),
+            weight: Uint128::zero(),
+            label: None,
+        });
+    }
+    let mut ranked: Vec<Vote> = counts.into_iter().map(|(_, vote)| vote).collect();
+    ranked.sort_by(|a, b| b.weight.cmp(&a.weight).then(a.candidate.cmp(&b.candidate)));
+    Ok(ranked)
+}
+
+/// label_for returns the label of `candidate` in `locale`, if there is one
+fn label_for<S: Storage, A: Api>(
+    storage: &S,
+    api: &A,
+    candidate: &HumanAddr,
+    locale: &Option<String>,
+) -> StdResult<Option<String>> {
+    let locale = match locale {
+        Some(locale) => locale,
+        None => return Ok(None),
+    };
+    let candidate = api.canonical_address(candidate)?;
+    let labels: Vec<LocalizedLabel> = labels_read(storage)
+        .may_load(candidate.as_slice())?
+        .unwrap_or_default();
+    Ok(labels
+        .into_iter()
+        .find(|l| &l.locale == locale)
+        .map(|l| l.label))
+}
+
+fn query_vote_info<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    locale: Option<String>,
+    sort: Option<VoteSort>,
+) -> StdResult<VoteResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let ballots = humanize_votes(&deps.api, &state, &state.votes)?;
+    let mut votes: Vec<Vote> = count_votes(&ballots)?
+        .into_iter()
+        .map(|(_, vote)| vote)
+        .collect();
+    if let Some(write_ins) = &state.write_ins {
+        let candidates = humanize_addrs(&deps.api, &state.candidates)?;
+        votes.retain(|vote| {
+            candidates.contains(&vote.candidate) || vote.count >= write_ins.min_count
+        });
+    }
+    // the counts come out of a map, sort them so every node answers the same
+    match sort.unwrap_or_default() {
+        VoteSort::Count => {
+            votes.sort_by(|a, b| b.count.cmp(&a.count).then(a.candidate.cmp(&b.candidate)))
+        }
+        VoteSort::Candidate => votes.sort_by(|a, b| a.candidate.cmp(&b.candidate)),
+    }
+    for vote in votes.iter_mut() {
+        vote.label = label_for(&deps.storage, &deps.api, &vote.candidate, &locale)?;
+    }
+    Ok(VoteResponse {
+        votes,
+        start: state.start,
+        end: state.end,
+        round: round(&state),
+    })
+}
+
+fn query_result<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: Env,
+    locale: Option<String>,
+) -> StdResult<ResultResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let result = result_read(&deps.storage).load()?;
+    let status = result_status(&deps.storage, &state, &result, env.block.height)?;
+    let mut winners = vec![];
+    for winner in result.winners {
+        winners.push(Vote {
+            label: label_for(&deps.storage, &deps.api, &winner.candidate, &locale)?,
+            candidate: winner.candidate,
+            count: winner.count,
+            weight: winner.weight,
+        });
+    }
+    Ok(ResultResponse {
+        seats: state.seats,
+        finalized_at: result.finalized_at,
+        winners,
+        tie_policy: result.tie_policy,
+        tied: result.tied,
+        round: round(&state),
+        vetoed_at: result.vetoed_at,
+        voting_system: result.voting_system,
+        status,
+        questions: question_outcomes(&deps.storage, &state.questions)?,
+    })
+}
+
+fn query_simulate_tally<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    method: Option<VotingSystem>,
+) -> StdResult<SimulatedTallyResponse> {
+    let mut state = config_read(&deps.storage).load()?;
+    if let Some(method) = method {
+        state.voting_system = method;
+    }
+    if state.voting_system.is_ranked() {
+        for vote in state
+            .votes
+            .iter_mut()
+            .filter(|vote| vote.ranking.is_empty())
+        {
+            vote.ranking = vec![vote.candidate.clone()];
+        }
+    }
+    let count = count_winners(&deps.api, &state)?;
+    Ok(SimulatedTallyResponse {
+        voting_system: state.voting_system,
+        ballots: state.votes.len() as u64,
+        winners: count.winners,
+        tied: count.tied,
+    })
+}
+
+fn query_tally_rounds<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    locale: Option<String>,
+) -> StdResult<TallyRoundsResponse> {
+    let mut rounds = tally_rounds_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default();
+    for tally in rounds.iter_mut().flat_map(|round| round.tallies.iter_mut()) {
+        tally.label = label_for(&deps.storage, &deps.api, &tally.candidate, &locale)?;
+    }
+    Ok(TallyRoundsResponse { rounds })
+}
+
+fn query_stale_elections<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<StaleElectionsResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let mut elections = vec![];
+    let end = voting_end(&state);
+    if env.block.height > end && result_read(&deps.storage).may_load()?.is_none() {
+        elections.push(StaleElection {
+            address: env.contract.address,
+            end,
+            stale_blocks: env.block.height - end,
+        });
+    }
+    Ok(StaleElectionsResponse { elections })
+}
+
+fn query_ledger<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: Env,
+) -> StdResult<LedgerResponse> {
+    let ledger = ledger_read(&deps.storage).may_load()?.unwrap_or_default();
+    let balance = deps.querier.query_all_balances(env.contract.address)?;
+    Ok(LedgerResponse {
+        solvent: ledger.check(&balance).is_ok(),
+        buckets: ledger.balances,
+        balance,
+    })
+}
+
+fn query_ballot<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    voter: HumanAddr,
+) -> StdResult<BallotResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let voter_raw = voter_key(&deps.api, &state, &voter)?;
+    let vote = state
+        .votes
+        .iter()
+        .find(|vote| vote.voter == voter_raw)
+        .ok_or_else(|| StdError::not_found("ballot"))?;
+    humanize_vote(&deps.api, &state, vote)
+}
+
+fn query_history<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    voter: HumanAddr,
+) -> StdResult<HistoryResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let voter_raw = voter_key(&deps.api, &state, &voter)?;
+    Ok(HistoryResponse {
+        voted_at: participants_read(&deps.storage).may_load(voter_raw.as_slice())?,
+        registered: registered_read(&deps.storage)
+            .may_load(voter_raw.as_slice())?
+            .is_some(),
+    })
+}
+
+fn query_participation<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ParticipationResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let mut voters = vec![];
+    for vote in &state.votes {
+        voters.push(humanize_voter(&deps.api, &state, &vote.voter)?);
+    }
+    voters.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+    Ok(ParticipationResponse {
+        finalized_at: result_read(&deps.storage)
+            .may_load()?
+            .map(|result| result.finalized_at),
+        voters,
+    })
+}
+
+/// participation reads the voter set of `election`, which is either this
+/// contract or another instance of it
+fn participation<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: &Env,
+    election: &HumanAddr,
+) -> StdResult<Vec<HumanAddr>> {
+    let res = if election == &env.contract.address {
+        query_participation(deps)?
+    } else {
+        deps.querier
+            .query_wasm_smart(election, &QueryMsg::GetParticipation {})?
+    };
+    Ok(res.voters)
+}
+
+fn query_voter_set_diff<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: Env,
+    from: HumanAddr,
+    to: HumanAddr,
+) -> StdResult<VoterSetDiffResponse> {
+    let before = participation(deps, &env, &from)?;
+    let after = participation(deps, &env, &to)?;
+    let new_voters: Vec<HumanAddr> = after
+        .iter()
+        .filter(|voter| !before.contains(voter))
+        .cloned()
+        .collect();
+    let lapsed_voters: Vec<HumanAddr> = before
+        .iter()
+        .filter(|voter| !after.contains(voter))
+        .cloned()
+        .collect();
+    let retained = (before.len() - lapsed_voters.len()) as u32;
+    let retention_rate = if before.is_empty() {
+        Decimal::zero()
+    } else {
+        Decimal::from_ratio(retained, before.len() as u128)
+    };
+    Ok(VoterSetDiffResponse {
+        new_voters,
+        lapsed_voters,
+        retained,
+        retention_rate,
+    })
+}
+
+fn query_spawned_elections<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<SpawnedElectionsResponse> {
+    let elections = spawned_read(&deps.storage)
+        .may_load()?
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|e| e.address.is_some())
+        .collect();
+    Ok(SpawnedElectionsResponse { elections })
+}
+
+fn query_elections<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    env: Env,
+    status_filter: Option<ElectionStatus>,
+    tag_filter: Option<String>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<ElectionsResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_SNAPSHOT_LIMIT)
+        .min(MAX_SNAPSHOT_LIMIT) as usize;
+    let height = env.block.height;
+    let mut elections = vec![];
+    for spawned in spawned_read(&deps.storage).may_load()?.unwrap_or_default() {
+        let address = match spawned.address {
+            Some(address) if spawned.spawn_id > start_after.unwrap_or(0) => address,
+            _ => continue,
+        };
+        let status = if archives_read(&deps.storage)
+            .may_load(&spawned.spawn_id.to_be_bytes())?
+            .is_some()
+        {
+            ElectionStatus::Archived
+        } else if height > spawned.end {
+            ElectionStatus::Ended
+        } else if height >= spawned.start {
+            ElectionStatus::Active
+        } else {
+            ElectionStatus::Upcoming
+        };
+        let tagged = match (&tag_filter, &spawned.metadata) {
+            (None, _) => true,
+            (Some(tag), Some(metadata)) => metadata.tags.contains(tag),
+            (Some(_), None) => false,
+        };
+        if !tagged || status_filter.map_or(false, |filter| filter != status) {
+            continue;
+        }
+        elections.push(ElectionSummary {
+            spawn_id: spawned.spawn_id,
+            label: spawned.label,
+            address,
+            metadata: spawned.metadata,
+            start: spawned.start,
+            end: spawned.end,
+            status,
+        });
+        if elections.len() == limit {
+            break;
+        }
+    }
+    Ok(ElectionsResponse { elections })
+}
+
+fn query_voters<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+) -> StdResult<VotersResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_SNAPSHOT_LIMIT)
+        .min(MAX_SNAPSHOT_LIMIT) as usize;
+    let state = config_read(&deps.storage).load()?;
+    // the smallest key after `start_after`
+    let start = match start_after {
+        Some(voter) => {
+            let mut key = cursor_key(&deps.api, &state, &voter)?.as_slice().to_vec();
+            key.push(0);
+            Some(key)
+        }
+        None => None,
+    };
+    let voters = registered_read(&deps.storage)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.and_then(|(key, _)| humanize_voter(&deps.api, &state, &CanonicalAddr::from(key)))
+        })
+        .collect::<StdResult<Vec<HumanAddr>>>()?;
+    Ok(VotersResponse { voters })
+}
+
+fn query_candidate_proposals<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+) -> StdResult<CandidateProposalsResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_SNAPSHOT_LIMIT)
+        .min(MAX_SNAPSHOT_LIMIT) as usize;
+    let start = start_after.map(|candidate| {
+        let mut key = candidate.as_bytes().to_vec();
+        key.push(0);
+        key
+    });
+    let proposals = proposals_read(&deps.storage)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, proposal)| proposal))
+        .collect::<StdResult<Vec<CandidateProposal>>>()?;
+    Ok(CandidateProposalsResponse { proposals })
+}
+
+fn query_challenges<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<ChallengesResponse> {
+    let challenges = challenges_read(&deps.storage)
+        .range(None, None, Order::Ascending)
+        .map(|item| item.map(|(_, challenge)| challenge))
+        .collect::<StdResult<Vec<Challenge>>>()?;
+    Ok(ChallengesResponse { challenges })
+}
+
+fn query_audit_log<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<AuditLogResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_SNAPSHOT_LIMIT)
+        .min(MAX_SNAPSHOT_LIMIT) as usize;
+    let start = start_after.map(|id| (id + 1).to_be_bytes());
+    let entries = audit_log_read(&deps.storage)
+        .range(start.as_ref().map(|s| &s[..]), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, entry)| entry))
+        .collect::<StdResult<Vec<AuditEntry>>>()?;
+    Ok(AuditLogResponse { entries })
+}
+
+fn query_disclosures<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    candidate: HumanAddr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<DisclosuresResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_SNAPSHOT_LIMIT)
+        .min(MAX_SNAPSHOT_LIMIT) as usize;
+    let start = start_after.map(|id| (id + 1).to_be_bytes());
+    let disclosures = disclosures_read(&deps.storage, &candidate)
+        .range(start.as_ref().map(|s| &s[..]), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(_, disclosure)| disclosure))
+        .collect::<StdResult<Vec<Disclosure>>>()?;
+    Ok(DisclosuresResponse { disclosures })
+}
+
+fn query_candidate_votes<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    candidate: HumanAddr,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+) -> StdResult<CandidateVotesResponse> {
+    let limit = limit
+        .unwrap_or(DEFAULT_SNAPSHOT_LIMIT)
+        .min(MAX_SNAPSHOT_LIMIT) as usize;
+    let state = config_read(&deps.storage).load()?;
+    let start = match start_after {
+        Some(voter) => {
+            let mut key = cursor_key(&deps.api, &state, &voter)?.as_slice().to_vec();
+            key.push(0);
+            Some(key)
+        }
+        None => None,
+    };
+    let candidate = deps.api.canonical_address(&candidate)?;
+    let votes = candidate_votes_read(&deps.storage, &candidate)
+        .range(start.as_deref(), None, Order::Ascending)
+        .take(limit)
+        .map(|item| {
+            item.and_then(|(key, weight)| {
+                Ok(CandidateVote {
+                    voter: humanize_voter(&deps.api, &state, &CanonicalAddr::from(key))?,
+                    weight,
+                })
+            })
+        })
+        .collect::<StdResult<Vec<CandidateVote>>>()?;
+    Ok(CandidateVotesResponse { votes })
+}
+
+fn query_voter_roll<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+) -> StdResult<VoterRollResponse> {
+    let snapshot = roll_snapshot_read(&deps.storage)
+        .may_load()?
+        .ok_or_else(|| StdError::not_found("voter roll"))?;
+    Ok(VoterRollResponse {
+        height: snapshot.height,
+        eligibility: snapshot.eligibility.map(eligibility_params),
+        registered: snapshot.registered,
+    })
+}
+
+fn query_stats<S: Storage, A: Api, Q: Querier>(deps: &Extern<S, A, Q>) -> StdResult<StatsResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let counters = stats_read(&deps.storage).may_load()?.unwrap_or_default();
+    let turnout = match (&state.registration, counters.registered) {
+        (Some(_), 0) => Some(Decimal::zero()),
+        (Some(_), registered) => Some(Decimal::from_ratio(counters.unique_voters, registered)),
+        (None, _) => None,
+    };
+    let width = histogram_width(&state);
+    let histogram = counters
+        .histogram
+        .iter()
+        .enumerate()
+        .map(|(i, ballots)| HistogramBucket {
+            start: state.start + i as u64 * width,
+            end: state.start + (i as u64 + 1) * width - 1,
+            ballots: *ballots,
+        })
+        .collect();
+    let mut totals = vec![];
+    for total in &counters.totals {
+        totals.push(Vote {
+            candidate: deps.api.human_address(&total.candidate)?,
+            count: Uint128::zero(),
+            weight: total.weight,
+            label: None,
+        });
+    }
+    totals.sort_by(|a, b| b.weight.cmp(&a.weight).then(a.candidate.cmp(&b.candidate)));
+    let margin = match totals.as_slice() {
+        [first, second, ..] => (first.weight - second.weight)?,
+        [first] => first.weight,
+        [] => Uint128::zero(),
+    };
+    Ok(StatsResponse {
+        ballots: counters.ballots,
+        unique_voters: counters.unique_voters,
+        turnout,
+        histogram,
+        leader: totals.into_iter().next().map(|t| t.candidate),
+        margin,
+    })
+}
+
+fn query_snapshot<S: Storage, A: Api, Q: Querier>(
+    deps: &Extern<S, A, Q>,
+    start_after: Option<HumanAddr>,
+    limit: Option<u32>,
+) -> StdResult<SnapshotResponse> {
+    let state = config_read(&deps.storage).load()?;
+    let limit = limit
+        .unwrap_or(DEFAULT_SNAPSHOT_LIMIT)
+        .min(MAX_SNAPSHOT_LIMIT) as usize;
+    let ballots = humanize_votes(&deps.api, &state, &state.votes)?;
+    let mut votes: Vec<&BallotResponse> = ballots
+        .iter()
+        .filter(|vote| {
+            start_after
+                .as_ref()
+                .map_or(true, |after| vote.voter.as_str() > after.as_str())
+        })
+        .collect();
+    votes.sort_by(|a, b| a.voter.as_str().cmp(b.voter.as_str()));
+    let next = if votes.len() > limit {
+        votes.truncate(limit);
+        votes.last().map(|vote| vote.voter.clone())
+    } else {
+        None
+    };
+    let chunk = encode_ballots(&votes);
+    Ok(SnapshotResponse {
+        schema_id: SCHEMA_ID.to_string(),
+        schema_version: SCHEMA_VERSION,
+        total: state.votes.len() as u32,
+        checksum: crc32(&chunk),
+        chunk: Binary(chunk),
+        next,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::msg::{
+        AntiSnipe, CohortWindow, FinalizationBounty, MajorityRunoff, MerkleRoll, Nomination,
+        ParticipationReward, PartyList, Referendum, ReferendumChoice, ReferendumResponse,
+        ReferendumStatus, RegistrationConfig, SeatAllocation, Slate, Succession, VetoCouncil,
+        WriteIns,
+    };
+    use cosmwasm_std::testing::{
+        mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+    };
+    use cosmwasm_std::{
+        coin, coins, from_binary, from_slice, Empty, FullDelegation, QuerierResult, QueryRequest,
+        SystemResult, Uint128, WasmQuery,
+    };
+
+    type WasmHandler = Box<dyn Fn(&HumanAddr, &Binary) -> StdResult<Binary>>;
+
+    /// WasmMockQuerier answers smart queries to other contracts with `wasm`
+    /// and hands everything else to the regular mock querier
+    struct WasmMockQuerier {
+        base: MockQuerier,
+        wasm: WasmHandler,
+    }
+
+    impl Querier for WasmMockQuerier {
+        fn raw_query(&self, bin_request: &[u8]) -> QuerierResult {
+            let request: QueryRequest<Empty> = from_slice(bin_request).unwrap();
+            match &request {
+                QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => {
+                    SystemResult::Ok((self.wasm)(contract_addr, msg).into())
+                }
+                _ => self.base.handle_query(&request),
+            }
+        }
+    }
+
+    fn with_wasm_querier<S: Storage, A: Api>(
+        deps: Extern<S, A, MockQuerier>,
+        wasm: impl Fn(&HumanAddr, &Binary) -> StdResult<Binary> + 'static,
+    ) -> Extern<S, A, WasmMockQuerier> {
+        Extern {
+            storage: deps.storage,
+            api: deps.api,
+            querier: WasmMockQuerier {
+                base: deps.querier,
+                wasm: Box::new(wasm),
+            },
+        }
+    }
+
+    /// init_msg describes a single seat plurality election open from height 10 to 100
+    fn init_msg() -> InitMsg {
+        InitMsg {
+            start: 10,
+            end: 100,
+            seats: 1,
+            voting_system: VotingSystem::Plurality,
+            tie_policy: TiePolicy::TieBreak,
+            withdrawal_policy: WithdrawalPolicy::Discard,
+            stake_weight: None,
+            oracle_fallback: OracleFallback::Pause,
+            nft_gate: None,
+            cohorts: vec![],
+            guardians: vec![],
+            roles: vec![],
+            dispute: None,
+            veto_council: None,
+            completion_hook: None,
+            referendum: None,
+            succession: None,
+            finalization_bounty: None,
+            participation_reward: None,
+            receipts: None,
+            limits: Limits::default(),
+            write_ins: None,
+            allow_contract_votes: false,
+            registry: None,
+            majority_runoff: None,
+            merkle_roll: None,
+            weight_decay: None,
+            eligibility: None,
+            registration: None,
+            election_code_id: None,
+            factory: None,
+            nomination: None,
+            candidate_rules: None,
+            denom: None,
+            min_votes: None,
+            party_list: None,
+            metadata: None,
+            ballot_fee: None,
+            anti_snipe: None,
+            history: None,
+            questions: vec![],
+            pseudonymous: false,
+            candidates: vec!["alice".into()],
+            labels: vec![],
+        }
+    }
+
+    #[test]
+    fn proper_initialization() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InitMsg {
+            start: 10,
+            end: 100,
+            seats: 1,
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        let info = mock_info("creator", &coins(1000, "earth"));
+
+        // we can just call .unwrap() to assert this was a success
+        let res = init(&mut deps, mock_env(), info, msg).unwrap();
+        assert_eq!(0, res.messages.len());
+
+        // it worked, let's query the state
+        let res = query(
+            &deps,
+            mock_env(),
+            QueryMsg::GetVoteInfo {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(10, value.start);
+        assert_eq!(100, value.end);
+    }
+
+    #[test]
+    fn vote() {
+        let mut deps = mock_dependencies(&coins(2, "token"));
+
+        let candidates: Vec<HumanAddr> = vec!["candidates1".into(), "candidates2".into()];
+        let msg = InitMsg {
+            start: 10_000,
+            end: 20_000,
+            seats: 1,
+            candidates,
+            ..init_msg()
+        };
+        let info = mock_info("creator", &coins(2, "token"));
+        let _res = init(&mut deps, mock_env(), info, msg).unwrap();
+
+        // beneficiary can release it
+        let info = mock_info("voter1", &coins(2, "token"));
+        let msg = HandleMsg::Vote {
+            candidate: "candidates1".into(),
+            proof: None,
+            answers: None,
+        };
+        let _res = handle(&mut deps, mock_env(), info, msg).unwrap();
+
+        // should increase counter by 1
+        let res = query(
+            &deps,
+            mock_env(),
+            QueryMsg::GetVoteInfo {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(10_000, value.start);
+        assert_eq!(20_000, value.end);
+        assert_eq!("candidates1", value.votes[0].candidate);
+        assert_eq!(Uint128(1), value.votes[0].count);
+    }
+
+    #[test]
+    fn finalize_selects_top_seats() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InitMsg {
+            start: 10,
+            end: 100,
+            seats: 2,
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        let info = mock_info("creator", &[]);
+        init(&mut deps, mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        for (voter, candidate) in &[
+            ("voter1", "carol"),
+            ("voter2", "carol"),
+            ("voter3", "bob"),
+            ("voter4", "alice"),
+        ] {
+            let msg = HandleMsg::Vote {
+                candidate: (*candidate).into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+
+        // cannot finalize while voting is still open
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::ElectionNotEnded { end } => assert_eq!(100, end),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+
+        // alice and bob tie, alice wins the second seat by address order
+        let res = query(&deps, env.clone(), QueryMsg::GetResult { locale: None }).unwrap();
+        let value: ResultResponse = from_binary(&res).unwrap();
+        assert_eq!(2, value.seats);
+        assert_eq!(101, value.finalized_at);
+        assert_eq!(
+            vec![
+                Vote {
+                    candidate: "carol".into(),
+                    count: Uint128(2),
+                    weight: Uint128(2),
+                    label: None,
+                },
+                Vote {
+                    candidate: "alice".into(),
+                    count: Uint128(1),
+                    weight: Uint128(1),
+                    label: None,
+                },
+            ],
+            value.winners
+        );
+
+        let err = handle(
+            &mut deps,
+            env,
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap_err();
+        match err {
+            ContractError::AlreadyFinalized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn init_rejects_zero_seats() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InitMsg {
+            start: 10,
+            end: 100,
+            seats: 0,
+            candidates: Vec::new(),
+            ..init_msg()
+        };
+        let err = init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        match err {
+            ContractError::InvalidSeats {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn stv_finalize_records_rounds() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InitMsg {
+            start: 10,
+            end: 100,
+            seats: 2,
+            voting_system: VotingSystem::Stv,
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        for i in 0..5 {
+            let msg = HandleMsg::RankedVote {
+                ranking: vec!["alice".into(), "carol".into()],
+            };
+            let info = mock_info(format!("voter{}", i), &[]);
+            handle(&mut deps, env.clone(), info, msg).unwrap();
+        }
+        let msg = HandleMsg::Vote {
+            candidate: "bob".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter5", &[]), msg).unwrap();
+
+        // rankings may only contain registered candidates, once each
+        let msg = HandleMsg::RankedVote {
+            ranking: vec!["alice".into(), "alice".into()],
+        };
+        let err = handle(&mut deps, env.clone(), mock_info("voter6", &[]), msg).unwrap_err();
+        match err {
+            ContractError::DuplicateRanking { candidate } => assert_eq!("alice", candidate),
+            e => panic!("unexpected error: {}", e),
+        }
+        let msg = HandleMsg::RankedVote {
+            ranking: vec!["dave".into()],
+        };
+        let err = handle(&mut deps, env.clone(), mock_info("voter6", &[]), msg).unwrap_err();
+        match err {
+            ContractError::UnknownCandidate { candidate } => assert_eq!("dave", candidate),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 101;
+        let info = mock_info("anyone", &[]);
+        handle(
+            &mut deps,
+            env.clone(),
+            info,
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+
+        // quota is 3: alice's surplus of 2 elects carol over bob
+        let res = query(&deps, env.clone(), QueryMsg::GetResult { locale: None }).unwrap();
+        let value: ResultResponse = from_binary(&res).unwrap();
+        let winners: Vec<HumanAddr> = value.winners.into_iter().map(|w| w.candidate).collect();
+        assert_eq!(vec![HumanAddr::from("alice"), "carol".into()], winners);
+
+        let res = query(&deps, env, QueryMsg::GetTallyRounds { locale: None }).unwrap();
+        let value: TallyRoundsResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.rounds.len());
+        assert_eq!(vec![HumanAddr::from("alice")], value.rounds[0].elected);
+        assert_eq!(Some("bob".into()), value.rounds[1].eliminated);
+    }
+
+    #[test]
+    fn plurality_rejects_ranked_ballot() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InitMsg {
+            start: 10_000,
+            end: 20_000,
+            seats: 1,
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let msg = HandleMsg::RankedVote {
+            ranking: vec!["alice".into()],
+        };
+        let err = handle(&mut deps, mock_env(), mock_info("voter", &[]), msg).unwrap_err();
+        match err {
+            ContractError::RankedBallotNotSupported {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn stale_election_is_reported() {
+        let mut deps = mock_dependencies(&[]);
+
+        let msg = InitMsg {
+            start: 10,
+            end: 100,
+            seats: 1,
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 100;
+        let res = query(&deps, env.clone(), QueryMsg::ListStaleElections {}).unwrap();
+        let value: StaleElectionsResponse = from_binary(&res).unwrap();
+        assert!(value.elections.is_empty());
+
+        env.block.height = 130;
+        let res = query(&deps, env.clone(), QueryMsg::ListStaleElections {}).unwrap();
+        let value: StaleElectionsResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            vec![StaleElection {
+                address: env.contract.address.clone(),
+                end: 100,
+                stale_blocks: 30,
+            }],
+            value.elections
+        );
+
+        // the first late vote emits the event without counting the ballot
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        let res = handle(&mut deps, env.clone(), mock_info("voter", &[]), msg.clone()).unwrap();
+        assert_eq!(attr("action", "stale_election"), res.attributes[0]);
+        assert_eq!(attr("stale_blocks", 30), res.attributes[2]);
+        let res = query(
+            &deps,
+            env.clone(),
+            QueryMsg::GetVoteInfo {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert!(value.votes.is_empty());
+
+        let err = handle(&mut deps, env.clone(), mock_info("voter", &[]), msg).unwrap_err();
+        match err {
+            ContractError::NotAllowance { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("keeper", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let res = query(&deps, env, QueryMsg::ListStaleElections {}).unwrap();
+        let value: StaleElectionsResponse = from_binary(&res).unwrap();
+        assert!(value.elections.is_empty());
+    }
+
+    #[test]
+    fn ledger_reports_buckets_against_balance() {
+        let mut deps = mock_dependencies(&coins(100, "earth"));
+
+        let msg = InitMsg {
+            start: 10,
+            end: 100,
+            seats: 1,
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let res = query(&deps, mock_env(), QueryMsg::GetLedger {}).unwrap();
+        let value: LedgerResponse = from_binary(&res).unwrap();
+        assert!(value.buckets.is_empty());
+        assert_eq!(coins(100, "earth"), value.balance);
+        assert!(value.solvent);
+
+        let mut books = Ledger::default();
+        books.deposit(FundBucket::PrizePool, &coins(150, "earth"));
+        ledger(&mut deps.storage).save(&books).unwrap();
+        let res = query(&deps, mock_env(), QueryMsg::GetLedger {}).unwrap();
+        let value: LedgerResponse = from_binary(&res).unwrap();
+        assert_eq!(FundBucket::PrizePool, value.buckets[0].bucket);
+        assert!(!value.solvent);
+    }
+
+    #[test]
+    fn native_stake_weights_ballots() {
+        let mut deps = mock_dependencies(&[]);
+        let delegation = |delegator: &str, amount: u128| FullDelegation {
+            delegator: delegator.into(),
+            validator: "validator".into(),
+            amount: coin(amount, "ustake"),
+            can_redelegate: coin(0, "ustake"),
+            accumulated_rewards: vec![],
+        };
+        deps.querier.update_staking(
+            "ustake",
+            &[],
+            &[delegation("whale", 500), delegation("minnow", 20)],
+        );
+
+        let msg = InitMsg {
+            seats: 1,
+            stake_weight: Some(StakeWeight::Native {}),
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = |candidate: &str| HandleMsg::Vote {
+            candidate: candidate.into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("whale", &[]), vote("bob")).unwrap();
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("minnow", &[]),
+            vote("alice"),
+        )
+        .unwrap();
+
+        // unstaked addresses cannot vote, nor can anyone vote twice
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("nobody", &[]),
+            vote("alice"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NoVotingPower { voter } => assert_eq!("nobody", voter),
+            e => panic!("unexpected error: {}", e),
+        }
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("whale", &[]),
+            vote("alice"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::AlreadyVoted { voter } => assert_eq!("whale", voter),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let whale = deps.api.canonical_address(&"whale".into()).unwrap();
+        let cached = voter_weights_read(&deps.storage)
+            .load(whale.as_slice())
+            .unwrap();
+        assert_eq!(500, cached.weight);
+        assert_eq!(50, cached.height);
+
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let res = query(&deps, env, QueryMsg::GetResult { locale: None }).unwrap();
+        let value: ResultResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            vec![Vote {
+                candidate: "bob".into(),
+                count: Uint128(1),
+                weight: Uint128(500),
+                label: None,
+            }],
+            value.winners
+        );
+    }
+
+    #[test]
+    fn staking_contract_weights_at_snapshot_height() {
+        let deps = mock_dependencies(&[]);
+        let mut deps = with_wasm_querier(deps, |contract, msg| {
+            assert_eq!("staking", contract.as_str());
+            match from_binary(msg)? {
+                StakingQueryMsg::StakedBalanceAtHeight { address, height } => {
+                    assert_eq!(Some(7), height);
+                    let balance = if address.as_str() == "staker" { 42 } else { 0 };
+                    to_binary(&StakedBalanceAtHeightResponse {
+                        balance: Uint128(balance),
+                        height: 7,
+                    })
+                }
+            }
+        });
+
+        let msg = InitMsg {
+            stake_weight: Some(StakeWeight::Contract {
+                address: "staking".into(),
+                snapshot_height: Some(7),
+            }),
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("staker", &[]), msg).unwrap();
+
+        let res = query(
+            &deps,
+            env,
+            QueryMsg::GetVoteInfo {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128(42), value.votes[0].weight);
+        assert_eq!(Uint128(1), value.votes[0].count);
+    }
+
+    #[test]
+    fn split_vote_counts_as_one_weighted_ballot() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.update_staking(
+            "ustake",
+            &[],
+            &[FullDelegation {
+                delegator: "org".into(),
+                validator: "validator".into(),
+                amount: coin(100, "ustake"),
+                can_redelegate: coin(0, "ustake"),
+                accumulated_rewards: vec![],
+            }],
+        );
+        let msg = InitMsg {
+            stake_weight: Some(StakeWeight::Native {}),
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let share = |candidate: &str, share: u64| Share {
+            candidate: candidate.into(),
+            share,
+        };
+        // members split 1:1:1, the rounding remainder goes to the first share
+        let msg = HandleMsg::SplitVote {
+            shares: vec![share("alice", 1), share("bob", 1), share("carol", 1)],
+        };
+        handle(&mut deps, env.clone(), mock_info("org", &[]), msg).unwrap();
+
+        let res = query(
+            &deps,
+            env.clone(),
+            QueryMsg::GetBallot {
+                voter: "org".into(),
+            },
+        )
+        .unwrap();
+        let ballot: BallotResponse = from_binary(&res).unwrap();
+        assert_eq!(100, ballot.weight);
+        let weights: Vec<u64> = ballot.allocations.iter().map(|a| a.weight).collect();
+        assert_eq!(vec![34, 33, 33], weights);
+
+        let msg = HandleMsg::SplitVote {
+            shares: vec![share("alice", 1), share("alice", 2)],
+        };
+        let err = handle(&mut deps, env.clone(), mock_info("org2", &[]), msg).unwrap_err();
+        match err {
+            ContractError::DuplicateShare { candidate } => assert_eq!("alice", candidate),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let res = query(
+            &deps,
+            env,
+            QueryMsg::GetVoteInfo {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+        let mut value: VoteResponse = from_binary(&res).unwrap();
+        value.votes.sort_by(|a, b| a.candidate.cmp(&b.candidate));
+        assert_eq!(Uint128(34), value.votes[0].weight);
+        assert_eq!(Uint128(33), value.votes[2].weight);
+    }
+
+    #[test]
+    fn withdrawn_share_of_split_ballot_is_redistributed() {
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.update_staking(
+            "ustake",
+            &[],
+            &[FullDelegation {
+                delegator: "org".into(),
+                validator: "validator".into(),
+                amount: coin(100, "ustake"),
+                can_redelegate: coin(0, "ustake"),
+                accumulated_rewards: vec![],
+            }],
+        );
+        let msg = InitMsg {
+            stake_weight: Some(StakeWeight::Native {}),
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let shares = vec![
+            Share {
+                candidate: "alice".into(),
+                share: 2,
+            },
+            Share {
+                candidate: "bob".into(),
+                share: 1,
+            },
+            Share {
+                candidate: "carol".into(),
+                share: 2,
+            },
+        ];
+        let msg = HandleMsg::SplitVote { shares };
+        handle(&mut deps, env.clone(), mock_info("org", &[]), msg).unwrap();
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("alice", &[]),
+            HandleMsg::WithdrawCandidacy {},
+        )
+        .unwrap();
+        assert_eq!(attr("discarded", "0"), res.attributes[2]);
+
+        // bob and carol split the whole weight 1:2
+        let msg = QueryMsg::GetBallot {
+            voter: "org".into(),
+        };
+        let ballot: BallotResponse = from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        let weights: Vec<(&str, u64)> = ballot
+            .allocations
+            .iter()
+            .map(|a| (a.candidate.as_str(), a.weight))
+            .collect();
+        assert_eq!(vec![("bob", 33), ("carol", 67)], weights);
+        let msg = QueryMsg::GetVoteInfo {
+            locale: None,
+            sort: None,
+        };
+        let value: VoteResponse = from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        let total: u128 = value.votes.iter().map(|vote| vote.weight.u128()).sum();
+        assert_eq!(100, total);
+        let stats = stats_read(&deps.storage).load().unwrap();
+        assert_eq!(1, stats.unique_voters);
+    }
+
+    #[test]
+    fn nft_gate_rejects_transferred_tokens() {
+        let deps = mock_dependencies(&[]);
+        // holder1 votes with token "1" and then transfers it to holder2
+        let mut deps = with_wasm_querier(deps, |contract, msg| {
+            assert_eq!("collection", contract.as_str());
+            match from_binary(msg)? {
+                Cw721QueryMsg::Tokens { owner, .. } => {
+                    let tokens = match owner.as_str() {
+                        "holder1" | "holder2" => vec!["1".to_string()],
+                        _ => vec![],
+                    };
+                    to_binary(&TokensResponse { tokens })
+                }
+            }
+        });
+        let msg = InitMsg {
+            nft_gate: Some("collection".into()),
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("holder1", &[]),
+            vote.clone(),
+        )
+        .unwrap();
+        let res = query(
+            &deps,
+            env.clone(),
+            QueryMsg::GetBallot {
+                voter: "holder1".into(),
+            },
+        )
+        .unwrap();
+        let ballot: BallotResponse = from_binary(&res).unwrap();
+        assert_eq!(Some("1".to_string()), ballot.token_id);
+
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("holder2", &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::TokenAlreadyUsed { voter } => assert_eq!("holder2", voter),
+            e => panic!("unexpected error: {}", e),
+        }
+        let err = handle(&mut deps, env, mock_info("outsider", &[]), vote).unwrap_err();
+        match err {
+            ContractError::NotTokenHolder { voter } => assert_eq!("outsider", voter),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn queries_return_labels_in_requested_locale() {
+        let mut deps = mock_dependencies(&[]);
+        let label = |locale: &str, label: &str| LocalizedLabel {
+            locale: locale.into(),
+            label: label.into(),
+        };
+        let msg = InitMsg {
+            candidates: vec!["option1".into(), "option2".into()],
+            labels: vec![CandidateLabels {
+                candidate: "option1".into(),
+                labels: vec![label("en", "Yes"), label("de", "Ja")],
+            }],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::Vote {
+            candidate: "option1".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter", &[]), msg).unwrap();
+
+        let msg = QueryMsg::GetVoteInfo {
+            locale: Some("de".into()),
+            sort: None,
+        };
+        let value: VoteResponse = from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        let vote = value
+            .votes
+            .iter()
+            .find(|v| v.candidate == "option1")
+            .unwrap();
+        assert_eq!(Some("Ja".to_string()), vote.label);
+
+        // unknown locales fall back to the bare id
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let msg = QueryMsg::GetResult {
+            locale: Some("fr".into()),
+        };
+        let value: ResultResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        assert_eq!("option1", value.winners[0].candidate);
+        assert_eq!(None, value.winners[0].label);
+    }
+
+    #[test]
+    fn init_rejects_duplicate_locale() {
+        let mut deps = mock_dependencies(&[]);
+        let label = |label: &str| LocalizedLabel {
+            locale: "en".into(),
+            label: label.into(),
+        };
+        let msg = InitMsg {
+            candidates: vec!["option1".into()],
+            labels: vec![CandidateLabels {
+                candidate: "option1".into(),
+                labels: vec![label("Yes"), label("Aye")],
+            }],
+            ..init_msg()
+        };
+        let err = init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        match err {
+            ContractError::InvalidLabel { locale, .. } => assert_eq!("en", locale),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn group_members_vote_with_their_weight() {
+        let deps = mock_dependencies(&[]);
+        let mut deps = with_wasm_querier(deps, |contract, msg| {
+            assert_eq!("group", contract.as_str());
+            match from_binary(msg)? {
+                Cw4QueryMsg::Member { addr, at_height } => {
+                    assert_eq!(Some(10), at_height);
+                    let weight = match addr.as_str() {
+                        "member" => Some(5),
+                        "idle" => Some(0),
+                        _ => None,
+                    };
+                    to_binary(&MemberResponse { weight })
+                }
+            }
+        });
+        let msg = InitMsg {
+            stake_weight: Some(StakeWeight::Group {
+                address: "group".into(),
+                snapshot: true,
+            }),
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("member", &[]),
+            vote.clone(),
+        )
+        .unwrap();
+
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("outsider", &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NotGroupMember { voter } => assert_eq!("outsider", voter),
+            e => panic!("unexpected error: {}", e),
+        }
+        let err = handle(&mut deps, env.clone(), mock_info("idle", &[]), vote).unwrap_err();
+        match err {
+            ContractError::NoVotingPower { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let res = query(
+            &deps,
+            env,
+            QueryMsg::GetVoteInfo {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128(5), value.votes[0].weight);
+    }
+
+    #[test]
+    fn batch_vote_reports_each_entry() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = HandleMsg::SetRelayer {
+            relayer: Some("relayer".into()),
+        };
+        handle(&mut deps, mock_env(), mock_info("member", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let entry = |voter: &str| BallotEntry {
+            voter: voter.into(),
+            ballot: Ballot::Vote {
+                candidate: "alice".into(),
+            },
+            idempotency_key: None,
+        };
+        let msg = HandleMsg::BatchVote {
+            votes: vec![
+                entry("relayer"),
+                entry("member"),
+                entry("stranger"),
+                entry("member"),
+            ],
+        };
+        let res = handle(&mut deps, env.clone(), mock_info("relayer", &[]), msg).unwrap();
+        assert_eq!(attr("accepted", 2), res.attributes[1]);
+        assert_eq!(attr("entry_0", "ok"), res.attributes[2]);
+        assert_eq!(attr("entry_1", "ok"), res.attributes[3]);
+        assert_eq!(
+            attr("entry_2", "failed: [E2] Unauthorized"),
+            res.attributes[4]
+        );
+        assert_eq!(
+            attr("entry_3", "failed: [E104] member has already voted"),
+            res.attributes[5]
+        );
+
+        let res = query(
+            &deps,
+            env,
+            QueryMsg::GetVoteInfo {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128(2), value.votes[0].count);
+    }
+
+    #[test]
+    fn export_snapshot_pages_by_voter() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for voter in &["carol", "bob", "dave"] {
+            let msg = HandleMsg::Vote {
+                candidate: "alice".into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+
+        let msg = QueryMsg::ExportSnapshot {
+            start_after: None,
+            limit: Some(2),
+        };
+        let page: SnapshotResponse = from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(3, page.total);
+        assert_eq!(Some("carol".into()), page.next);
+        assert_eq!(crc32(page.chunk.as_slice()), page.checksum);
+        // two ballots, the first one by bob
+        assert_eq!(&[0, 0, 0, 2, 0, 0, 0, 3], &page.chunk.as_slice()[..8]);
+        assert_eq!(b"bob", &page.chunk.as_slice()[8..11]);
+
+        let msg = QueryMsg::ExportSnapshot {
+            start_after: page.next,
+            limit: Some(2),
+        };
+        let page: SnapshotResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        assert_eq!(None, page.next);
+        assert_eq!(b"dave", &page.chunk.as_slice()[8..12]);
+    }
+
+    #[test]
+    fn cohorts_vote_in_their_own_window() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            cohorts: vec![CohortWindow {
+                name: "east".into(),
+                start: 10,
+                end: 40,
+                members: vec!["early".into()],
+            }],
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("early", &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NotAllowance { begin, end } => assert_eq!((10, 40), (begin, end)),
+            e => panic!("unexpected error: {}", e),
+        }
+        // voters outside any cohort use the election window
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            vote.clone(),
+        )
+        .unwrap();
+
+        env.block.height = 30;
+        handle(&mut deps, env, mock_info("early", &[]), vote).unwrap();
+    }
+
+    #[test]
+    fn batch_vote_skips_processed_idempotency_keys() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        for member in &["member1", "member2"] {
+            let msg = HandleMsg::SetRelayer {
+                relayer: Some("relayer".into()),
+            };
+            handle(&mut deps, mock_env(), mock_info(*member, &[]), msg).unwrap();
+        }
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let entry = |voter: &str, key: &str| BallotEntry {
+            voter: voter.into(),
+            ballot: Ballot::Vote {
+                candidate: "alice".into(),
+            },
+            idempotency_key: Some(key.into()),
+        };
+        let msg = HandleMsg::BatchVote {
+            votes: vec![entry("member1", "op-1")],
+        };
+        handle(&mut deps, env.clone(), mock_info("relayer", &[]), msg).unwrap();
+
+        // a retry delivering the same operation together with a new one
+        let msg = HandleMsg::BatchVote {
+            votes: vec![entry("member1", "op-1"), entry("member2", "op-2")],
+        };
+        let res = handle(&mut deps, env.clone(), mock_info("relayer", &[]), msg).unwrap();
+        assert_eq!(attr("accepted", 1), res.attributes[1]);
+        assert_eq!(attr("entry_0", "duplicate"), res.attributes[2]);
+        assert_eq!(attr("entry_1", "ok"), res.attributes[3]);
+
+        let res = query(
+            &deps,
+            env,
+            QueryMsg::GetVoteInfo {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128(2), value.votes[0].count);
+    }
+
+    #[test]
+    fn sudo_extends_and_halts() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 150;
+        sudo(&mut deps, env.clone(), SudoMsg::Extend { end: 200 }).unwrap();
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            vote.clone(),
+        )
+        .unwrap();
+
+        sudo(&mut deps, env.clone(), SudoMsg::Halt {}).unwrap();
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter2", &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Halted {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        sudo(&mut deps, env.clone(), SudoMsg::Resume {}).unwrap();
+        handle(&mut deps, env.clone(), mock_info("voter2", &[]), vote).unwrap();
+
+        let err = sudo(&mut deps, env.clone(), SudoMsg::Extend { end: 120 }).unwrap_err();
+        match err {
+            ContractError::InvalidExtension { end } => assert_eq!(200, end),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 201;
+        let res = sudo(&mut deps, env, SudoMsg::CheckPhase {}).unwrap();
+        assert_eq!(Some(&attr("phase", "finalized")), res.attributes.last());
+    }
+
+    #[test]
+    fn read_only_blocks_handles_but_not_queries() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let msg = HandleMsg::SetReadOnly { read_only: true };
+        let err = handle(&mut deps, mock_env(), mock_info("anyone", &[]), msg.clone()).unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter", &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::ReadOnly {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        query(
+            &deps,
+            env.clone(),
+            QueryMsg::GetVoteInfo {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+
+        let msg = HandleMsg::SetReadOnly { read_only: false };
+        handle(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        handle(&mut deps, env, mock_info("voter", &[]), vote).unwrap();
+    }
+
+    #[test]
+    fn guardian_pauses_voting() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            guardians: vec!["guardian".into()],
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let err = handle(
+            &mut deps,
+            mock_env(),
+            mock_info("anyone", &[]),
+            HandleMsg::Pause {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(
+            &mut deps,
+            mock_env(),
+            mock_info("guardian", &[]),
+            HandleMsg::Pause {},
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter", &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Paused {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        handle(
+            &mut deps,
+            mock_env(),
+            mock_info("creator", &[]),
+            HandleMsg::Unpause {},
+        )
+        .unwrap();
+        handle(&mut deps, env, mock_info("voter", &[]), vote).unwrap();
+    }
+
+    #[test]
+    fn finalize_pays_prize_pool_to_winners() {
+        let mut deps = mock_dependencies(&coins(101, "earth"));
+        let msg = InitMsg {
+            seats: 2,
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        let info = mock_info("creator", &coins(101, "earth"));
+        init(&mut deps, mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        for (voter, candidate) in &[("voter1", "alice"), ("voter2", "alice"), ("voter3", "bob")] {
+            let msg = HandleMsg::Vote {
+                candidate: (*candidate).into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+
+        env.block.height = 101;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let send = |to: &str, amount: u128| -> CosmosMsg {
+            BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: to.into(),
+                amount: coins(amount, "earth"),
+            }
+            .into()
+        };
+        // 2:1 split of 101, the dust goes to the first winner
+        assert_eq!(vec![send("alice", 68), send("bob", 33)], res.messages);
+
+        let books = ledger_read(&deps.storage).load().unwrap();
+        assert!(books.bucket(FundBucket::PrizePool).is_empty());
+    }
+
+    #[test]
+    fn confirmed_challenge_forfeits_bond() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            dispute: Some(DisputeConfig {
+                window: 10,
+                bond: coin(50, "earth"),
+                treasury: "treasury".into(),
+            }),
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+
+        let msg = HandleMsg::ChallengeResult {};
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("challenger", &coins(49, "earth")),
+            msg.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InsufficientBond { amount, .. } => assert_eq!(Uint128(50), amount),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("challenger", &coins(50, "earth")),
+            msg.clone(),
+        )
+        .unwrap();
+        assert_eq!(attr("discrepancy", false), res.attributes[2]);
+        let forfeit: CosmosMsg = BankMsg::Send {
+            from_address: env.contract.address.clone(),
+            to_address: "treasury".into(),
+            amount: coins(50, "earth"),
+        }
+        .into();
+        assert_eq!(vec![forfeit], res.messages);
+
+        env.block.height = 112;
+        let err = handle(
+            &mut deps,
+            env,
+            mock_info("challenger", &coins(50, "earth")),
+            msg,
+        )
+        .unwrap_err();
+        match err {
+            ContractError::DisputeWindowClosed { end } => assert_eq!(111, end),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn challenge_respects_succession_and_invalid_results() {
+        let dispute = Some(DisputeConfig {
+            window: 10,
+            bond: coin(50, "earth"),
+            treasury: "treasury".into(),
+        });
+        let challenge = |deps: &mut Extern<_, _, _>, env: &Env| {
+            let info = mock_info("challenger", &coins(50, "earth"));
+            handle(deps, env.clone(), info, HandleMsg::ChallengeResult {}).unwrap_err()
+        };
+
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            dispute: dispute.clone(),
+            succession: Some(Succession {
+                window: 10,
+                arbiter: None,
+            }),
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap();
+        env.block.height = 101;
+        let msg = HandleMsg::Finalize { limit: None };
+        handle(&mut deps, env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        let msg = HandleMsg::PromoteRunnerUp {
+            vacated: "alice".into(),
+        };
+        handle(&mut deps, env.clone(), mock_info("creator", &[]), msg).unwrap();
+        match challenge(&mut deps, &env) {
+            ContractError::SuccessionApplied {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let stored = result_read(&deps.storage).load().unwrap();
+        assert_eq!(HumanAddr::from("bob"), stored.winners[0].candidate);
+
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            dispute,
+            min_votes: Some(2),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = HandleMsg::Finalize { limit: None };
+        handle(&mut deps, env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        match challenge(&mut deps, &env) {
+            ContractError::ElectionInvalid {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let stored = result_read(&deps.storage).load().unwrap();
+        assert!(stored.invalid && stored.winners.is_empty());
+    }
+
+    #[test]
+    fn finalize_notifies_completion_hook() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            completion_hook: Some("registry".into()),
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 101;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let hook = CompletionHookMsg::ElectionFinalized {
+            election: env.contract.address,
+            finalized_at: 101,
+            winners: vec![Winner {
+                candidate: "alice".into(),
+                count: Uint128(0),
+                weight: Uint128(0),
+            }],
+        };
+        let expected: CosmosMsg = WasmMsg::Execute {
+            contract_addr: "registry".into(),
+            msg: to_binary(&hook).unwrap(),
+            send: vec![],
+        }
+        .into();
+        assert_eq!(vec![expected], res.messages);
+    }
+
+    #[test]
+    fn dispute_window_holds_prize_and_announcements() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            dispute: Some(DisputeConfig {
+                window: 10,
+                bond: coin(50, "earth"),
+                treasury: "treasury".into(),
+            }),
+            completion_hook: Some("hook".into()),
+            registry: Some(RegistryConfig {
+                address: "kv".into(),
+                key_template: "elections/{election}".into(),
+                deferred: false,
+            }),
+            ..init_msg()
+        };
+        let info = mock_info("creator", &coins(100, "earth"));
+        init(&mut deps, mock_env(), info, msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap();
+        env.block.height = 101;
+        let msg = HandleMsg::Finalize { limit: None };
+        let res = handle(&mut deps, env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        assert!(res.messages.is_empty());
+
+        let release = HandleMsg::ReleasePrize {};
+        env.block.height = 111;
+        let info = mock_info("anyone", &[]);
+        let err = handle(&mut deps, env.clone(), info.clone(), release.clone()).unwrap_err();
+        match err {
+            ContractError::ResultNotFinal {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let msg = HandleMsg::PublishResult {};
+        let err = handle(&mut deps, env.clone(), info.clone(), msg).unwrap_err();
+        match err {
+            ContractError::ResultNotFinal {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 112;
+        let res = handle(&mut deps, env.clone(), info.clone(), release.clone()).unwrap();
+        let targets: Vec<&str> = res
+            .messages
+            .iter()
+            .map(|msg| match msg {
+                CosmosMsg::Bank(BankMsg::Send { to_address, .. }) => to_address.as_str(),
+                CosmosMsg::Wasm(WasmMsg::Execute { contract_addr, .. }) => contract_addr.as_str(),
+                msg => panic!("unexpected message: {:?}", msg),
+            })
+            .collect();
+        assert_eq!(vec!["alice", "hook", "kv"], targets);
+        let err = handle(&mut deps, env, info, release).unwrap_err();
+        match err {
+            ContractError::PrizeReleased {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn voter_set_diff_against_previous_election() {
+        let deps = mock_dependencies(&[]);
+        let mut deps = with_wasm_querier(deps, |contract, msg| {
+            assert_eq!("previous", contract.as_str());
+            match from_binary(msg)? {
+                QueryMsg::GetParticipation {} => to_binary(&ParticipationResponse {
+                    finalized_at: Some(5),
+                    voters: vec!["carol".into(), "dave".into(), "erin".into()],
+                }),
+                _ => Err(StdError::generic_err("unexpected query")),
+            }
+        });
+        let msg = InitMsg {
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for voter in &["dave", "bob", "carol"] {
+            let msg = HandleMsg::Vote {
+                candidate: "alice".into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+
+        let msg = QueryMsg::VoterSetDiff {
+            from: "previous".into(),
+            to: env.contract.address.clone(),
+        };
+        let diff: VoterSetDiffResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        assert_eq!(vec![HumanAddr::from("bob")], diff.new_voters);
+        assert_eq!(vec![HumanAddr::from("erin")], diff.lapsed_voters);
+        assert_eq!(2, diff.retained);
+        assert_eq!(Decimal::from_ratio(2u128, 3u128), diff.retention_rate);
+    }
+
+    #[test]
+    fn factory_spawns_and_tracks_elections() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            election_code_id: Some(7),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let params = InitMsg {
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        let msg = HandleMsg::SpawnElection {
+            label: "council".into(),
+            params: Box::new(params.clone()),
+        };
+        let env = mock_env();
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let res = handle(&mut deps, env.clone(), mock_info("creator", &[]), msg).unwrap();
+        let child_msg = InitMsg {
+            factory: Some(FactoryLink {
+                address: env.contract.address.clone(),
+                spawn_id: 1,
+            }),
+            ..params.clone()
+        };
+        let instantiate: CosmosMsg = WasmMsg::Instantiate {
+            code_id: 7,
+            msg: to_binary(&child_msg).unwrap(),
+            send: vec![],
+            label: Some("council".into()),
+        }
+        .into();
+        assert_eq!(vec![instantiate], res.messages);
+
+        // the child reports back from its init
+        let mut child = mock_dependencies(&[]);
+        let res = init(&mut child, mock_env(), mock_info("factory", &[]), child_msg).unwrap();
+        let register: CosmosMsg = WasmMsg::Execute {
+            contract_addr: env.contract.address.clone(),
+            msg: to_binary(&HandleMsg::RegisterSpawned { spawn_id: 1 }).unwrap(),
+            send: vec![],
+        }
+        .into();
+        assert_eq!(vec![register], res.messages);
+
+        let msg = HandleMsg::RegisterSpawned { spawn_id: 1 };
+        handle(&mut deps, env.clone(), mock_info("child", &[]), msg.clone()).unwrap();
+        let err = handle(&mut deps, env.clone(), mock_info("impostor", &[]), msg).unwrap_err();
+        match err {
+            ContractError::UnknownSpawn { spawn_id } => assert_eq!(1, spawn_id),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let res = query(&deps, env, QueryMsg::ListSpawnedElections {}).unwrap();
+        let value: SpawnedElectionsResponse = from_binary(&res).unwrap();
+        assert_eq!(Some("child".into()), value.elections[0].address);
+    }
+
+    #[test]
+    fn tie_policies_at_seat_boundary() {
+        let setup = |tie_policy: TiePolicy| {
+            let mut deps = mock_dependencies(&[]);
+            let msg = InitMsg {
+                seats: 2,
+                tie_policy,
+                candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+                ..init_msg()
+            };
+            init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let mut env = mock_env();
+            env.block.height = 50;
+            // alice 2, bob 1, carol 1: bob and carol tie for the second seat
+            for (voter, candidate) in &[
+                ("voter1", "alice"),
+                ("voter2", "alice"),
+                ("voter3", "bob"),
+                ("voter4", "carol"),
+            ] {
+                let msg = HandleMsg::Vote {
+                    candidate: (*candidate).into(),
+                    proof: None,
+                    answers: None,
+                };
+                handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+            }
+            deps
+        };
+        let winners = |deps: &Extern<_, _, _>, env: Env| -> Vec<HumanAddr> {
+            let res = query(deps, env, QueryMsg::GetResult { locale: None }).unwrap();
+            let value: ResultResponse = from_binary(&res).unwrap();
+            assert_eq!(
+                vec![HumanAddr::from("bob"), HumanAddr::from("carol")],
+                value.tied
+            );
+            value.winners.into_iter().map(|w| w.candidate).collect()
+        };
+        let mut env = mock_env();
+        env.block.height = 101;
+
+        let mut deps = setup(TiePolicy::Expand);
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        assert_eq!(
+            vec![HumanAddr::from("alice"), "bob".into(), "carol".into()],
+            winners(&deps, env.clone())
+        );
+
+        let mut deps = setup(TiePolicy::Runoff { duration: 20 });
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        assert_eq!(attr("action", "start_runoff"), res.attributes[0]);
+        let vote = |candidate: &str| HandleMsg::Vote {
+            candidate: candidate.into(),
+            proof: None,
+            answers: None,
+        };
+        env.block.height = 110;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            vote("carol"),
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter3", &[]),
+            vote("bob"),
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter4", &[]),
+            vote("carol"),
+        )
+        .unwrap();
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter2", &[]),
+            vote("alice"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::UnknownCandidate { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("newcomer", &[]),
+            vote("bob"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NotEligibleForRunoff { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 122;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        assert_eq!(
+            vec![HumanAddr::from("alice"), "carol".into()],
+            winners(&deps, env)
+        );
+    }
+
+    #[test]
+    fn only_registered_voters_vote() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            registration: Some(RegistrationConfig {
+                start: 1,
+                end: 9,
+                bond: Some(coin(10, "earth")),
+            }),
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 5;
+        for voter in &["carol", "bob", "dave"] {
+            let info = mock_info(*voter, &coins(10, "earth"));
+            handle(&mut deps, env.clone(), info, HandleMsg::RegisterVoter {}).unwrap();
+        }
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("erin", &[]),
+            HandleMsg::RegisterVoter {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InsufficientBond { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let msg = QueryMsg::ListVoters {
+            start_after: Some("bob".into()),
+            limit: Some(1),
+        };
+        let value: VotersResponse = from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(vec![HumanAddr::from("carol")], value.voters);
+
+        env.block.height = 50;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("bob", &[]), vote.clone()).unwrap();
+        let err = handle(&mut deps, env, mock_info("erin", &[]), vote).unwrap_err();
+        match err {
+            ContractError::NotRegistered { voter } => assert_eq!("erin", voter),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn stats_follow_ballots() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            start: 10,
+            end: 109,
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        for (height, voter, candidate) in &[
+            (12, "voter1", "alice"),
+            (15, "voter2", "bob"),
+            (45, "voter3", "alice"),
+        ] {
+            env.block.height = *height;
+            let msg = HandleMsg::Vote {
+                candidate: (*candidate).into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+
+        let res = query(&deps, env, QueryMsg::GetStats {}).unwrap();
+        let value: StatsResponse = from_binary(&res).unwrap();
+        assert_eq!(3, value.ballots);
+        assert_eq!(3, value.unique_voters);
+        assert_eq!(None, value.turnout);
+        assert_eq!(Some("alice".into()), value.leader);
+        assert_eq!(Uint128(1), value.margin);
+        assert_eq!(
+            HistogramBucket {
+                start: 10,
+                end: 19,
+                ballots: 2
+            },
+            value.histogram[0]
+        );
+        assert_eq!(1, value.histogram[3].ballots);
+    }
+
+    #[test]
+    fn compromised_oracle_falls_back_until_replaced() {
+        let deps = mock_dependencies(&[]);
+        let mut deps = with_wasm_querier(deps, |contract, msg| match from_binary(msg)? {
+            StakingQueryMsg::StakedBalanceAtHeight { .. } => {
+                let balance = if contract.as_str() == "staking" {
+                    42
+                } else {
+                    7
+                };
+                to_binary(&StakedBalanceAtHeightResponse {
+                    balance: Uint128(balance),
+                    height: 10,
+                })
+            }
+        });
+        let msg = InitMsg {
+            stake_weight: Some(StakeWeight::Contract {
+                address: "staking".into(),
+                snapshot_height: None,
+            }),
+            oracle_fallback: OracleFallback::EqualWeight,
+            guardians: vec!["guardian".into()],
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let msg = HandleMsg::MarkOracleCompromised {};
+        handle(&mut deps, mock_env(), mock_info("guardian", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            vote.clone(),
+        )
+        .unwrap();
+
+        let msg = HandleMsg::ReplaceOracle {
+            address: "staking2".into(),
+        };
+        let err = handle(
+            &mut deps,
+            mock_env(),
+            mock_info("guardian", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        handle(&mut deps, env.clone(), mock_info("voter2", &[]), vote).unwrap();
+
+        let res = query(
+            &deps,
+            env,
+            QueryMsg::GetVoteInfo {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128(1 + 7), value.votes[0].weight);
+    }
+
+    #[test]
+    fn ballots_are_stored_canonical() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap();
+
+        let state = config_read(&deps.storage).load().unwrap();
+        let voter = deps.api.canonical_address(&"voter1".into()).unwrap();
+        assert_eq!(voter, state.votes[0].voter);
+        assert_eq!(
+            vec![deps.api.canonical_address(&"alice".into()).unwrap()],
+            state.candidates
+        );
+
+        let msg = QueryMsg::GetBallot {
+            voter: "voter1".into(),
+        };
+        let ballot: BallotResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        assert_eq!("voter1", ballot.voter);
+        assert_eq!("alice", ballot.candidate);
+    }
+
+    #[test]
+    fn finalize_publishes_winners_to_registry() {
+        let mut deps = mock_dependencies(&[]);
+        let registry = RegistryConfig {
+            address: "kv".into(),
+            key_template: "operator".into(),
+            deferred: false,
+        };
+        let msg = InitMsg {
+            seats: 2,
+            registry: Some(registry.clone()),
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        let err = init(
+            &mut deps,
+            mock_env(),
+            mock_info("creator", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidKeyTemplate {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let msg = InitMsg {
+            registry: Some(RegistryConfig {
+                key_template: "operator/{election}/{seat}".into(),
+                ..registry
+            }),
+            ..msg
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 101;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        assert_eq!(2, res.messages.len());
+        let stored = result_read(&deps.storage).load().unwrap();
+        let value = PublishedWinner {
+            election: env.contract.address.clone(),
+            seat: 2,
+            winner: "bob".into(),
+            finalized_at: 101,
+            result_hash: Binary::from(sha256(&to_vec(&stored).unwrap()).to_vec()),
+            counts_hash: Binary::from(sha256(&encode_tally(&stored.standings)).to_vec()),
+            turnout: 0,
+        };
+        let msg = RegistryMsg::Set {
+            key: format!("operator/{}/2", env.contract.address),
+            value: to_binary(&value).unwrap(),
+        };
+        let expected: CosmosMsg = WasmMsg::Execute {
+            contract_addr: "kv".into(),
+            msg: to_binary(&msg).unwrap(),
+            send: vec![],
+        }
+        .into();
+        assert_eq!(expected, res.messages[1]);
+    }
+
+    #[test]
+    fn legacy_vote_info_keeps_integer_counts() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap();
+
+        let res = query(
+            &deps,
+            env,
+            QueryMsg::GetVoteInfoLegacy {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+        let json = String::from_utf8(res.to_vec()).unwrap();
+        assert!(json.contains(r#""count":1,"weight":1"#), "{}", json);
+        let value: LegacyVoteResponse = from_binary(&res).unwrap();
+        assert_eq!(1, value.votes[0].count);
+    }
+
+    #[test]
+    fn vote_info_is_sorted() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for (voter, candidate) in &[
+            ("voter1", "carol"),
+            ("voter2", "bob"),
+            ("voter3", "alice"),
+            ("voter4", "carol"),
+        ] {
+            let msg = HandleMsg::Vote {
+                candidate: (*candidate).into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+
+        let candidates = |sort| {
+            let msg = QueryMsg::GetVoteInfo { locale: None, sort };
+            let value: VoteResponse =
+                from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+            value
+                .votes
+                .into_iter()
+                .map(|vote| vote.candidate)
+                .collect::<Vec<HumanAddr>>()
+        };
+        let by_count: Vec<HumanAddr> = vec!["carol".into(), "alice".into(), "bob".into()];
+        assert_eq!(by_count, candidates(None));
+        assert_eq!(by_count, candidates(Some(VoteSort::Count)));
+        let by_candidate: Vec<HumanAddr> = vec!["alice".into(), "bob".into(), "carol".into()];
+        assert_eq!(by_candidate, candidates(Some(VoteSort::Candidate)));
+    }
+
+    #[test]
+    fn multi_query_answers_in_order() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap();
+
+        let msg = QueryMsg::Multi {
+            queries: vec![
+                QueryMsg::GetStats {},
+                QueryMsg::GetBallot {
+                    voter: "voter1".into(),
+                },
+            ],
+        };
+        let res: MultiResponse = from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(2, res.responses.len());
+        let stats: StatsResponse = from_binary(&res.responses[0]).unwrap();
+        assert_eq!(1, stats.ballots);
+        let ballot: BallotResponse = from_binary(&res.responses[1]).unwrap();
+        assert_eq!("alice", ballot.candidate);
+
+        let msg = QueryMsg::Multi {
+            queries: vec![QueryMsg::Multi { queries: vec![] }],
+        };
+        query(&deps, env, msg).unwrap_err();
+    }
+
+    #[test]
+    fn init_validates_period_and_candidates() {
+        let mut deps = mock_dependencies(&[]);
+        let info = mock_info("creator", &[]);
+        let msg = InitMsg {
+            start: 100,
+            end: 100,
+            ..init_msg()
+        };
+        match init(&mut deps, mock_env(), info.clone(), msg).unwrap_err() {
+            ContractError::InvalidPeriod { start, end } => assert_eq!((100, 100), (start, end)),
+            e => panic!("unexpected error: {}", e),
+        }
+        let msg = InitMsg {
+            candidates: vec![],
+            ..init_msg()
+        };
+        match init(&mut deps, mock_env(), info.clone(), msg).unwrap_err() {
+            ContractError::NoCandidates {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let msg = InitMsg {
+            candidates: vec!["alice".into(), "bob".into(), "alice".into()],
+            ..init_msg()
+        };
+        match init(&mut deps, mock_env(), info, msg).unwrap_err() {
+            ContractError::DuplicateCandidate { candidate } => assert_eq!("alice", candidate),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn eligibility_filter_gates_voters() {
+        let mut deps = mock_dependencies(&[]);
+        let mut bits = vec![0u8; 32];
+        for voter in &["voter1", "voter2"] {
+            let voter = deps.api.canonical_address(&(*voter).into()).unwrap();
+            bloom::insert(&mut bits, 3, &voter);
+        }
+        let filter = EligibilityFilter {
+            bits: Binary(bits),
+            hashes: 3,
+            members: 2,
+            excluded: vec!["voter2".into()],
+        };
+        let msg = InitMsg {
+            eligibility: Some(filter),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            vote.clone(),
+        )
+        .unwrap();
+        for voter in &["voter2", "outsider"] {
+            let err =
+                handle(&mut deps, env.clone(), mock_info(*voter, &[]), vote.clone()).unwrap_err();
+            match err {
+                ContractError::NotEligible { .. } => {}
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+
+        let config: ConfigResponse =
+            from_binary(&query(&deps, env.clone(), QueryMsg::GetConfig {}).unwrap()).unwrap();
+        let expected = EligibilityParams {
+            bits: 256,
+            hashes: 3,
+            members: 2,
+            excluded: 1,
+        };
+        assert_eq!(Some(expected), config.eligibility);
+
+        // the first ballot froze the roll, clearing the filter comes too late
+        let msg = HandleMsg::SetEligibilityFilter { filter: None };
+        handle(&mut deps, env.clone(), mock_info("creator", &[]), msg).unwrap();
+        let err = handle(&mut deps, env, mock_info("outsider", &[]), vote).unwrap_err();
+        match err {
+            ContractError::NotEligible { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn tie_rules_pick_among_tied_candidates() {
+        let setup = |tie_policy| {
+            let mut deps = mock_dependencies(&[]);
+            let msg = InitMsg {
+                tie_policy,
+                candidates: vec!["carol".into(), "bob".into(), "alice".into()],
+                ..init_msg()
+            };
+            init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let mut env = mock_env();
+            env.block.height = 50;
+            for (voter, candidate) in &[("voter1", "alice"), ("voter2", "carol")] {
+                let msg = HandleMsg::Vote {
+                    candidate: (*candidate).into(),
+                    proof: None,
+                    answers: None,
+                };
+                handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+            }
+            deps
+        };
+        let mut env = mock_env();
+        env.block.height = 101;
+
+        let mut deps = setup(TiePolicy::EarliestCandidate);
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let res = result_read(&deps.storage).load().unwrap();
+        assert_eq!(HumanAddr::from("carol"), res.winners[0].candidate);
+        assert_eq!(TiePolicy::EarliestCandidate, res.tie_policy);
+
+        let mut deps = setup(TiePolicy::Manual);
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        assert_eq!(attr("action", "tie_pending"), res.attributes[0]);
+        assert!(result_read(&deps.storage).may_load().unwrap().is_none());
+        let msg = HandleMsg::ResolveTie {
+            winners: vec!["bob".into()],
+        };
+        let err = handle(&mut deps, env.clone(), mock_info("creator", &[]), msg).unwrap_err();
+        match err {
+            ContractError::InvalidTieResolution { seats } => assert_eq!(1, seats),
+            e => panic!("unexpected error: {}", e),
+        }
+        let msg = HandleMsg::ResolveTie {
+            winners: vec!["alice".into()],
+        };
+        let res = handle(&mut deps, env, mock_info("creator", &[]), msg).unwrap();
+        assert_eq!(attr("action", "finalize"), res.attributes[0]);
+        let res = result_read(&deps.storage).load().unwrap();
+        assert_eq!(HumanAddr::from("alice"), res.winners[0].candidate);
+    }
+
+    #[test]
+    fn runoff_between_top_two_without_majority() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            majority_runoff: Some(MajorityRunoff {
+                threshold: Decimal::percent(50),
+                duration: 20,
+            }),
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = |candidate: &str| HandleMsg::Vote {
+            candidate: candidate.into(),
+            proof: None,
+            answers: None,
+        };
+        for (voter, candidate) in &[
+            ("voter1", "alice"),
+            ("voter2", "alice"),
+            ("voter3", "bob"),
+            ("voter4", "carol"),
+            ("voter5", "bob"),
+        ] {
+            handle(
+                &mut deps,
+                env.clone(),
+                mock_info(*voter, &[]),
+                vote(candidate),
+            )
+            .unwrap();
+        }
+
+        env.block.height = 101;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        assert_eq!(attr("action", "start_runoff"), res.attributes[0]);
+        let msg = QueryMsg::GetVoteInfo {
+            locale: None,
+            sort: None,
+        };
+        let value: VoteResponse = from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(2, value.round);
+
+        env.block.height = 110;
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter4", &[]),
+            vote("carol"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::UnknownCandidate { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter4", &[]),
+            vote("bob"),
+        )
+        .unwrap();
+
+        env.block.height = 122;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let msg = QueryMsg::GetResult { locale: None };
+        let value: ResultResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        assert_eq!(HumanAddr::from("bob"), value.winners[0].candidate);
+        assert_eq!(2, value.round);
+    }
+
+    #[test]
+    fn disclosures_are_appended_and_paged() {
+        let mut deps = mock_dependencies(&[]);
+        init(&mut deps, mock_env(), mock_info("creator", &[]), init_msg()).unwrap();
+
+        let record = |memo: &str| HandleMsg::RecordDisclosure {
+            candidate: "alice".into(),
+            source: "donor".into(),
+            amount: coin(10, "earth"),
+            memo: memo.into(),
+        };
+        let err = handle(&mut deps, mock_env(), mock_info("bob", &[]), record("x")).unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(
+            &mut deps,
+            mock_env(),
+            mock_info("alice", &[]),
+            record("first"),
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            mock_env(),
+            mock_info("creator", &[]),
+            record("second"),
+        )
+        .unwrap();
+        handle(
+            &mut deps,
+            mock_env(),
+            mock_info("alice", &[]),
+            record("third"),
+        )
+        .unwrap();
+
+        let msg = QueryMsg::ListDisclosures {
+            candidate: "alice".into(),
+            start_after: Some(0),
+            limit: Some(1),
+        };
+        let res: DisclosuresResponse =
+            from_binary(&query(&deps, mock_env(), msg).unwrap()).unwrap();
+        assert_eq!(1, res.disclosures.len());
+        assert_eq!(1, res.disclosures[0].id);
+        assert_eq!("second", res.disclosures[0].memo);
+        assert_eq!(HumanAddr::from("creator"), res.disclosures[0].recorded_by);
+    }
+
+    #[test]
+    fn contract_votes_need_opt_in() {
+        let mut deps = mock_dependencies(&[]);
+        init(&mut deps, mock_env(), mock_info("creator", &[]), init_msg()).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::VoteAsContract {
+            on_behalf_of_policy: "dao-proposal-7".into(),
+            ballot: Ballot::Vote {
+                candidate: "alice".into(),
+            },
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("aggregator", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::ContractVotesDisabled {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let mut deps = mock_dependencies(&[]);
+        let init = InitMsg {
+            allow_contract_votes: true,
+            ..init_msg()
+        };
+        super::init(&mut deps, mock_env(), mock_info("creator", &[]), init).unwrap();
+        let res = handle(&mut deps, env.clone(), mock_info("aggregator", &[]), msg).unwrap();
+        assert_eq!(
+            attr("on_behalf_of_policy", "dao-proposal-7"),
+            res.attributes[2]
+        );
+        let msg = QueryMsg::GetBallot {
+            voter: "aggregator".into(),
+        };
+        let ballot: BallotResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        assert_eq!(1, ballot.weight);
+    }
+
+    #[test]
+    fn write_ins_are_opt_in_and_listed_above_minimum() {
+        let mut deps = mock_dependencies(&[]);
+        init(&mut deps, mock_env(), mock_info("creator", &[]), init_msg()).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = |candidate: &str| HandleMsg::Vote {
+            candidate: candidate.into(),
+            proof: None,
+            answers: None,
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            vote("zed"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::UnknownCandidate { candidate } => assert_eq!("zed", candidate),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            write_ins: Some(WriteIns {
+                min_count: Uint128(2),
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        for (voter, candidate) in &[("voter1", "zed"), ("voter2", "yan"), ("voter3", "zed")] {
+            handle(
+                &mut deps,
+                env.clone(),
+                mock_info(*voter, &[]),
+                vote(candidate),
+            )
+            .unwrap();
+        }
+        let msg = QueryMsg::GetVoteInfo {
+            locale: None,
+            sort: Some(VoteSort::Candidate),
+        };
+        let value: VoteResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        let listed: Vec<HumanAddr> = value.votes.into_iter().map(|v| v.candidate).collect();
+        assert_eq!(vec![HumanAddr::from("zed")], listed);
+    }
+
+    #[test]
+    fn runner_up_replaces_resigned_winner() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            succession: Some(Succession {
+                window: 50,
+                arbiter: Some("arbiter".into()),
+            }),
+            completion_hook: Some("roles".into()),
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for (voter, candidate) in &[
+            ("voter1", "alice"),
+            ("voter2", "alice"),
+            ("voter3", "carol"),
+        ] {
+            let msg = HandleMsg::Vote {
+                candidate: (*candidate).into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let standings: Vec<HumanAddr> = result_read(&deps.storage)
+            .load()
+            .unwrap()
+            .standings
+            .into_iter()
+            .map(|s| s.candidate)
+            .collect();
+        let expected: Vec<HumanAddr> = vec!["alice".into(), "carol".into(), "bob".into()];
+        assert_eq!(expected, standings);
+
+        let promote = |vacated: &str| HandleMsg::PromoteRunnerUp {
+            vacated: vacated.into(),
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            promote("alice"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("arbiter", &[]),
+            promote("alice"),
+        )
+        .unwrap();
+        assert_eq!(attr("promoted", "carol"), res.attributes[2]);
+        assert_eq!(1, res.messages.len());
+        // the runner-up after carol is bob, alice does not come back
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            promote("carol"),
+        )
+        .unwrap();
+        let stored = result_read(&deps.storage).load().unwrap();
+        assert_eq!(HumanAddr::from("bob"), stored.winners[0].candidate);
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            promote("bob"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NoRunnerUp {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 152;
+        let err = handle(&mut deps, env, mock_info("creator", &[]), promote("bob")).unwrap_err();
+        match err {
+            ContractError::SuccessionWindowClosed { end } => assert_eq!(151, end),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn referendum_quorum_and_veto() {
+        let referendum = Referendum {
+            title: "Raise the fee".into(),
+            description: "Doubles the fee".into(),
+            electorate: Uint128(10),
+            quorum: Decimal::percent(40),
+            threshold: Decimal::percent(50),
+            veto_threshold: Decimal::percent(33),
+        };
+        let vote = |choice| HandleMsg::ReferendumVote { choice };
+        let run = |choices: &[ReferendumChoice]| {
+            let mut deps = mock_dependencies(&[]);
+            let msg = InitMsg {
+                referendum: Some(referendum.clone()),
+                candidates: vec![],
+                ..init_msg()
+            };
+            init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let mut env = mock_env();
+            env.block.height = 50;
+            for (i, choice) in choices.iter().enumerate() {
+                let voter = format!("voter{}", i);
+                handle(&mut deps, env.clone(), mock_info(voter, &[]), vote(*choice)).unwrap();
+            }
+            let counters = stats_read(&deps.storage).load().unwrap();
+            assert_eq!(choices.len() as u64, counters.unique_voters);
+            env.block.height = 101;
+            handle(
+                &mut deps,
+                env.clone(),
+                mock_info("anyone", &[]),
+                HandleMsg::Finalize { limit: None },
+            )
+            .unwrap();
+            let res = query(&deps, env, QueryMsg::GetReferendum {}).unwrap();
+            let value: ReferendumResponse = from_binary(&res).unwrap();
+            value.status.unwrap()
+        };
+        use ReferendumChoice::*;
+        assert_eq!(ReferendumStatus::QuorumNotMet, run(&[Yes, Yes, Yes]));
+        assert_eq!(ReferendumStatus::Passed, run(&[Yes, Yes, No, Abstain]));
+        assert_eq!(
+            ReferendumStatus::Rejected,
+            run(&[Yes, No, Abstain, Abstain])
+        );
+        assert_eq!(
+            ReferendumStatus::Vetoed,
+            run(&[Yes, Yes, NoWithVeto, NoWithVeto])
+        );
+
+        // candidate ballots are refused
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            referendum: Some(referendum),
+            candidates: vec![],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        let err = handle(&mut deps, env, mock_info("voter1", &[]), msg).unwrap_err();
+        match err {
+            ContractError::ReferendumBallotRequired {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn update_period_before_start() {
+        let mut deps = mock_dependencies(&[]);
+        init(&mut deps, mock_env(), mock_info("creator", &[]), init_msg()).unwrap();
+        let mut env = mock_env();
+        env.block.height = 5;
+        let update = |start, end| HandleMsg::UpdatePeriod { start, end };
+
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            update(20, 200),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        for (start, end) in &[(5, 200), (20, 20)] {
+            let err = handle(
+                &mut deps,
+                env.clone(),
+                mock_info("creator", &[]),
+                update(*start, *end),
+            )
+            .unwrap_err();
+            match err {
+                ContractError::InvalidPeriod { .. } => {}
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            update(20, 200),
+        )
+        .unwrap();
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_eq!((20, 200), (state.start, state.end));
+
+        env.block.height = 20;
+        let err = handle(&mut deps, env, mock_info("creator", &[]), update(30, 300)).unwrap_err();
+        match err {
+            ContractError::ElectionStarted { start } => assert_eq!(20, start),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn withdrawn_candidate_ballots_are_redirected() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            voting_system: VotingSystem::Stv,
+            withdrawal_policy: WithdrawalPolicy::Redirect,
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for (voter, ranking) in &[
+            ("voter1", vec!["alice", "bob"]),
+            ("voter2", vec!["alice"]),
+            ("voter3", vec!["carol"]),
+        ] {
+            let msg = HandleMsg::RankedVote {
+                ranking: ranking.iter().map(|c| HumanAddr::from(*c)).collect(),
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("alice", &[]),
+            HandleMsg::WithdrawCandidacy {},
+        )
+        .unwrap();
+        assert_eq!(attr("discarded", "1"), res.attributes[2]);
+        let state = config_read(&deps.storage).load().unwrap();
+        let votes = humanize_votes(&deps.api, &state, &state.votes).unwrap();
+        assert_eq!(2, votes.len());
+        assert_eq!(HumanAddr::from("bob"), votes[0].candidate);
+        assert_eq!(vec![HumanAddr::from("bob")], votes[0].ranking);
+
+        // alice no longer takes ballots, the discarded voter may vote again
+        let msg = HandleMsg::RankedVote {
+            ranking: vec!["carol".into(), "alice".into()],
+        };
+        let err = handle(&mut deps, env.clone(), mock_info("voter2", &[]), msg).unwrap_err();
+        match err {
+            ContractError::CandidateWithdrawn { candidate } => {
+                assert_eq!("alice", candidate.as_str())
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let msg = HandleMsg::RankedVote {
+            ranking: vec!["carol".into()],
+        };
+        handle(&mut deps, env.clone(), mock_info("voter2", &[]), msg).unwrap();
+
+        env.block.height = 101;
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("bob", &[]),
+            HandleMsg::WithdrawCandidacy {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::WithdrawalClosed { end } => assert_eq!(100, end),
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let res = query(&deps, env, QueryMsg::GetResult { locale: None }).unwrap();
+        let value: ResultResponse = from_binary(&res).unwrap();
+        assert_eq!(HumanAddr::from("carol"), value.winners[0].candidate);
+    }
+
+    #[test]
+    fn ballot_limits() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            limits: Limits {
+                max_candidates: Some(1),
+                ..Limits::default()
+            },
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        let err = init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        match err {
+            ContractError::TooManyCandidates { max } => assert_eq!(1, max),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let msg = InitMsg {
+            limits: Limits {
+                max_candidates: None,
+                max_total_votes: Some(3),
+                max_votes_per_block: Some(2),
+                cooldown: None,
+            },
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = || HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), vote()).unwrap();
+        handle(&mut deps, env.clone(), mock_info("voter2", &[]), vote()).unwrap();
+        let err = handle(&mut deps, env.clone(), mock_info("voter3", &[]), vote()).unwrap_err();
+        match err {
+            ContractError::BlockBallotLimitReached { max } => assert_eq!(2, max),
+            e => panic!("unexpected error: {}", e),
+        }
+        env.block.height = 51;
+        handle(&mut deps, env.clone(), mock_info("voter3", &[]), vote()).unwrap();
+        let err = handle(&mut deps, env, mock_info("voter4", &[]), vote()).unwrap_err();
+        match err {
+            ContractError::BallotLimitReached { max } => assert_eq!(3, max),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn vote_mints_receipt() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            receipts: Some("receipts".into()),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        let res = handle(&mut deps, env, mock_info("voter1", &[]), msg).unwrap();
+        assert_eq!(1, res.messages.len());
+        match &res.messages[0] {
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr, msg, ..
+            }) => {
+                assert_eq!("receipts", contract_addr.as_str());
+                let Cw721HandleMsg::Mint(mint) = from_binary(msg).unwrap();
+                assert_eq!("cosmos2contract/1", mint.token_id);
+                assert_eq!("voter1", mint.owner.as_str());
+                let description = mint.description.unwrap();
+                assert!(description.contains("cosmos2contract") && description.contains("50"));
+                assert!(!description.contains("alice"));
+            }
+            msg => panic!("unexpected message: {:?}", msg),
+        }
+    }
+
+    #[test]
+    fn voters_claim_weighted_reward() {
+        let mut deps = mock_dependencies(&[]);
+        let delegation = |delegator: &str, amount: u128| FullDelegation {
+            delegator: delegator.into(),
+            validator: "validator".into(),
+            amount: coin(amount, "ustake"),
+            can_redelegate: coin(0, "ustake"),
+            accumulated_rewards: vec![],
+        };
+        deps.querier.update_staking(
+            "ustake",
+            &[],
+            &[delegation("whale", 300), delegation("minnow", 100)],
+        );
+        let msg = InitMsg {
+            stake_weight: Some(StakeWeight::Native {}),
+            participation_reward: Some(ParticipationReward {
+                pool: coins(100, "earth"),
+                split: RewardSplit::Weight,
+                claim_window: None,
+            }),
+            ..init_msg()
+        };
+        init(
+            &mut deps,
+            mock_env(),
+            mock_info("creator", &coins(150, "earth")),
+            msg,
+        )
+        .unwrap();
+        let books = ledger_read(&deps.storage).load().unwrap();
+        assert_eq!(Uint128(50), books.balance(FundBucket::PrizePool, "earth"));
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        for voter in &["whale", "minnow"] {
+            let msg = HandleMsg::Vote {
+                candidate: "alice".into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("whale", &[]),
+            HandleMsg::ClaimReward {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NotFinalized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("whale", &[]),
+            HandleMsg::ClaimReward {},
+        )
+        .unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "whale".into(),
+                amount: coins(75, "earth"),
+            }),
+            res.messages[0]
+        );
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("whale", &[]),
+            HandleMsg::ClaimReward {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::RewardAlreadyClaimed { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("outsider", &[]),
+            HandleMsg::ClaimReward {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NoRewardShare { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(
+            &mut deps,
+            env,
+            mock_info("minnow", &[]),
+            HandleMsg::ClaimReward {},
+        )
+        .unwrap();
+        let books = ledger_read(&deps.storage).load().unwrap();
+        assert!(books.bucket(FundBucket::RewardPool).is_empty());
+    }
+
+    #[test]
+    fn archive_returns_reward_dust_to_owner() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            participation_reward: Some(ParticipationReward {
+                pool: coins(100, "earth"),
+                split: RewardSplit::Equal,
+                claim_window: None,
+            }),
+            ..init_msg()
+        };
+        let info = mock_info("creator", &coins(100, "earth"));
+        init(&mut deps, mock_env(), info, msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let voters = ["voter1", "voter2", "voter3"];
+        for voter in &voters {
+            let msg = HandleMsg::Vote {
+                candidate: "alice".into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        env.block.height = 101;
+        let msg = HandleMsg::Finalize { limit: None };
+        handle(&mut deps, env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        env.block.height = 102;
+        for voter in &voters[..2] {
+            let info = mock_info(*voter, &[]);
+            let res = handle(&mut deps, env.clone(), info, HandleMsg::ClaimReward {}).unwrap();
+            assert_eq!(
+                CosmosMsg::Bank(BankMsg::Send {
+                    from_address: env.contract.address.clone(),
+                    to_address: (*voter).into(),
+                    amount: coins(33, "earth"),
+                }),
+                res.messages[0]
+            );
+        }
+        let info = mock_info("creator", &[]);
+        let err = handle(&mut deps, env.clone(), info, HandleMsg::Archive {}).unwrap_err();
+        match err {
+            ContractError::RewardsUnclaimed {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("voter3", &[]);
+        handle(&mut deps, env.clone(), info, HandleMsg::ClaimReward {}).unwrap();
+
+        // every share is paid and the indivisible remainder goes to the owner
+        let info = mock_info("creator", &[]);
+        let res = handle(&mut deps, env.clone(), info, HandleMsg::Archive {}).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "creator".into(),
+                amount: coins(1, "earth"),
+            }),
+            res.messages[0]
+        );
+        let books = ledger_read(&deps.storage).load().unwrap();
+        assert!(books.bucket(FundBucket::RewardPool).is_empty());
+    }
+
+    #[test]
+    fn claim_window_sweeps_unclaimed_rewards() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            participation_reward: Some(ParticipationReward {
+                pool: coins(100, "earth"),
+                split: RewardSplit::Equal,
+                claim_window: Some(20),
+            }),
+            ..init_msg()
+        };
+        let info = mock_info("creator", &coins(100, "earth"));
+        init(&mut deps, mock_env(), info, msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for voter in &["voter1", "voter2", "voter3"] {
+            let msg = HandleMsg::Vote {
+                candidate: "alice".into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        env.block.height = 101;
+        let msg = HandleMsg::Finalize { limit: None };
+        handle(&mut deps, env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        let info = mock_info("voter1", &[]);
+        handle(&mut deps, env.clone(), info, HandleMsg::ClaimReward {}).unwrap();
+
+        env.block.height = 122;
+        let info = mock_info("voter2", &[]);
+        let err = handle(&mut deps, env.clone(), info, HandleMsg::ClaimReward {}).unwrap_err();
+        match err {
+            ContractError::ClaimWindowClosed { end: 121 } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("creator", &[]);
+        let res = handle(&mut deps, env.clone(), info, HandleMsg::Archive {}).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "creator".into(),
+                amount: coins(67, "earth"),
+            }),
+            res.messages[0]
+        );
+    }
+
+    #[test]
+    fn merkle_roll_proofs() {
+        let mut deps = mock_dependencies(&[]);
+        let leaf = |voter: &str, weight: u64| {
+            let mut data = voter.as_bytes().to_vec();
+            data.extend_from_slice(&weight.to_be_bytes());
+            merkle::leaf(&data)
+        };
+        let (a, b) = (leaf("voter1", 5), leaf("voter2", 2));
+        let root = merkle::node(&a, &b);
+        let msg = InitMsg {
+            merkle_roll: Some(MerkleRoll {
+                root: Binary::from(root.to_vec()),
+                weighted: true,
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = |sibling: [u8; 32], weight: u64| HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: Some(MerkleProof {
+                path: vec![Binary::from(sibling.to_vec())],
+                weight: Some(weight),
+            }),
+            answers: None,
+        };
+
+        // claiming more weight than the leaf holds breaks the proof
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            vote(b, 50),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InvalidMerkleProof { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        let err = handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap_err();
+        match err {
+            ContractError::NotEligible { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), vote(b, 5)).unwrap();
+        handle(&mut deps, env.clone(), mock_info("voter2", &[]), vote(a, 2)).unwrap();
+        let res = query(
+            &deps,
+            env,
+            QueryMsg::GetVoteInfo {
+                locale: None,
+                sort: None,
+            },
+        )
+        .unwrap();
+        let value: VoteResponse = from_binary(&res).unwrap();
+        assert_eq!(Uint128(7), value.votes[0].weight);
+    }
+
+    #[test]
+    fn tally_proof_commits_to_ballots() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for (voter, candidate) in &[("voter2", "bob"), ("voter1", "alice"), ("voter3", "alice")] {
+            let msg = HandleMsg::Vote {
+                candidate: (*candidate).into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+
+        let res = query(&deps, env.clone(), QueryMsg::GetTallyProof {}).unwrap();
+        let proof: TallyProof = from_binary(&res).unwrap();
+        assert_eq!(3, proof.ballots);
+        assert_eq!(
+            sha256(proof.tally.as_slice()).to_vec(),
+            proof.tally_hash.to_vec()
+        );
+
+        // the root can be rebuilt from the snapshot export
+        let msg = QueryMsg::ExportSnapshot {
+            start_after: None,
+            limit: None,
+        };
+        let snapshot: SnapshotResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        let state = config_read(&deps.storage).load().unwrap();
+        let ballots = humanize_votes(&deps.api, &state, &state.votes).unwrap();
+        let mut sorted: Vec<&BallotResponse> = ballots.iter().collect();
+        sorted.sort_by(|a, b| a.voter.as_str().cmp(b.voter.as_str()));
+        assert_eq!(encode_ballots(&sorted), snapshot.chunk.to_vec());
+        let leaves: Vec<[u8; 32]> = sorted
+            .iter()
+            .map(|b| merkle::leaf(&encode_ballot(b)))
+            .collect();
+        assert_eq!(merkle::root(&leaves).to_vec(), proof.ballots_root.to_vec());
+    }
+
+    #[test]
+    fn keeper_collects_finalization_bounty() {
+        let bounty_msg = || InitMsg {
+            finalization_bounty: Some(FinalizationBounty {
+                amount: coins(5, "earth"),
+                window: 10,
+            }),
+            ..init_msg()
+        };
+        let mut deps = mock_dependencies(&[]);
+        init(
+            &mut deps,
+            mock_env(),
+            mock_info("creator", &coins(105, "earth")),
+            bounty_msg(),
+        )
+        .unwrap();
+        let mut env = mock_env();
+        env.block.height = 110;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("keeper", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        // the prize goes to alice, the bounty to the keeper
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "keeper".into(),
+                amount: coins(5, "earth"),
+            }),
+            res.messages[1]
+        );
+
+        // too late, the bounty returns to the owner
+        let mut deps = mock_dependencies(&[]);
+        init(
+            &mut deps,
+            mock_env(),
+            mock_info("creator", &coins(5, "earth")),
+            bounty_msg(),
+        )
+        .unwrap();
+        env.block.height = 111;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("keeper", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "creator".into(),
+                amount: coins(5, "earth"),
+            }),
+            res.messages[0]
+        );
+    }
+
+    #[test]
+    fn cooldown_between_messages() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            limits: Limits {
+                cooldown: Some(5),
+                ..Limits::default()
+            },
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = |candidate: &str| HandleMsg::Vote {
+            candidate: candidate.into(),
+            proof: None,
+            answers: None,
+        };
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            vote("alice"),
+        )
+        .unwrap();
+        env.block.height = 54;
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            vote("bob"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::TooFrequent { retry_at } => assert_eq!(55, retry_at),
+            e => panic!("unexpected error: {}", e),
+        }
+        // other senders are not affected
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter2", &[]),
+            vote("bob"),
+        )
+        .unwrap();
+        // once the cooldown has passed the ballot is checked as usual
+        env.block.height = 55;
+        let err = handle(&mut deps, env, mock_info("voter1", &[]), vote("bob")).unwrap_err();
+        match err {
+            ContractError::AlreadyVoted { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn weight_decays_over_window() {
+        let weights = |decay: WeightDecay| {
+            let mut deps = mock_dependencies(&[]);
+            let msg = InitMsg {
+                weight_decay: Some(decay),
+                ..init_msg()
+            };
+            init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let mut env = mock_env();
+            for (voter, height) in &[("voter1", 10), ("voter2", 55), ("voter3", 100)] {
+                env.block.height = *height;
+                let msg = HandleMsg::Vote {
+                    candidate: "alice".into(),
+                    proof: None,
+                    answers: None,
+                };
+                handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+            }
+            let state = config_read(&deps.storage).load().unwrap();
+            state
+                .votes
+                .iter()
+                .map(|vote| vote.weight)
+                .collect::<Vec<_>>()
+        };
+        let linear = WeightDecay::Linear {
+            floor: Decimal::percent(50),
+        };
+        assert_eq!(vec![1000, 750, 500], weights(linear));
+        let exponential = WeightDecay::Exponential { half_life: 30 };
+        assert_eq!(vec![1000, 375, 125], weights(exponential));
+
+        // thousandths of a stake near u64::MAX do not fit a ballot weight
+        let mut deps = mock_dependencies(&[]);
+        deps.querier.update_staking(
+            "ustake",
+            &[],
+            &[FullDelegation {
+                delegator: "whale".into(),
+                validator: "validator".into(),
+                amount: coin(u128::from(u64::MAX), "ustake"),
+                can_redelegate: coin(0, "ustake"),
+                accumulated_rewards: vec![],
+            }],
+        );
+        let msg = InitMsg {
+            stake_weight: Some(StakeWeight::Native {}),
+            weight_decay: Some(WeightDecay::Linear {
+                floor: Decimal::percent(50),
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 10;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        let err = handle(&mut deps, env, mock_info("whale", &[]), msg).unwrap_err();
+        match err {
+            ContractError::Std(StdError::GenericErr { msg, .. }) => {
+                assert_eq!("decayed weight exceeds u64", msg)
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let state = config_read(&deps.storage).load().unwrap();
+        assert!(state.votes.is_empty());
+    }
+
+    #[test]
+    fn candidate_votes_query() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for (voter, candidate) in &[("voter1", "alice"), ("voter2", "bob"), ("voter3", "alice")] {
+            let msg = HandleMsg::Vote {
+                candidate: (*candidate).into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        let page = |start_after: Option<&str>| {
+            let msg = QueryMsg::GetCandidateVotes {
+                candidate: "alice".into(),
+                start_after: start_after.map(HumanAddr::from),
+                limit: Some(1),
+            };
+            let res = query(&deps, env.clone(), msg).unwrap();
+            let value: CandidateVotesResponse = from_binary(&res).unwrap();
+            value.votes
+        };
+        let first = page(None);
+        assert_eq!(1, first.len());
+        assert_eq!(1, first[0].weight);
+        let second = page(Some(first[0].voter.as_str()));
+        assert_eq!(1, second.len());
+        assert_ne!(first[0].voter, second[0].voter);
+        assert!(page(Some(second[0].voter.as_str())).is_empty());
+    }
+
+    #[test]
+    fn archive_prunes_ballots() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            factory: Some(FactoryLink {
+                address: "factory".into(),
+                spawn_id: 3,
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap();
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            HandleMsg::Archive {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NotFinalized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            HandleMsg::Archive {},
+        )
+        .unwrap();
+        let state = config_read(&deps.storage).load().unwrap();
+        assert!(state.votes.is_empty());
+
+        let msg = QueryMsg::GetArchivedResult { election_id: 0 };
+        let summary: ArchivedResult =
+            from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(HumanAddr::from("alice"), summary.winners[0].candidate);
+        assert_eq!(1, summary.ballots);
+        let record: CosmosMsg = WasmMsg::Execute {
+            contract_addr: "factory".into(),
+            msg: to_binary(&HandleMsg::RecordArchive {
+                spawn_id: 3,
+                summary,
+            })
+            .unwrap(),
+            send: vec![],
+        }
+        .into();
+        assert_eq!(vec![record], res.messages);
+
+        let err = handle(
+            &mut deps,
+            env,
+            mock_info("creator", &[]),
+            HandleMsg::Archive {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::AlreadyArchived {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn roles_gate_admin_messages() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            roles: vec![RoleGrant {
+                address: "admin".into(),
+                role: Role::Admin,
+            }],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let env = mock_env();
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("pauser", &[]),
+            HandleMsg::Pause {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let grant = HandleMsg::GrantRole {
+            address: "pauser".into(),
+            role: Role::Pauser,
+        };
+        handle(&mut deps, env.clone(), mock_info("admin", &[]), grant).unwrap();
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("pauser", &[]),
+            HandleMsg::Pause {},
+        )
+        .unwrap();
+        // a pauser cannot manage the election
+        let msg = HandleMsg::UpdatePeriod { start: 20, end: 90 };
+        let err = handle(&mut deps, env.clone(), mock_info("pauser", &[]), msg).unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let revoke = HandleMsg::RevokeRole {
+            address: "pauser".into(),
+            role: Role::Pauser,
+        };
+        handle(&mut deps, env.clone(), mock_info("creator", &[]), revoke).unwrap();
+        let err = handle(
+            &mut deps,
+            env,
+            mock_info("pauser", &[]),
+            HandleMsg::Unpause {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn two_step_ownership_transfer() {
+        let mut deps = mock_dependencies(&[]);
+        init(&mut deps, mock_env(), mock_info("creator", &[]), init_msg()).unwrap();
+        let env = mock_env();
+        let msg = HandleMsg::ProposeNewOwner {
+            address: "multisig".into(),
+        };
+        handle(&mut deps, env.clone(), mock_info("creator", &[]), msg).unwrap();
+        let res = query(&deps, env.clone(), QueryMsg::GetConfig {}).unwrap();
+        let value: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!(HumanAddr::from("creator"), value.owner);
+        assert_eq!(Some("multisig".into()), value.pending_owner);
+
+        let accept = HandleMsg::AcceptOwnership {};
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("someone", &[]),
+            accept.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(&mut deps, env.clone(), mock_info("multisig", &[]), accept).unwrap();
+        let res = query(&deps, env, QueryMsg::GetConfig {}).unwrap();
+        let value: ConfigResponse = from_binary(&res).unwrap();
+        assert_eq!(HumanAddr::from("multisig"), value.owner);
+        assert_eq!(None, value.pending_owner);
+    }
+
+    #[test]
+    fn council_vetoes_result() {
+        let council_msg = || InitMsg {
+            veto_council: Some(VetoCouncil {
+                members: vec!["guard1".into(), "guard2".into(), "guard3".into()],
+                threshold: 2,
+                window: 10,
+            }),
+            ..init_msg()
+        };
+        let finalized = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>| {
+            let funds = coins(100, "earth");
+            init(
+                deps,
+                mock_env(),
+                mock_info("creator", &funds),
+                council_msg(),
+            )
+            .unwrap();
+            let mut env = mock_env();
+            env.block.height = 101;
+            let res = handle(
+                deps,
+                env.clone(),
+                mock_info("creator", &[]),
+                HandleMsg::Finalize { limit: None },
+            )
+            .unwrap();
+            // the prize is held for the veto window
+            assert!(res.messages.is_empty());
+            env
+        };
+
+        let mut deps = mock_dependencies(&[]);
+        let env = finalized(&mut deps);
+        let approve = HandleMsg::ApproveVeto {};
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("guard1", &[]),
+            approve.clone(),
+        )
+        .unwrap();
+        assert!(res.messages.is_empty());
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("guard1", &[]),
+            approve.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::AlreadyApproved { member } => assert_eq!("guard1", member.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+        let res = handle(&mut deps, env.clone(), mock_info("guard2", &[]), approve).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "creator".into(),
+                amount: coins(100, "earth"),
+            }),
+            res.messages[0]
+        );
+        let res = query(&deps, env.clone(), QueryMsg::GetResult { locale: None }).unwrap();
+        let value: ResultResponse = from_binary(&res).unwrap();
+        assert_eq!(Some(101), value.vetoed_at);
+
+        // without a veto the prize is released once the window passes
+        let mut deps = mock_dependencies(&[]);
+        let mut env = finalized(&mut deps);
+        let release = HandleMsg::ReleasePrize {};
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            release.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::VetoWindowOpen { end } => assert_eq!(111, end),
+            e => panic!("unexpected error: {}", e),
+        }
+        env.block.height = 112;
+        let res = handle(&mut deps, env.clone(), mock_info("anyone", &[]), release).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "alice".into(),
+                amount: coins(100, "earth"),
+            }),
+            res.messages[0]
+        );
+    }
+
+    #[test]
+    fn challenges_hold_result_until_resolved() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            dispute: Some(DisputeConfig {
+                window: 10,
+                bond: coin(50, "earth"),
+                treasury: "treasury".into(),
+            }),
+            roles: vec![
+                RoleGrant {
+                    address: "manager".into(),
+                    role: Role::ElectionManager,
+                },
+                RoleGrant {
+                    address: "pauser".into(),
+                    role: Role::Pauser,
+                },
+            ],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let status = |deps: &Extern<MockStorage, MockApi, MockQuerier>, env: &Env| {
+            let msg = QueryMsg::GetResult { locale: None };
+            let value: ResultResponse =
+                from_binary(&query(deps, env.clone(), msg).unwrap()).unwrap();
+            value.status
+        };
+        assert_eq!(ResultStatus::Provisional, status(&deps, &env));
+
+        let msg = HandleMsg::Challenge {
+            reason: "miscount".into(),
+        };
+        let info = mock_info("skeptic", &coins(50, "earth"));
+        handle(&mut deps, env.clone(), info, msg).unwrap();
+        env.block.height = 120;
+        // still challenged after the window closes
+        assert_eq!(ResultStatus::Challenged, status(&deps, &env));
+
+        let msg = HandleMsg::ResolveChallenge {
+            id: 0,
+            upheld: false,
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("pauser", &[]),
+            msg.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let res = handle(&mut deps, env.clone(), mock_info("manager", &[]), msg).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "treasury".into(),
+                amount: coins(50, "earth"),
+            }),
+            res.messages[0]
+        );
+        assert_eq!(ResultStatus::Final, status(&deps, &env));
+    }
+
+    #[test]
+    fn committee_nominates_candidates() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            nomination: Some(Nomination {
+                committee: vec!["member1".into(), "member2".into()],
+                approvals: 2,
+            }),
+            candidates: vec![],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 5;
+        let msg = HandleMsg::ProposeCandidate {
+            candidate: "dave".into(),
+        };
+        handle(&mut deps, env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        let approve = HandleMsg::ApproveCandidate {
+            candidate: "dave".into(),
+        };
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("member1", &[]),
+            approve.clone(),
+        )
+        .unwrap();
+
+        let msg = QueryMsg::ListCandidateProposals {
+            start_after: None,
+            limit: None,
+        };
+        let res = query(&deps, env.clone(), msg.clone()).unwrap();
+        let value: CandidateProposalsResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            vec![HumanAddr::from("member1")],
+            value.proposals[0].approvals
+        );
+
+        handle(&mut deps, env.clone(), mock_info("member2", &[]), approve).unwrap();
+        let res = query(&deps, env.clone(), msg).unwrap();
+        let value: CandidateProposalsResponse = from_binary(&res).unwrap();
+        assert!(value.proposals.is_empty());
+
+        env.block.height = 50;
+        let vote = HandleMsg::Vote {
+            candidate: "dave".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), vote).unwrap();
+        let msg = HandleMsg::ProposeCandidate {
+            candidate: "erin".into(),
+        };
+        let err = handle(&mut deps, env, mock_info("anyone", &[]), msg).unwrap_err();
+        match err {
+            ContractError::NominationClosed { start } => assert_eq!(10, start),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn borda_count_elects_consensus_candidate() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            voting_system: VotingSystem::Borda,
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let rankings: &[(&str, &[&str])] = &[
+            ("voter1", &["alice", "bob", "carol"]),
+            ("voter2", &["alice", "bob", "carol"]),
+            ("voter3", &["carol", "bob", "alice"]),
+            ("voter4", &["carol", "bob", "alice"]),
+            ("voter5", &["bob", "alice", "carol"]),
+        ];
+        for (voter, ranking) in rankings {
+            let msg = HandleMsg::RankedVote {
+                ranking: ranking.iter().map(|c| HumanAddr::from(*c)).collect(),
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let res = query(&deps, env, QueryMsg::GetResult { locale: None }).unwrap();
+        let value: ResultResponse = from_binary(&res).unwrap();
+        // bob has a single first preference but is everyone's second choice
+        assert_eq!(HumanAddr::from("bob"), value.winners[0].candidate);
+        assert_eq!(Uint128(6), value.winners[0].weight);
+        assert_eq!(VotingSystem::Borda, value.voting_system);
+    }
+
+    #[test]
+    fn cumulative_points() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            voting_system: VotingSystem::Cumulative { points: 3 },
+            seats: 2,
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let split = |shares: &[(&str, u64)]| HandleMsg::SplitVote {
+            shares: shares
+                .iter()
+                .map(|(candidate, share)| Share {
+                    candidate: (*candidate).into(),
+                    share: *share,
+                })
+                .collect(),
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            split(&[("alice", 2), ("bob", 2)]),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::PointsExceeded { budget } => assert_eq!(3, budget),
+            e => panic!("unexpected error: {}", e),
+        }
+        let ballots = vec![
+            ("voter1", split(&[("alice", 2), ("bob", 1)])),
+            ("voter2", split(&[("bob", 1), ("carol", 1)])),
+            // a minority concentrates its points
+            (
+                "voter3",
+                HandleMsg::Vote {
+                    candidate: "carol".into(),
+                    proof: None,
+                    answers: None,
+                },
+            ),
+        ];
+        for (voter, msg) in ballots {
+            handle(&mut deps, env.clone(), mock_info(voter, &[]), msg).unwrap();
+        }
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+        let res = query(&deps, env, QueryMsg::GetResult { locale: None }).unwrap();
+        let value: ResultResponse = from_binary(&res).unwrap();
+        assert_eq!(HumanAddr::from("carol"), value.winners[0].candidate);
+        assert_eq!(Uint128(4), value.winners[0].weight);
+        assert_eq!(HumanAddr::from("alice"), value.winners[1].candidate);
+    }
+
+    #[test]
+    fn campaign_contributions_follow_outcome() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into(), "bob".into()],
+            veto_council: Some(VetoCouncil {
+                members: vec!["member1".into()],
+                threshold: 1,
+                window: 10,
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let contribute = |candidate: &str| HandleMsg::Contribute {
+            candidate: candidate.into(),
+        };
+        for (supporter, candidate) in &[("fan1", "alice"), ("fan2", "alice"), ("fan3", "bob")] {
+            let info = mock_info(*supporter, &coins(10, "earth"));
+            handle(&mut deps, env.clone(), info, contribute(candidate)).unwrap();
+        }
+        let msg = QueryMsg::GetContributions {
+            candidate: "alice".into(),
+        };
+        let res = query(&deps, env.clone(), msg).unwrap();
+        let value: ContributionsResponse = from_binary(&res).unwrap();
+        assert_eq!(coins(20, "earth"), value.total);
+        // the escrow is kept under the canonical address of the candidate
+        let alice_raw = deps.api.canonical_address(&"alice".into()).unwrap();
+        let total = campaign_totals_read(&deps.storage)
+            .may_load(alice_raw.as_slice())
+            .unwrap();
+        assert_eq!(Some(coins(20, "earth")), total);
+        assert!(campaign_totals_read(&deps.storage)
+            .may_load(b"alice")
+            .unwrap()
+            .is_none());
+
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), vote).unwrap();
+        env.block.height = 101;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            HandleMsg::Finalize { limit: None },
+        )
+        .unwrap();
+
+        let release = |candidate: &str| HandleMsg::ReleaseContributions {
+            candidate: candidate.into(),
+        };
+        // the veto window is open until 111
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            release("alice"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::ResultNotFinal {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        env.block.height = 112;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            release("alice"),
+        )
+        .unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "alice".into(),
+                amount: coins(20, "earth"),
+            }),
+            res.messages[0]
+        );
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            release("bob"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NotAWinner { candidate } => assert_eq!("bob", candidate.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+        let refund = HandleMsg::RefundContribution {
+            candidate: "bob".into(),
+        };
+        let res = handle(&mut deps, env.clone(), mock_info("fan3", &[]), refund).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "fan3".into(),
+                amount: coins(10, "earth"),
+            }),
+            res.messages[0]
+        );
+    }
+
+    #[test]
+    fn locked_stake_weighs_ballots_until_end() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            stake_weight: Some(StakeWeight::Locked {
+                denom: "earth".into(),
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let info = mock_info("voter1", &coins(40, "earth"));
+        handle(&mut deps, env.clone(), info, HandleMsg::Lock {}).unwrap();
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), vote).unwrap();
+        let msg = QueryMsg::GetVoteInfo {
+            locale: None,
+            sort: None,
+        };
+        let value: VoteResponse = from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(Uint128(40), value.votes[0].weight);
+
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            HandleMsg::Unlock {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::ElectionNotEnded { end } => assert_eq!(100, end),
+            e => panic!("unexpected error: {:?}", e),
+        }
+        let msg = QueryMsg::GetLockedBalance {
+            voter: "voter1".into(),
+        };
+        let res = query(&deps, env.clone(), msg).unwrap();
+        let value: LockedBalanceResponse = from_binary(&res).unwrap();
+        assert_eq!(coin(40, "earth"), value.locked);
+        assert_eq!(100, value.until);
+
+        env.block.height = 101;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            HandleMsg::Unlock {},
+        )
+        .unwrap();
+        let expected: CosmosMsg = BankMsg::Send {
+            from_address: env.contract.address.clone(),
+            to_address: "voter1".into(),
+            amount: coins(40, "earth"),
+        }
+        .into();
+        assert_eq!(vec![expected], res.messages);
+        let err = handle(
+            &mut deps,
+            env,
+            mock_info("voter1", &[]),
+            HandleMsg::Unlock {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NothingLocked { .. } => {}
+            e => panic!("unexpected error: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn candidate_rules_block_self_support() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into(), "bob".into()],
+            candidate_rules: Some(CandidateRules {
+                no_self_vote: true,
+                no_candidate_relayers: true,
+                max_relay_depth: Some(2),
+                ..CandidateRules::default()
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = |candidate: &str| HandleMsg::Vote {
+            candidate: candidate.into(),
+            proof: None,
+            answers: None,
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("alice", &[]),
+            vote("alice"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::SelfVote { voter } => assert_eq!(HumanAddr::from("alice"), voter),
+            e => panic!("unexpected error: {:?}", e),
+        }
+        handle(&mut deps, env.clone(), mock_info("alice", &[]), vote("bob")).unwrap();
+
+        let set_relayer = |relayer: &str| HandleMsg::SetRelayer {
+            relayer: Some(relayer.into()),
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            set_relayer("bob"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::CandidateRelayer { relayer } => {
+                assert_eq!(HumanAddr::from("bob"), relayer)
+            }
+            e => panic!("unexpected error: {:?}", e),
+        }
+        // voter1 -> relay1 -> relay2 fits a depth of two, a third hop does not
+        for (voter, relayer) in &[("relay1", "relay2"), ("voter1", "relay1")] {
+            handle(
+                &mut deps,
+                env.clone(),
+                mock_info(*voter, &[]),
+                set_relayer(relayer),
+            )
+            .unwrap();
+        }
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter2", &[]),
+            set_relayer("voter1"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::RelayDepthExceeded { max } => assert_eq!(2, max),
+            e => panic!("unexpected error: {:?}", e),
+        }
+        env.block.height = 51;
+        let batch = HandleMsg::BatchVote {
+            votes: vec![BallotEntry {
+                voter: "voter1".into(),
+                ballot: Ballot::Vote {
+                    candidate: "alice".into(),
+                },
+                idempotency_key: None,
+            }],
+        };
+        let res = handle(&mut deps, env, mock_info("relay2", &[]), batch).unwrap();
+        assert_eq!(attr("entry_0", "ok"), res.attributes[2]);
+    }
+
+    #[test]
+    fn templates_spawn_with_relative_window() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            election_code_id: Some(7),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let template = InitMsg {
+            start: 5,
+            end: 50,
+            seats: 2,
+            candidates: vec![],
+            ..init_msg()
+        };
+        let save = HandleMsg::SaveTemplate {
+            name: "board".into(),
+            params: Box::new(template.clone()),
+        };
+        let err = handle(&mut deps, mock_env(), mock_info("dao", &[]), save.clone()).unwrap_err();
+        match err {
+            ContractError::Unauthorized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(&mut deps, mock_env(), mock_info("creator", &[]), save).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 1000;
+        let spawn = HandleMsg::SpawnFromTemplate {
+            name: "board".into(),
+            label: "board 2026".into(),
+            candidates: vec!["alice".into(), "bob".into()],
+        };
+        let res = handle(&mut deps, env.clone(), mock_info("creator", &[]), spawn).unwrap();
+        let child_msg = InitMsg {
+            start: 1005,
+            end: 1050,
+            candidates: vec!["alice".into(), "bob".into()],
+            factory: Some(FactoryLink {
+                address: env.contract.address.clone(),
+                spawn_id: 1,
+            }),
+            ..template
+        };
+        let instantiate: CosmosMsg = WasmMsg::Instantiate {
+            code_id: 7,
+            msg: to_binary(&child_msg).unwrap(),
+            send: vec![],
+            label: Some("board 2026".into()),
+        }
+        .into();
+        assert_eq!(vec![instantiate], res.messages);
+
+        let spawn = HandleMsg::SpawnFromTemplate {
+            name: "senate".into(),
+            label: "senate".into(),
+            candidates: vec![],
+        };
+        let err = handle(&mut deps, env, mock_info("creator", &[]), spawn).unwrap_err();
+        match err {
+            ContractError::UnknownTemplate { name } => assert_eq!("senate", name),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn check_phase_follows_heights() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            registration: Some(RegistrationConfig {
+                start: 5,
+                end: 9,
+                bond: None,
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        let mut phase_at = |height: u64| {
+            env.block.height = height;
+            let res = query(&deps, env.clone(), QueryMsg::GetPhase {}).unwrap();
+            let value: PhaseResponse = from_binary(&res).unwrap();
+            (value.phase, value.next_at)
+        };
+        assert_eq!((Phase::Pending, Some(5)), phase_at(1));
+        assert_eq!((Phase::Registration, Some(10)), phase_at(7));
+        assert_eq!((Phase::Voting, Some(101)), phase_at(50));
+        assert_eq!((Phase::Tallying, None), phase_at(101));
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("keeper", &[]),
+            HandleMsg::CheckPhase {},
+        )
+        .unwrap();
+        assert_eq!(attr("phase", "voting"), res.attributes[1]);
+        env.block.height = 101;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("keeper", &[]),
+            HandleMsg::CheckPhase {},
+        )
+        .unwrap();
+        assert_eq!(Some(&attr("phase", "finalized")), res.attributes.last());
+        let res = query(&deps, env, QueryMsg::GetPhase {}).unwrap();
+        let value: PhaseResponse = from_binary(&res).unwrap();
+        assert_eq!(Phase::Finalized, value.phase);
+    }
+
+    #[test]
+    fn finalize_resumes_in_batches() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for (voter, candidate) in &[
+            ("voter1", "bob"),
+            ("voter2", "alice"),
+            ("voter3", "bob"),
+            ("voter4", "alice"),
+            ("voter5", "bob"),
+        ] {
+            let vote = HandleMsg::Vote {
+                candidate: (*candidate).into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), vote).unwrap();
+        }
+        env.block.height = 101;
+        let finalize = HandleMsg::Finalize { limit: Some(2) };
+        for remaining in &[3, 1] {
+            let res = handle(
+                &mut deps,
+                env.clone(),
+                mock_info("keeper", &[]),
+                finalize.clone(),
+            )
+            .unwrap();
+            assert_eq!(attr("remaining", remaining), res.attributes[2]);
+        }
+        let err = query(&deps, env.clone(), QueryMsg::GetResult { locale: None }).unwrap_err();
+        match err {
+            StdError::NotFound { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let res = handle(&mut deps, env.clone(), mock_info("keeper", &[]), finalize).unwrap();
+        assert_eq!(attr("action", "finalize"), res.attributes[0]);
+        let res = query(&deps, env, QueryMsg::GetResult { locale: None }).unwrap();
+        let value: ResultResponse = from_binary(&res).unwrap();
+        assert_eq!(HumanAddr::from("bob"), value.winners[0].candidate);
+        assert_eq!(Uint128(3), value.winners[0].weight);
+    }
+
+    #[test]
+    fn funds_must_match_election_denom() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            denom: Some("earth".into()),
+            ..init_msg()
+        };
+        let info = mock_info("creator", &coins(100, "mars"));
+        let err = init(&mut deps, mock_env(), info, msg.clone()).unwrap_err();
+        match err {
+            ContractError::WrongDenom { expected, denom } => {
+                assert_eq!(("earth", "mars"), (expected.as_str(), denom.as_str()))
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("creator", &coins(100, "earth"));
+        init(&mut deps, mock_env(), info, msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let contribute = HandleMsg::Contribute {
+            candidate: "alice".into(),
+        };
+        let info = mock_info("fan1", &[coin(5, "earth"), coin(5, "mars")]);
+        let err = handle(&mut deps, env.clone(), info, contribute.clone()).unwrap_err();
+        match err {
+            ContractError::WrongDenom { denom, .. } => assert_eq!("mars", denom),
+            e => panic!("unexpected error: {}", e),
+        }
+        let info = mock_info("fan1", &coins(5, "earth"));
+        handle(&mut deps, env, info, contribute).unwrap();
+    }
+
+    #[test]
+    fn candidate_count_query() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for voter in &["voter1", "voter2"] {
+            let msg = HandleMsg::Vote {
+                candidate: "alice".into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        let count = |candidate: &str| {
+            let msg = QueryMsg::GetCandidateCount {
+                candidate: candidate.into(),
+            };
+            query(&deps, env.clone(), msg)
+                .map(|res| from_binary::<CandidateCountResponse>(&res).unwrap())
+                .map(|value| (value.count, value.weight))
+        };
+        assert_eq!((2, Uint128(2)), count("alice").unwrap());
+        assert_eq!((0, Uint128::zero()), count("bob").unwrap());
+        match count("mallory").unwrap_err() {
+            StdError::NotFound { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn can_vote_reports_first_failing_check() {
+        let mut deps = mock_dependencies(&[]);
+        init(&mut deps, mock_env(), mock_info("creator", &[]), init_msg()).unwrap();
+        let mut env = mock_env();
+        let check =
+            |env: &Env, deps: &Extern<MockStorage, MockApi, MockQuerier>, candidate: &str| {
+                let msg = QueryMsg::CanVote {
+                    voter: "voter1".into(),
+                    candidate: candidate.into(),
+                };
+                let value: CanVoteResponse =
+                    from_binary(&query(deps, env.clone(), msg).unwrap()).unwrap();
+                (value.check, value.weight)
+            };
+        env.block.height = 5;
+        assert_eq!(
+            (
+                VoteCheck::OutsideWindow {
+                    begin: 10,
+                    end: 100
+                },
+                0
+            ),
+            check(&env, &deps, "alice")
+        );
+        env.block.height = 50;
+        assert_eq!((VoteCheck::UnknownCandidate, 0), check(&env, &deps, "bob"));
+        assert_eq!((VoteCheck::Eligible, 1), check(&env, &deps, "alice"));
+
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), vote).unwrap();
+        assert_eq!((VoteCheck::AlreadyVoted, 0), check(&env, &deps, "alice"));
+    }
+
+    #[test]
+    fn too_few_ballots_invalidate_the_election() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            min_votes: Some(2),
+            ..init_msg()
+        };
+        init(
+            &mut deps,
+            mock_env(),
+            mock_info("creator", &coins(100, "earth")),
+            msg,
+        )
+        .unwrap();
+        let mut env = mock_env();
+        env.block.height = 10;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), vote).unwrap();
+
+        env.block.height = 101;
+        let finalize = HandleMsg::Finalize { limit: None };
+        let res = handle(&mut deps, env.clone(), mock_info("keeper", &[]), finalize).unwrap();
+        assert_eq!(
+            CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "creator".into(),
+                amount: coins(100, "earth"),
+            }),
+            res.messages[0]
+        );
+        let res = query(&deps, env, QueryMsg::GetResult { locale: None }).unwrap();
+        let value: ResultResponse = from_binary(&res).unwrap();
+        assert!(value.winners.is_empty());
+        assert_eq!(ResultStatus::Invalid, value.status);
+    }
+
+    #[test]
+    fn party_list_splits_seats_between_slates() {
+        let mut deps = mock_dependencies(&[]);
+        let slate = |name: &str, candidates: &[&str]| Slate {
+            name: name.into(),
+            candidates: candidates.iter().map(|c| HumanAddr::from(*c)).collect(),
+        };
+        let msg = InitMsg {
+            seats: 3,
+            party_list: Some(PartyList {
+                allocation: SeatAllocation::DHondt,
+                slates: vec![
+                    slate("reds", &["alice", "bob", "carol"]),
+                    slate("blues", &["dave", "erin"]),
+                ],
+            }),
+            candidates: ["alice", "bob", "carol", "dave", "erin"]
+                .iter()
+                .map(|c| HumanAddr::from(*c))
+                .collect(),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 10;
+        for voter in &["voter1", "voter2", "voter3", "voter4"] {
+            let vote = HandleMsg::SlateVote {
+                name: "reds".into(),
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), vote).unwrap();
+        }
+        // a vote for a listed candidate counts for the slate
+        let vote = HandleMsg::Vote {
+            candidate: "erin".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter5", &[]), vote).unwrap();
+        for voter in &["voter6", "voter7"] {
+            let vote = HandleMsg::SlateVote {
+                name: "blues".into(),
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), vote).unwrap();
+        }
+        let vote = HandleMsg::SlateVote {
+            name: "greens".into(),
+        };
+        let err = handle(&mut deps, env.clone(), mock_info("voter8", &[]), vote).unwrap_err();
+        match err {
+            ContractError::UnknownSlate { name } => assert_eq!("greens", name),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 101;
+        let finalize = HandleMsg::Finalize { limit: None };
+        handle(&mut deps, env.clone(), mock_info("creator", &[]), finalize).unwrap();
+        let res = query(&deps, env, QueryMsg::GetResult { locale: None }).unwrap();
+        let value: ResultResponse = from_binary(&res).unwrap();
+        let winners: Vec<&str> = value.winners.iter().map(|w| w.candidate.as_str()).collect();
+        // reds 4, then blues 3 over reds 4 / 2, then reds 4 / 2 over blues 3 / 2
+        assert_eq!(vec!["alice", "dave", "bob"], winners);
+    }
+
+    #[test]
+    fn factory_lists_elections_by_status_and_tag() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            election_code_id: Some(7),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let spawns = [
+            ("treasury", 10, 100),
+            ("council", 200, 300),
+            ("grants", 10, 50),
+        ];
+        for (i, (tag, start, end)) in spawns.iter().enumerate() {
+            let params = InitMsg {
+                start: *start,
+                end: *end,
+                metadata: Some(Metadata {
+                    title: format!("{} election", tag),
+                    tags: vec![tag.to_string(), "dao".into()],
+                    ..Metadata::default()
+                }),
+                ..init_msg()
+            };
+            let msg = HandleMsg::SpawnElection {
+                label: tag.to_string(),
+                params: Box::new(params),
+            };
+            handle(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let msg = HandleMsg::RegisterSpawned {
+                spawn_id: i as u64 + 1,
+            };
+            let child = format!("child{}", i + 1);
+            handle(&mut deps, mock_env(), mock_info(child, &[]), msg).unwrap();
+        }
+
+        let mut env = mock_env();
+        env.block.height = 60;
+        let list = |status_filter, tag_filter: Option<&str>, start_after| {
+            let msg = QueryMsg::ListElections {
+                status_filter,
+                tag_filter: tag_filter.map(String::from),
+                start_after,
+                limit: None,
+            };
+            let res = query(&deps, env.clone(), msg).unwrap();
+            let value: ElectionsResponse = from_binary(&res).unwrap();
+            value
+                .elections
+                .into_iter()
+                .map(|e| e.label)
+                .collect::<Vec<String>>()
+        };
+        assert_eq!(
+            vec!["treasury"],
+            list(Some(ElectionStatus::Active), None, None)
+        );
+        assert_eq!(
+            vec!["grants"],
+            list(Some(ElectionStatus::Ended), None, None)
+        );
+        assert_eq!(vec!["council"], list(None, Some("council"), None));
+        assert_eq!(vec!["council", "grants"], list(None, Some("dao"), Some(1)));
+
+        let msg = InitMsg {
+            metadata: Some(Metadata::default()),
+            ..init_msg()
+        };
+        let err = init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap_err();
+        match err {
+            ContractError::InvalidMetadata { max_tags } => assert_eq!(8, max_tags),
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn first_ballot_freezes_the_voter_roll() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            registration: Some(RegistrationConfig {
+                start: 5,
+                end: 50,
+                bond: None,
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 8;
+        for voter in &["voter1", "voter2"] {
+            let msg = HandleMsg::RegisterVoter {};
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("anyone", &[]),
+            HandleMsg::Snapshot {},
+        )
+        .unwrap_err();
+        match err {
+            ContractError::SnapshotTooEarly { start } => assert_eq!(10, start),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 12;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), vote).unwrap();
+        let msg = HandleMsg::RegisterVoter {};
+        let err = handle(&mut deps, env.clone(), mock_info("voter3", &[]), msg).unwrap_err();
+        match err {
+            ContractError::SnapshotTaken { height } => assert_eq!(12, height),
+            e => panic!("unexpected error: {}", e),
+        }
+        let res = query(&deps, env, QueryMsg::GetVoterRoll {}).unwrap();
+        let roll: VoterRollResponse = from_binary(&res).unwrap();
+        assert_eq!(12, roll.height);
+        assert_eq!(2, roll.registered);
+    }
+
+    #[test]
+    fn proxy_casts_one_ballot_for_its_principal() {
+        let mut deps = mock_dependencies(&[]);
+        init(&mut deps, mock_env(), mock_info("creator", &[]), init_msg()).unwrap();
+        let mut env = mock_env();
+        env.block.height = 10;
+        let authorize = HandleMsg::AuthorizeProxy {
+            proxy: "proxy".into(),
+            expires: 20,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), authorize).unwrap();
+        let proxy_vote = HandleMsg::ProxyVote {
+            principal: "voter1".into(),
+            ballot: Ballot::Vote {
+                candidate: "alice".into(),
+            },
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("mallory", &[]),
+            proxy_vote.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NotProxy { principal } => assert_eq!("voter1", principal.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 21;
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("proxy", &[]),
+            proxy_vote.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::ProxyExpired { expires } => assert_eq!(20, expires),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 15;
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("proxy", &[]),
+            proxy_vote.clone(),
+        )
+        .unwrap();
+        assert!(res.attributes.contains(&attr("principal", "voter1")));
+        assert!(res.attributes.contains(&attr("proxy", "proxy")));
+        let msg = QueryMsg::GetBallot {
+            voter: "voter1".into(),
+        };
+        let ballot: BallotResponse = from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        assert_eq!("alice", ballot.candidate.as_str());
+        // the grant is used up
+        let err = handle(&mut deps, env, mock_info("proxy", &[]), proxy_vote).unwrap_err();
+        match err {
+            ContractError::NotProxy { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn adapter_contract_reports_voting_power() {
+        let deps = mock_dependencies(&[]);
+        let mut deps = with_wasm_querier(deps, |contract, msg| {
+            assert_eq!("adapter", contract.as_str());
+            match from_binary(msg)? {
+                VotingPowerQueryMsg::VotingPower { voter, height } => {
+                    assert_eq!(10, height);
+                    let power = if voter.as_str() == "member" { 9 } else { 0 };
+                    to_binary(&VotingPowerResponse {
+                        power: Uint128(power),
+                    })
+                }
+            }
+        });
+        let msg = InitMsg {
+            stake_weight: Some(StakeWeight::Adapter {
+                address: "adapter".into(),
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("member", &[]),
+            vote.clone(),
+        )
+        .unwrap();
+        let err = handle(&mut deps, env.clone(), mock_info("outsider", &[]), vote).unwrap_err();
+        match err {
+            ContractError::NoVotingPower { voter } => assert_eq!("outsider", voter.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+        let msg = QueryMsg::GetVoteInfo {
+            locale: None,
+            sort: None,
+        };
+        let value: VoteResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        assert_eq!(Uint128(9), value.votes[0].weight);
+    }
+
+    #[test]
+    fn every_privileged_action_is_audited() {
+        let run = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>,
+                   height: u64,
+                   sender: &str,
+                   funds: &[Coin],
+                   msg: HandleMsg| {
+            let mut env = mock_env();
+            env.block.height = height;
+            handle(deps, env, mock_info(sender, funds), msg).unwrap();
+        };
+        let actions = |deps: &Extern<MockStorage, MockApi, MockQuerier>| -> Vec<String> {
+            let msg = QueryMsg::GetAuditLog {
+                start_after: None,
+                limit: Some(100),
+            };
+            let log: AuditLogResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            log.entries.into_iter().map(|e| e.action).collect()
+        };
+
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            tie_policy: TiePolicy::Manual,
+            nomination: Some(Nomination {
+                committee: vec!["member1".into()],
+                approvals: 1,
+            }),
+            dispute: Some(DisputeConfig {
+                window: 10,
+                bond: coin(50, "earth"),
+                treasury: "treasury".into(),
+            }),
+            veto_council: Some(VetoCouncil {
+                members: vec!["guard1".into(), "guard2".into()],
+                threshold: 2,
+                window: 10,
+            }),
+            succession: Some(Succession {
+                window: 30,
+                arbiter: None,
+            }),
+            roles: vec![RoleGrant {
+                address: "manager".into(),
+                role: Role::ElectionManager,
+            }],
+            election_code_id: Some(7),
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let template = InitMsg {
+            candidates: vec![],
+            ..init_msg()
+        };
+        let d = &mut deps;
+        let msg = HandleMsg::SaveTemplate {
+            name: "board".into(),
+            params: Box::new(template),
+        };
+        run(d, 5, "creator", &[], msg);
+        let msg = HandleMsg::SpawnElection {
+            label: "council".into(),
+            params: Box::new(init_msg()),
+        };
+        run(d, 5, "creator", &[], msg);
+        let msg = HandleMsg::SpawnFromTemplate {
+            name: "board".into(),
+            label: "board".into(),
+            candidates: vec!["alice".into()],
+        };
+        run(d, 5, "manager", &[], msg);
+        let carol = || HumanAddr::from("carol");
+        let msg = HandleMsg::ProposeCandidate { candidate: carol() };
+        run(d, 5, "anyone", &[], msg);
+        let msg = HandleMsg::ApproveCandidate { candidate: carol() };
+        run(d, 5, "member1", &[], msg);
+        let disclosure = |candidate: &str| HandleMsg::RecordDisclosure {
+            candidate: candidate.into(),
+            source: "donor".into(),
+            amount: coin(10, "earth"),
+            memo: "".into(),
+        };
+        run(d, 5, "alice", &[], disclosure("alice"));
+        run(d, 5, "manager", &[], disclosure("bob"));
+        let msg = HandleMsg::SetEligibilityFilter { filter: None };
+        run(d, 5, "manager", &[], msg);
+        let msg = HandleMsg::UpdatePeriod {
+            start: 10,
+            end: 100,
+        };
+        run(d, 5, "manager", &[], msg);
+        let msg = HandleMsg::GrantRole {
+            address: "pauser".into(),
+            role: Role::Pauser,
+        };
+        run(d, 5, "creator", &[], msg);
+        run(d, 5, "pauser", &[], HandleMsg::Pause {});
+        run(d, 5, "pauser", &[], HandleMsg::Unpause {});
+        let msg = HandleMsg::RevokeRole {
+            address: "pauser".into(),
+            role: Role::Pauser,
+        };
+        run(d, 5, "creator", &[], msg);
+        for (voter, candidate) in &[("voter1", "alice"), ("voter2", "bob")] {
+            let msg = HandleMsg::Vote {
+                candidate: (*candidate).into(),
+                proof: None,
+                answers: None,
+            };
+            run(d, 50, voter, &[], msg);
+        }
+        let msg = HandleMsg::ResolveTie {
+            winners: vec!["alice".into()],
+        };
+        run(d, 101, "manager", &[], msg);
+        let msg = HandleMsg::Challenge {
+            reason: "miscount".into(),
+        };
+        run(d, 101, "skeptic", &coins(50, "earth"), msg);
+        let msg = HandleMsg::ResolveChallenge {
+            id: 0,
+            upheld: false,
+        };
+        run(d, 102, "manager", &[], msg);
+        run(d, 102, "guard1", &[], HandleMsg::ApproveVeto {});
+        let msg = HandleMsg::PromoteRunnerUp {
+            vacated: "alice".into(),
+        };
+        run(d, 102, "manager", &[], msg);
+        run(d, 112, "manager", &[], HandleMsg::Archive {});
+        run(d, 112, "manager", &[], HandleMsg::Scrub { limit: None });
+        let msg = HandleMsg::ProposeNewOwner {
+            address: "heir".into(),
+        };
+        run(d, 112, "creator", &[], msg);
+        run(d, 112, "heir", &[], HandleMsg::AcceptOwnership {});
+        let expected = vec![
+            "save_template",
+            "spawn_election",
+            "spawn_from_template",
+            "propose_candidate",
+            "approve_candidate",
+            "record_disclosure",
+            "set_eligibility_filter",
+            "update_period",
+            "grant_role",
+            "pause",
+            "unpause",
+            "revoke_role",
+            "resolve_tie",
+            "resolve_challenge",
+            "approve_veto",
+            "promote_runner_up",
+            "archive",
+            "scrub",
+            "propose_new_owner",
+            "accept_ownership",
+        ];
+        assert_eq!(expected, actions(&deps));
+
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            stake_weight: Some(StakeWeight::Contract {
+                address: "staking".into(),
+                snapshot_height: None,
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let d = &mut deps;
+        run(d, 5, "creator", &[], HandleMsg::MarkOracleCompromised {});
+        let msg = HandleMsg::ReplaceOracle {
+            address: "staking2".into(),
+        };
+        run(d, 5, "creator", &[], msg);
+        run(
+            d,
+            5,
+            "creator",
+            &[],
+            HandleMsg::SetReadOnly { read_only: true },
+        );
+        run(
+            d,
+            5,
+            "creator",
+            &[],
+            HandleMsg::SetReadOnly { read_only: false },
+        );
+        let expected = vec![
+            "mark_oracle_compromised",
+            "replace_oracle",
+            "set_read_only",
+            "set_read_only",
+        ];
+        assert_eq!(expected, actions(&deps));
+    }
+
+    #[test]
+    fn admin_actions_are_audited() {
+        let mut deps = mock_dependencies(&[]);
+        init(&mut deps, mock_env(), mock_info("creator", &[]), init_msg()).unwrap();
+        let mut env = mock_env();
+        env.block.height = 5;
+        let update = HandleMsg::UpdatePeriod { start: 20, end: 90 };
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            update.clone(),
+        )
+        .unwrap();
+        // rejected and unprivileged messages leave no entry
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("mallory", &[]),
+            HandleMsg::Pause {},
+        )
+        .unwrap_err();
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        env.block.height = 30;
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), vote).unwrap();
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            HandleMsg::Pause {},
+        )
+        .unwrap();
+        sudo(&mut deps, env.clone(), SudoMsg::Halt {}).unwrap();
+
+        let msg = QueryMsg::GetAuditLog {
+            start_after: None,
+            limit: None,
+        };
+        let log: AuditLogResponse = from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        let actions: Vec<&str> = log.entries.iter().map(|e| e.action.as_str()).collect();
+        assert_eq!(vec!["update_period", "pause", "halt"], actions);
+        let first = &log.entries[0];
+        assert_eq!(("creator", 5), (first.actor.as_str(), first.height));
+        assert_eq!(
+            sha256(&to_vec(&update).unwrap()).to_vec(),
+            first.payload_hash.to_vec()
+        );
+        assert_eq!(env.contract.address, log.entries[2].actor);
+
+        let msg = QueryMsg::GetAuditLog {
+            start_after: Some(1),
+            limit: None,
+        };
+        let log: AuditLogResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        assert_eq!(2, log.entries[0].id);
+    }
+
+    #[test]
+    fn candidate_rules_guard_registration() {
+        let rules = CandidateRules {
+            banned: vec!["mallory".into()],
+            no_owner: true,
+            ..CandidateRules::default()
+        };
+        let setup = |candidates: &[&str]| {
+            let msg = InitMsg {
+                candidate_rules: Some(rules.clone()),
+                nomination: Some(Nomination {
+                    committee: vec!["member".into()],
+                    approvals: 1,
+                }),
+                candidates: candidates.iter().map(|c| HumanAddr::from(*c)).collect(),
+                ..init_msg()
+            };
+            let mut deps = mock_dependencies(&[]);
+            init(&mut deps, mock_env(), mock_info("creator", &[]), msg).map(|_| deps)
+        };
+        match setup(&["alice", "creator"]).err().unwrap() {
+            ContractError::OwnerCandidate { candidate } => assert_eq!("creator", candidate),
+            e => panic!("unexpected error: {}", e),
+        }
+        match setup(&["mallory"]).err().unwrap() {
+            ContractError::BannedCandidate { candidate } => assert_eq!("mallory", candidate),
+            e => panic!("unexpected error: {}", e),
+        }
+
+        let mut deps = setup(&["alice"]).unwrap();
+        let mut env = mock_env();
+        env.block.height = 5;
+        let propose = |candidate: &str| HandleMsg::ProposeCandidate {
+            candidate: candidate.into(),
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("bob", &[]),
+            propose("mallory"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::BannedCandidate { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("bob", &[]),
+            propose("alice"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::DuplicateCandidate { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(&mut deps, env, mock_info("bob", &[]), propose("bob")).unwrap();
+    }
+
+    #[test]
+    fn leaderboard_ranks_by_ballots() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let ballots = [
+            ("voter1", "bob"),
+            ("voter2", "alice"),
+            ("voter3", "bob"),
+            ("voter4", "bob"),
+            ("voter5", "alice"),
+        ];
+        for (voter, candidate) in &ballots {
+            let msg = HandleMsg::Vote {
+                candidate: (*candidate).into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        let top = |deps: &Extern<MockStorage, MockApi, MockQuerier>, limit| {
+            let msg = QueryMsg::GetLeaderboard { limit };
+            let res: LeaderboardResponse =
+                from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.candidates
+                .into_iter()
+                .map(|entry| (entry.candidate.to_string(), entry.count))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![("bob".to_string(), 3), ("alice".to_string(), 2)],
+            top(&deps, None)
+        );
+        assert_eq!(vec![("bob".to_string(), 3)], top(&deps, Some(1)));
+
+        // discarded ballots leave the leaderboard with the candidate
+        let msg = HandleMsg::WithdrawCandidacy {};
+        handle(&mut deps, env, mock_info("bob", &[]), msg).unwrap();
+        assert_eq!(vec![("alice".to_string(), 2)], top(&deps, None));
+    }
+
+    #[test]
+    fn ballot_fees_are_refunded_or_forfeited() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            ballot_fee: Some(coin(10, "ucoin")),
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = |candidate: &str| HandleMsg::Vote {
+            candidate: candidate.into(),
+            proof: None,
+            answers: None,
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            vote("alice"),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::InsufficientBallotFee { amount, .. } => assert_eq!(Uint128(10), amount),
+            e => panic!("unexpected error: {}", e),
+        }
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &coins(15, "ucoin")),
+            vote("alice"),
+        )
+        .unwrap();
+        assert_eq!(
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "voter1".into(),
+                amount: coins(5, "ucoin"),
+            })],
+            res.messages
+        );
+        let info = mock_info("voter2", &coins(10, "ucoin"));
+        handle(&mut deps, env.clone(), info, vote("bob")).unwrap();
+        // a relayer pays for the ballots it submits and gets the fee back
+        let msg = HandleMsg::SetRelayer {
+            relayer: Some("relayer".into()),
+        };
+        handle(&mut deps, env.clone(), mock_info("voter3", &[]), msg).unwrap();
+        let msg = HandleMsg::BatchVote {
+            votes: vec![BallotEntry {
+                voter: "voter3".into(),
+                ballot: Ballot::Vote {
+                    candidate: "alice".into(),
+                },
+                idempotency_key: None,
+            }],
+        };
+        let info = mock_info("relayer", &coins(10, "ucoin"));
+        handle(&mut deps, env.clone(), info, msg).unwrap();
+
+        // voter2's ballot is discarded with bob and its fee forfeited
+        let msg = HandleMsg::WithdrawCandidacy {};
+        handle(&mut deps, env.clone(), mock_info("bob", &[]), msg).unwrap();
+        let claim = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>, env: &Env, voter| {
+            handle(
+                deps,
+                env.clone(),
+                mock_info(voter, &[]),
+                HandleMsg::ClaimBallotFee {},
+            )
+        };
+        match claim(&mut deps, &env, "voter1").unwrap_err() {
+            ContractError::NotFinalized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 101;
+        let msg = HandleMsg::Finalize { limit: None };
+        handle(&mut deps, env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        let res = claim(&mut deps, &env, "voter1").unwrap();
+        assert_eq!(
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "voter1".into(),
+                amount: coins(10, "ucoin"),
+            })],
+            res.messages
+        );
+        let res = claim(&mut deps, &env, "relayer").unwrap();
+        assert_eq!(
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                from_address: env.contract.address.clone(),
+                to_address: "relayer".into(),
+                amount: coins(10, "ucoin"),
+            })],
+            res.messages
+        );
+        for voter in &["voter1", "voter2", "voter3"] {
+            match claim(&mut deps, &env, voter).unwrap_err() {
+                ContractError::NoBallotFee { .. } => {}
+                e => panic!("unexpected error: {}", e),
+            }
+        }
+        let books = ledger_read(&deps.storage).load().unwrap();
+        assert_eq!(Uint128(10), books.balance(FundBucket::Burned, "ucoin"));
+        assert_eq!(
+            Uint128::zero(),
+            books.balance(FundBucket::BallotFees, "ucoin")
+        );
+    }
+
+    #[test]
+    fn deferred_registry_is_written_by_publish_result() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            registry: Some(RegistryConfig {
+                address: "kv".into(),
+                key_template: "elections/{election}".into(),
+                deferred: true,
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap();
+        let publish = |deps: &mut Extern<MockStorage, MockApi, MockQuerier>, env: &Env| {
+            handle(
+                deps,
+                env.clone(),
+                mock_info("anyone", &[]),
+                HandleMsg::PublishResult {},
+            )
+        };
+        match publish(&mut deps, &env).unwrap_err() {
+            ContractError::NotFinalized {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+
+        env.block.height = 101;
+        let msg = HandleMsg::Finalize { limit: None };
+        let res = handle(&mut deps, env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        assert!(res.messages.is_empty());
+
+        // a failed publish can simply be sent again
+        for _ in 0..2 {
+            let res = publish(&mut deps, &env).unwrap();
+            let msg = match &res.messages[..] {
+                [CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                })] => {
+                    assert_eq!("kv", contract_addr.as_str());
+                    from_binary::<RegistryMsg>(msg).unwrap()
+                }
+                messages => panic!("unexpected messages: {:?}", messages),
+            };
+            let RegistryMsg::Set { key, value } = msg;
+            assert_eq!(format!("elections/{}", env.contract.address), key);
+            let value: PublishedWinner = from_binary(&value).unwrap();
+            assert_eq!(("alice".into(), 1), (value.winner, value.turnout));
+        }
+    }
+
+    #[test]
+    fn late_surge_extends_the_deadline() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            anti_snipe: Some(AntiSnipe {
+                threshold: Decimal::percent(50),
+                window: 10,
+                extension: 20,
+                max_extension: 30,
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        let ballots = [
+            (50, "voter1"),
+            (50, "voter2"),
+            (50, "voter3"),
+            (95, "voter4"),
+            (96, "voter5"),
+            (97, "voter6"),
+        ];
+        for (height, voter) in &ballots {
+            env.block.height = *height;
+            let msg = HandleMsg::Vote {
+                candidate: "alice".into(),
+                proof: None,
+                answers: None,
+            };
+            let res = handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+            assert!(res.attributes.is_empty());
+        }
+        // four of seven ballots in the last ten blocks
+        env.block.height = 98;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter7", &[]),
+            vote.clone(),
+        )
+        .unwrap();
+        assert_eq!(vec![attr("extended_end", 120)], res.attributes);
+
+        // a second extension would exceed the cap
+        for (height, voter) in &[(115, "voter8"), (116, "voter9"), (117, "voter10")] {
+            env.block.height = *height;
+            let res = handle(&mut deps, env.clone(), mock_info(*voter, &[]), vote.clone()).unwrap();
+            assert!(res.attributes.is_empty());
+        }
+        let res: ExtensionsResponse =
+            from_binary(&query(&deps, env, QueryMsg::GetExtensions {}).unwrap()).unwrap();
+        assert_eq!(120, res.end);
+        assert_eq!(
+            vec![DeadlineExtension {
+                height: 98,
+                previous_end: 100,
+                end: 120,
+            }],
+            res.extensions
+        );
+    }
+
+    #[test]
+    fn history_rule_requires_an_earlier_ballot() {
+        let deps = mock_dependencies(&[]);
+        let mut deps = with_wasm_querier(deps, |contract, msg| {
+            assert_eq!("prior", contract.as_str());
+            match from_binary(msg)? {
+                QueryMsg::GetHistory { voter } => to_binary(&HistoryResponse {
+                    voted_at: Some(5).filter(|_| voter.as_str() == "veteran"),
+                    registered: true,
+                }),
+                _ => panic!("unexpected query"),
+            }
+        });
+        let msg = InitMsg {
+            history: Some(HistoryRule::VotedIn {
+                election: "prior".into(),
+            }),
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let vote = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("newcomer", &[]),
+            vote.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NoHistory { voter } => assert_eq!("newcomer", voter.as_str()),
+            e => panic!("unexpected error: {}", e),
+        }
+        handle(&mut deps, env.clone(), mock_info("veteran", &[]), vote).unwrap();
+
+        // this election keeps the record for the next one
+        let history = |voter: &str| {
+            let msg = QueryMsg::GetHistory {
+                voter: voter.into(),
+            };
+            from_binary::<HistoryResponse>(&query(&deps, env.clone(), msg).unwrap()).unwrap()
+        };
+        assert_eq!(Some(50), history("veteran").voted_at);
+        assert_eq!(None, history("newcomer").voted_at);
+    }
+
+    #[test]
+    fn questions_are_answered_in_one_ballot() {
+        let mut deps = mock_dependencies(&[]);
+        let options = |names: &[&str]| names.iter().map(|n| n.to_string()).collect();
+        let msg = InitMsg {
+            questions: vec![
+                Question {
+                    name: "chair".into(),
+                    options: options(&["ann", "ben"]),
+                    method: QuestionMethod::Plurality,
+                },
+                Question {
+                    name: "budget".into(),
+                    options: options(&["low", "mid", "high"]),
+                    method: QuestionMethod::Borda,
+                },
+            ],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let answer = |question: &str, choices: &[&str]| Answer {
+            question: question.into(),
+            choices: options(choices),
+        };
+        let vote = |answers| HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: Some(answers),
+        };
+        let msg = vote(vec![answer("chair", &["ann", "ben"])]);
+        match handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap_err() {
+            ContractError::InvalidAnswer { question } => assert_eq!("chair", question),
+            e => panic!("unexpected error: {}", e),
+        }
+        match handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            vote(vec![]),
+        )
+        .unwrap_err()
+        {
+            ContractError::NoAnswers {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let ballots = [
+            ("voter1", "ann", ["mid", "low", "high"]),
+            ("voter2", "ben", ["high", "mid", "low"]),
+            ("voter3", "ann", ["mid", "high", "low"]),
+        ];
+        for (voter, chair, budget) in &ballots {
+            let msg = vote(vec![answer("chair", &[chair]), answer("budget", budget)]);
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        // the answers are part of the candidate ballot
+        let msg = vote(vec![answer("chair", &["ben"])]);
+        match handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap_err() {
+            ContractError::AlreadyVoted { .. } => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        let counters = stats_read(&deps.storage).load().unwrap();
+        assert_eq!((3, 3), (counters.ballots, counters.unique_voters));
+
+        env.block.height = 101;
+        let msg = HandleMsg::Finalize { limit: None };
+        handle(&mut deps, env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        let msg = QueryMsg::GetResult { locale: None };
+        let res: ResultResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        let outcome = |question: usize| {
+            let outcome = &res.questions[question];
+            let totals: Vec<(&str, u128)> = outcome
+                .totals
+                .iter()
+                .map(|t| (t.option.as_str(), t.weight.u128()))
+                .collect();
+            (outcome.winner.as_deref(), totals)
+        };
+        assert_eq!((Some("ann"), vec![("ann", 2), ("ben", 1)]), outcome(0));
+        assert_eq!(
+            (Some("mid"), vec![("mid", 5), ("high", 3), ("low", 1)]),
+            outcome(1)
+        );
+    }
+
+    #[test]
+    fn simulate_tally_compares_methods() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            candidates: vec!["alice".into(), "bob".into(), "carol".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for (voter, candidate) in &[("voter1", "alice"), ("voter2", "alice"), ("voter3", "bob")] {
+            let msg = HandleMsg::Vote {
+                candidate: (*candidate).into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        let simulate = |method| {
+            let msg = QueryMsg::SimulateTally { method };
+            let res: SimulatedTallyResponse =
+                from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+            let winners: Vec<(String, u128)> = res
+                .winners
+                .into_iter()
+                .map(|w| (w.candidate.to_string(), w.weight.u128()))
+                .collect();
+            (res.voting_system, res.ballots, winners)
+        };
+        assert_eq!(
+            (VotingSystem::Plurality, 3, vec![("alice".to_string(), 2)]),
+            simulate(None)
+        );
+        // single candidate ballots rank only their candidate, worth n - 1 points
+        assert_eq!(
+            (VotingSystem::Borda, 3, vec![("alice".to_string(), 4)]),
+            simulate(Some(VotingSystem::Borda))
+        );
+        assert!(result_read(&deps.storage).may_load().unwrap().is_none());
+    }
+
+    #[test]
+    fn pseudonymous_records_survive_scrub() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            pseudonymous: true,
+            stake_weight: Some(StakeWeight::Locked {
+                denom: "earth".into(),
+            }),
+            questions: vec![Question {
+                name: "chair".into(),
+                options: vec!["ann".into(), "ben".into()],
+                method: QuestionMethod::Plurality,
+            }],
+            candidates: vec!["alice".into(), "bob".into()],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        let msg = HandleMsg::SetRelayer {
+            relayer: Some("relayer".into()),
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap();
+        let info = mock_info("voter1", &coins(100, "earth"));
+        handle(&mut deps, env.clone(), info, HandleMsg::Lock {}).unwrap();
+        for candidate in &["alice", "bob"] {
+            let msg = HandleMsg::Contribute {
+                candidate: (*candidate).into(),
+            };
+            let info = mock_info("voter1", &coins(5, "earth"));
+            handle(&mut deps, env.clone(), info, msg).unwrap();
+        }
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: Some(vec![Answer {
+                question: "chair".into(),
+                choices: vec!["ann".into()],
+            }]),
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap();
+        let voter_raw = deps.api.canonical_address(&"voter1".into()).unwrap();
+        assert!(participants_read(&deps.storage)
+            .may_load(voter_raw.as_slice())
+            .unwrap()
+            .is_none());
+        // neither the ballot nor its index name the voter
+        let state = config_read(&deps.storage).load().unwrap();
+        assert_ne!(voter_raw, state.votes[0].voter);
+        let alice = deps.api.canonical_address(&"alice".into()).unwrap();
+        assert!(candidate_votes_read(&deps.storage, &alice)
+            .may_load(voter_raw.as_slice())
+            .unwrap()
+            .is_none());
+        let msg = QueryMsg::GetBallot {
+            voter: "voter1".into(),
+        };
+        let ballot: BallotResponse = from_binary(&query(&deps, env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(64, ballot.voter.as_str().len());
+
+        let scrub = HandleMsg::Scrub { limit: None };
+        let err = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            scrub.clone(),
+        )
+        .unwrap_err();
+        match err {
+            ContractError::NotArchived {} => {}
+            e => panic!("unexpected error: {}", e),
+        }
+        env.block.height = 101;
+        let msg = HandleMsg::Finalize { limit: None };
+        handle(&mut deps, env.clone(), mock_info("anyone", &[]), msg).unwrap();
+        env.block.height = 102;
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            HandleMsg::Archive {},
+        )
+        .unwrap();
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            scrub.clone(),
+        )
+        .unwrap();
+        // the relayer, the cached weight, the answers and the contribution to
+        // the winner
+        assert_eq!(attr("removed", 4), res.attributes[1]);
+        let state = config_read(&deps.storage).load().unwrap();
+        let key = record_key(&state, voter_raw.as_slice());
+        assert!(relayers_read(&deps.storage)
+            .may_load(&key)
+            .unwrap()
+            .is_none());
+        let storage = &deps.storage;
+        let answers = crate::state::question_ballots_read(storage)
+            .range(None, None, Order::Ascending)
+            .count();
+        assert_eq!(0, answers);
+        let alice_funds = contributions_read(
+            storage,
+            &deps.api.canonical_address(&"alice".into()).unwrap(),
+        )
+        .range(None, None, Order::Ascending)
+        .count();
+        assert_eq!(0, alice_funds);
+        // funds the voter still holds in the contract stay under the pseudonym
+        let bob_raw = deps.api.canonical_address(&"bob".into()).unwrap();
+        assert!(contributions_read(storage, &bob_raw)
+            .may_load(&key)
+            .unwrap()
+            .is_some());
+        assert!(locks_read(storage).may_load(&key).unwrap().is_some());
+        let res = handle(
+            &mut deps,
+            env.clone(),
+            mock_info("creator", &[]),
+            scrub.clone(),
+        )
+        .unwrap();
+        assert_eq!(attr("removed", 0), res.attributes[1]);
+
+        handle(
+            &mut deps,
+            env.clone(),
+            mock_info("voter1", &[]),
+            HandleMsg::Unlock {},
+        )
+        .unwrap();
+        let msg = HandleMsg::RefundContribution {
+            candidate: "bob".into(),
+        };
+        handle(&mut deps, env.clone(), mock_info("voter1", &[]), msg).unwrap();
+        let storage = &deps.storage;
+        assert_eq!(
+            0,
+            locks_read(storage)
+                .range(None, None, Order::Ascending)
+                .count()
+        );
+        let bob_funds = contributions_read(storage, &bob_raw)
+            .range(None, None, Order::Ascending)
+            .count();
+        assert_eq!(0, bob_funds);
+
+        // the hashed history still answers for the voter
+        let msg = QueryMsg::GetHistory {
+            voter: "voter1".into(),
+        };
+        let history: HistoryResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        assert_eq!(Some(50), history.voted_at);
+    }
+
+    #[test]
+    fn failed_batch_entry_leaves_token_unused() {
+        let deps = mock_dependencies(&[]);
+        let mut deps = with_wasm_querier(deps, |_, msg| match from_binary(msg)? {
+            Cw721QueryMsg::Tokens { owner, .. } => to_binary(&TokensResponse {
+                tokens: vec![owner.to_string()],
+            }),
+        });
+        let msg = InitMsg {
+            nft_gate: Some("collection".into()),
+            limits: Limits {
+                max_votes_per_block: Some(1),
+                ..Limits::default()
+            },
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = HandleMsg::SetRelayer {
+            relayer: Some("relayer".into()),
+        };
+        handle(
+            &mut deps,
+            mock_env(),
+            mock_info("holder1", &[]),
+            msg.clone(),
+        )
+        .unwrap();
+        handle(&mut deps, mock_env(), mock_info("holder2", &[]), msg).unwrap();
+
+        let mut env = mock_env();
+        env.block.height = 50;
+        let entry = |voter: &str| BallotEntry {
+            voter: voter.into(),
+            ballot: Ballot::Vote {
+                candidate: "alice".into(),
+            },
+            idempotency_key: None,
+        };
+        let msg = HandleMsg::BatchVote {
+            votes: vec![entry("holder1"), entry("holder2")],
+        };
+        let res = handle(&mut deps, env.clone(), mock_info("relayer", &[]), msg).unwrap();
+        assert_eq!(attr("accepted", 1), res.attributes[1]);
+        assert_eq!(
+            attr(
+                "entry_1",
+                "failed: [E76] At most 1 ballots are accepted per block"
+            ),
+            res.attributes[3]
+        );
+        assert!(nft_votes_read(&deps.storage)
+            .may_load(b"holder2")
+            .unwrap()
+            .is_none());
+
+        env.block.height = 51;
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: None,
+        };
+        handle(&mut deps, env, mock_info("holder2", &[]), msg).unwrap();
+        assert_eq!(
+            Some(deps.api.canonical_address(&"holder2".into()).unwrap()),
+            nft_votes_read(&deps.storage).may_load(b"holder2").unwrap()
+        );
+    }
+
+    #[test]
+    fn question_and_referendum_ballots_pay_the_fee() {
+        let fee = coin(10, "ucoin");
+        let held = |deps: &Extern<MockStorage, MockApi, MockQuerier>| {
+            let books = ledger_read(&deps.storage).load().unwrap();
+            books.balance(FundBucket::BallotFees, "ucoin")
+        };
+        let mut env = mock_env();
+        env.block.height = 50;
+
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            ballot_fee: Some(fee.clone()),
+            questions: vec![Question {
+                name: "chair".into(),
+                options: vec!["ann".into(), "ben".into()],
+                method: QuestionMethod::Plurality,
+            }],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = HandleMsg::Vote {
+            candidate: "alice".into(),
+            proof: None,
+            answers: Some(vec![Answer {
+                question: "chair".into(),
+                choices: vec!["ann".into()],
+            }]),
+        };
+        let info = mock_info("voter1", &coins(10, "ucoin"));
+        let res = handle(&mut deps, env.clone(), info, msg).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(Uint128(10), held(&deps));
+
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            ballot_fee: Some(fee),
+            referendum: Some(Referendum {
+                title: "Raise the fee".into(),
+                description: "Doubles the fee".into(),
+                electorate: Uint128(10),
+                quorum: Decimal::percent(40),
+                threshold: Decimal::percent(50),
+                veto_threshold: Decimal::percent(33),
+            }),
+            candidates: vec![],
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let msg = HandleMsg::ReferendumVote {
+            choice: ReferendumChoice::Yes,
+        };
+        let info = mock_info("voter1", &coins(10, "ucoin"));
+        let res = handle(&mut deps, env.clone(), info, msg).unwrap();
+        assert!(res.messages.is_empty());
+        assert_eq!(Uint128(10), held(&deps));
+    }
+
+    #[test]
+    fn referendum_quorum_counts_decayed_weight() {
+        let run = |heights: &[u64]| {
+            let mut deps = mock_dependencies(&[]);
+            let msg = InitMsg {
+                referendum: Some(Referendum {
+                    title: "Raise the fee".into(),
+                    description: "Doubles the fee".into(),
+                    electorate: Uint128(10),
+                    quorum: Decimal::percent(40),
+                    threshold: Decimal::percent(50),
+                    veto_threshold: Decimal::percent(33),
+                }),
+                weight_decay: Some(WeightDecay::Linear {
+                    floor: Decimal::percent(50),
+                }),
+                candidates: vec![],
+                ..init_msg()
+            };
+            init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+            let mut env = mock_env();
+            for (i, height) in heights.iter().enumerate() {
+                env.block.height = *height;
+                let msg = HandleMsg::ReferendumVote {
+                    choice: ReferendumChoice::Yes,
+                };
+                handle(
+                    &mut deps,
+                    env.clone(),
+                    mock_info(format!("voter{}", i), &[]),
+                    msg,
+                )
+                .unwrap();
+            }
+            env.block.height = 101;
+            let msg = HandleMsg::Finalize { limit: None };
+            handle(&mut deps, env.clone(), mock_info("anyone", &[]), msg).unwrap();
+            let res = query(&deps, env, QueryMsg::GetReferendum {}).unwrap();
+            let value: ReferendumResponse = from_binary(&res).unwrap();
+            value.status.unwrap()
+        };
+        // four full weights are exactly 40% of the electorate
+        assert_eq!(ReferendumStatus::Passed, run(&[10, 10, 10, 10]));
+        // the last ballot counts for half, which leaves turnout at 35%
+        assert_eq!(ReferendumStatus::QuorumNotMet, run(&[10, 10, 10, 100]));
+    }
+
+    #[test]
+    fn pseudonymous_roster_lists_pseudonyms() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            registration: Some(RegistrationConfig {
+                start: 1,
+                end: 9,
+                bond: None,
+            }),
+            pseudonymous: true,
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 5;
+        for voter in &["carol", "bob", "dave"] {
+            let msg = HandleMsg::RegisterVoter {};
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        let bob = deps.api.canonical_address(&"bob".into()).unwrap();
+        assert!(registered_read(&deps.storage)
+            .may_load(bob.as_slice())
+            .unwrap()
+            .is_none());
+
+        let list = |start_after| {
+            let msg = QueryMsg::ListVoters {
+                start_after,
+                limit: Some(2),
+            };
+            from_binary::<VotersResponse>(&query(&deps, env.clone(), msg).unwrap())
+                .unwrap()
+                .voters
+        };
+        let first = list(None);
+        assert_eq!(2, first.len());
+        assert!(first.iter().all(|voter| voter.as_str().len() == 64));
+        let rest = list(first.last().cloned());
+        assert_eq!(1, rest.len());
+        assert!(!first.contains(&rest[0]));
+
+        env.block.height = 50;
+        let msg = QueryMsg::GetHistory {
+            voter: "bob".into(),
+        };
+        let history: HistoryResponse = from_binary(&query(&deps, env, msg).unwrap()).unwrap();
+        assert!(history.registered);
+    }
+
+    #[test]
+    fn pseudonymous_candidate_votes_page_by_pseudonym() {
+        let mut deps = mock_dependencies(&[]);
+        let msg = InitMsg {
+            pseudonymous: true,
+            ..init_msg()
+        };
+        init(&mut deps, mock_env(), mock_info("creator", &[]), msg).unwrap();
+        let mut env = mock_env();
+        env.block.height = 50;
+        for voter in &["voter1", "voter2", "voter3"] {
+            let msg = HandleMsg::Vote {
+                candidate: "alice".into(),
+                proof: None,
+                answers: None,
+            };
+            handle(&mut deps, env.clone(), mock_info(*voter, &[]), msg).unwrap();
+        }
+        let page = |start_after| {
+            let msg = QueryMsg::GetCandidateVotes {
+                candidate: "alice".into(),
+                start_after,
+                limit: Some(2),
+            };
+            from_binary::<CandidateVotesResponse>(&query(&deps, env.clone(), msg).unwrap())
+                .unwrap()
+                .votes
+        };
+        let first = page(None);
+        assert_eq!(2, first.len());
+        let rest = page(Some(first[1].voter.clone()));
+        assert_eq!(1, rest.len());
+        let state = config_read(&deps.storage).load().unwrap();
+        let mut stored: Vec<HumanAddr> = state
+            .votes
+            .iter()
+            .map(|vote| humanize_voter(&deps.api, &state, &vote.voter).unwrap())
+            .collect();
+        stored.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+        let listed: Vec<HumanAddr> = first.into_iter().chain(rest).map(|v| v.voter).collect();
+        assert_eq!(stored, listed);
+    }
+}
@@ -1,16 +1,581 @@
-use cosmwasm_std::StdError;
+use cosmwasm_std::{HumanAddr, StdError, Uint128};
+
+use crate::state::FundBucket;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ContractError {
-    #[error("{0}")]
+    #[error("[E{}] {0}", self.code())]
     Std(#[from] StdError),
 
-    #[error("Unauthorized")]
+    #[error("[E{}] Unauthorized", self.code())]
     Unauthorized {},
 
-    #[error("Voting time is out of range,shoule be ({begin}, {end})")]
+    #[error("[E{}] Voting time is out of range,shoule be ({begin}, {end})", self.code())]
     NotAllowance { begin: u64, end: u64 },
+
+    #[error("[E{}] The weight oracle is marked compromised", self.code())]
+    OracleCompromised {},
+
+    #[error("[E{}] This election has no weight oracle contract", self.code())]
+    NoOracle {},
+
+    #[error("[E{}] Registry key template must contain {{seat}} when more than one seat is elected", self.code())]
+    InvalidKeyTemplate {},
+
+    #[error("[E{}] Eligibility filter needs between 1 and {max} hashes and at least one byte", self.code())]
+    InvalidEligibilityFilter { max: u32 },
+
+    #[error("[E{}] {voter} is not eligible to vote", self.code())]
+    NotEligible { voter: HumanAddr },
+
+    #[error("[E{}] Merkle proof of {voter} does not match the roll", self.code())]
+    InvalidMerkleProof { voter: HumanAddr },
+
+    #[error("[E{}] Merkle root must be 32 bytes", self.code())]
+    InvalidMerkleRoot {},
+
+    #[error("[E{}] Decay floor must be at most one and half-life positive", self.code())]
+    InvalidWeightDecay {},
+
+    #[error("[E{}] Relay depth must be positive", self.code())]
+    InvalidCandidateRules {},
+
+    #[error("[E{}] Candidate {voter} cannot vote for itself", self.code())]
+    SelfVote { voter: HumanAddr },
+
+    #[error("[E{}] Candidate {relayer} cannot be a relayer", self.code())]
+    CandidateRelayer { relayer: HumanAddr },
+
+    #[error("[E{}] Relayers cannot be chained more than {max} deep", self.code())]
+    RelayDepthExceeded { max: u32 },
+
+    #[error("[E{}] There is no tie waiting for a manual resolution", self.code())]
+    NoPendingTie {},
+
+    #[error("[E{}] Pick {seats} distinct winners among the tied candidates", self.code())]
+    InvalidTieResolution { seats: u32 },
+
+    #[error("[E{}] Majority runoff needs a single seat, plurality voting and a threshold between 0 and 1", self.code())]
+    InvalidMajorityRunoff {},
+
+    #[error("[E{}] Referendum thresholds must be between 0 and 1 and the electorate must not be empty", self.code())]
+    InvalidReferendum {},
+
+    #[error("[E{}] This election is not a referendum", self.code())]
+    NotAReferendum {},
+
+    #[error("[E{}] Referendums take ReferendumVote ballots only", self.code())]
+    ReferendumBallotRequired {},
+
+    #[error("[E{}] Disclosure source and memo are limited to {max} characters", self.code())]
+    DisclosureTooLong { max: usize },
+
+    #[error("[E{}] This election does not accept votes from contracts", self.code())]
+    ContractVotesDisabled {},
+
+    #[error("[E{}] Policy must be between 1 and {max} characters", self.code())]
+    InvalidPolicy { max: usize },
+
+    #[error("[E{}] This election has no runner-up succession", self.code())]
+    SuccessionDisabled {},
+
+    #[error("[E{}] Succession window closed at height {end}", self.code())]
+    SuccessionWindowClosed { end: u64 },
+
+    #[error("[E{}] {candidate} is not a winner", self.code())]
+    NotAWinner { candidate: HumanAddr },
+
+    #[error("[E{}] {candidate} won, contributions are released to the campaign", self.code())]
+    CandidateWon { candidate: HumanAddr },
+
+    #[error("[E{}] Ballots are not weighted by locked tokens", self.code())]
+    LockingDisabled {},
+
+    #[error("[E{}] Send {denom} to lock", self.code())]
+    NothingToLock { denom: String },
+
+    #[error("[E{}] Locking closed at height {end}", self.code())]
+    LockingClosed { end: u64 },
+
+    #[error("[E{}] {voter} has no locked tokens", self.code())]
+    NothingLocked { voter: HumanAddr },
+
+    #[error("[E{}] Contributions closed at height {end}", self.code())]
+    ContributionsClosed { end: u64 },
+
+    #[error("[E{}] Nothing escrowed for {candidate}", self.code())]
+    NoContribution { candidate: HumanAddr },
+
+    #[error("[E{}] No runner-up is left to promote", self.code())]
+    NoRunnerUp {},
+
+    #[error("[E{}] Voting is paused", self.code())]
+    Paused {},
+
+    #[error("[E{}] Contract is in read-only mode", self.code())]
+    ReadOnly {},
+
+    #[error("[E{}] This contract is not configured as an election factory", self.code())]
+    FactoryDisabled {},
+
+    #[error("[E{}] No pending spawn with id {spawn_id}", self.code())]
+    UnknownSpawn { spawn_id: u64 },
+
+    #[error("[E{}] Template name must be between 1 and {max} characters", self.code())]
+    InvalidTemplateName { max: usize },
+
+    #[error("[E{}] No template named {name}", self.code())]
+    UnknownTemplate { name: String },
+
+    #[error("[E{}] Election is halted by governance", self.code())]
+    Halted {},
+
+    #[error("[E{}] Election can only be extended past its current end {end}", self.code())]
+    InvalidExtension { end: u64 },
+
+    #[error("[E{}] Seats must be at least 1", self.code())]
+    InvalidSeats {},
+
+    #[error("[E{}] Voting period must end after it starts (start {start}, end {end})", self.code())]
+    InvalidPeriod { start: u64, end: u64 },
+
+    #[error("[E{}] At least one candidate is required", self.code())]
+    NoCandidates {},
+
+    #[error("[E{}] Candidate {candidate} is listed more than once", self.code())]
+    DuplicateCandidate { candidate: HumanAddr },
+
+    #[error("[E{}] Candidates cannot be nominated in this election", self.code())]
+    NominationDisabled {},
+
+    #[error("[E{}] Nominations closed at height {start}", self.code())]
+    NominationClosed { start: u64 },
+
+    #[error("[E{}] {candidate} is already nominated", self.code())]
+    AlreadyProposed { candidate: HumanAddr },
+
+    #[error("[E{}] No pending nomination of {candidate}", self.code())]
+    UnknownProposal { candidate: HumanAddr },
+
+    #[error("[E{}] Nomination needs between one and the committee size of approvals", self.code())]
+    InvalidNomination {},
+
+    #[error("[E{}] Voting already started at height {start}", self.code())]
+    ElectionStarted { start: u64 },
+
+    #[error("[E{}] Election has not ended yet, it ends at height {end}", self.code())]
+    ElectionNotEnded { end: u64 },
+
+    #[error("[E{}] Election is already finalized", self.code())]
+    AlreadyFinalized {},
+
+    #[error("[E{}] Election is not finalized yet", self.code())]
+    NotFinalized {},
+
+    #[error("[E{}] Results of this election cannot be challenged", self.code())]
+    DisputesDisabled {},
+
+    #[error("[E{}] This election has no veto council", self.code())]
+    VetoDisabled {},
+
+    #[error("[E{}] Veto window closed at height {end}", self.code())]
+    VetoWindowClosed { end: u64 },
+
+    #[error("[E{}] Veto window is open until height {end}", self.code())]
+    VetoWindowOpen { end: u64 },
+
+    #[error("[E{}] {member} already approved the veto", self.code())]
+    AlreadyApproved { member: HumanAddr },
+
+    #[error("[E{}] The result was vetoed", self.code())]
+    Vetoed {},
+
+    #[error("[E{}] Veto threshold must be between one and the number of members", self.code())]
+    InvalidVetoCouncil {},
+
+    #[error("[E{}] Dispute window closed at height {end}", self.code())]
+    DisputeWindowClosed { end: u64 },
+
+    #[error("[E{}] Dispute window is open until height {end}", self.code())]
+    DisputeWindowOpen { end: u64 },
+
+    #[error("[E{}] No open challenge with id {id}", self.code())]
+    UnknownChallenge { id: u64 },
+
+    #[error("[E{}] Election is already archived", self.code())]
+    AlreadyArchived {},
+
+    #[error("[E{}] Participation rewards have not all been claimed", self.code())]
+    RewardsUnclaimed {},
+
+    #[error("[E{}] Expected funds in {expected}, got {denom}", self.code())]
+    WrongDenom { expected: String, denom: String },
+
+    #[error("[E{}] A bond of {amount}{denom} is required", self.code())]
+    InsufficientBond { denom: String, amount: Uint128 },
+
+    #[error("[E{}] Ranked ballots are not accepted by this voting system", self.code())]
+    RankedBallotNotSupported {},
+
+    #[error("[E{}] Ranking must list at least one candidate", self.code())]
+    EmptyRanking {},
+
+    #[error("[E{}] Unknown candidate {candidate}", self.code())]
+    UnknownCandidate { candidate: HumanAddr },
+
+    #[error("[E{}] At most {max} candidates may stand", self.code())]
+    TooManyCandidates { max: u32 },
+
+    #[error("[E{}] The election accepts at most {max} ballots", self.code())]
+    BallotLimitReached { max: u64 },
+
+    #[error("[E{}] At most {max} ballots are accepted per block", self.code())]
+    BlockBallotLimitReached { max: u32 },
+
+    #[error("[E{}] Too frequent, retry at height {retry_at}", self.code())]
+    TooFrequent { retry_at: u64 },
+
+    #[error("[E{}] Candidate {candidate} has withdrawn", self.code())]
+    CandidateWithdrawn { candidate: HumanAddr },
+
+    #[error("[E{}] Candidates can no longer withdraw, voting ended at height {end}", self.code())]
+    WithdrawalClosed { end: u64 },
+
+    #[error("[E{}] Candidate {candidate} is ranked more than once", self.code())]
+    DuplicateRanking { candidate: HumanAddr },
+
+    #[error("[E{}] Split ballots are only accepted by plurality and cumulative elections", self.code())]
+    SplitBallotNotSupported {},
+
+    #[error("[E{}] Ballot allots more than the {budget} points available", self.code())]
+    PointsExceeded { budget: u32 },
+
+    #[error("[E{}] Split ballot must give a positive share to at least one candidate", self.code())]
+    EmptyShares {},
+
+    #[error("[E{}] Candidate {candidate} is listed more than once", self.code())]
+    DuplicateShare { candidate: HumanAddr },
+
+    #[error("[E{}] {voter} holds no token of the gating collection", self.code())]
+    NotTokenHolder { voter: HumanAddr },
+
+    #[error("[E{}] Every token held by {voter} has already been used to vote", self.code())]
+    TokenAlreadyUsed { voter: HumanAddr },
+
+    #[error("[E{}] Candidate {candidate} has more than {max} labels", self.code())]
+    TooManyLabels { candidate: HumanAddr, max: usize },
+
+    #[error("[E{}] Invalid or duplicate label for candidate {candidate} in locale {locale:?}", self.code())]
+    InvalidLabel {
+        candidate: HumanAddr,
+        locale: String,
+    },
+
+    #[error("[E{}] Batch holds more than {max} ballots", self.code())]
+    BatchTooLarge { max: usize },
+
+    #[error("[E{}] Cohort {name:?} is duplicated or its window is outside the election", self.code())]
+    InvalidCohort { name: String },
+
+    #[error("[E{}] {voter} is assigned to more than one cohort", self.code())]
+    DuplicateCohortMember { voter: HumanAddr },
+
+    #[error("[E{}] Idempotency key must be 1 to {max} bytes", self.code())]
+    InvalidIdempotencyKey { max: usize },
+
+    #[error("[E{}] Only single choice ballots are accepted in a runoff", self.code())]
+    RunoffBallotNotSupported {},
+
+    #[error("[E{}] {voter} did not vote in the first round and cannot vote in the runoff", self.code())]
+    NotEligibleForRunoff { voter: HumanAddr },
+
+    #[error("[E{}] Registration is open between heights {begin} and {end}", self.code())]
+    RegistrationClosed { begin: u64, end: u64 },
+
+    #[error("[E{}] {voter} is already registered", self.code())]
+    AlreadyRegistered { voter: HumanAddr },
+
+    #[error("[E{}] {voter} is not registered to vote", self.code())]
+    NotRegistered { voter: HumanAddr },
+
+    #[error("[E{}] This election has no participation reward", self.code())]
+    NoParticipationReward {},
+
+    #[error("[E{}] {voter} did not vote and has no reward to claim", self.code())]
+    NoRewardShare { voter: HumanAddr },
+
+    #[error("[E{}] {voter} already claimed the participation reward", self.code())]
+    RewardAlreadyClaimed { voter: HumanAddr },
+
+    #[error("[E{}] {voter} has already voted", self.code())]
+    AlreadyVoted { voter: HumanAddr },
+
+    #[error("[E{}] {voter} is not a member of the voting group", self.code())]
+    NotGroupMember { voter: HumanAddr },
+
+    #[error("[E{}] {voter} has no voting power", self.code())]
+    NoVotingPower { voter: HumanAddr },
+
+    #[error("[E{}] Bucket {bucket:?} holds {available}{denom}, {required}{denom} required", self.code())]
+    InsufficientBucketFunds {
+        bucket: FundBucket,
+        denom: String,
+        available: Uint128,
+        required: Uint128,
+    },
+
+    #[error("[E{}] Ledger postings for {denom} do not balance", self.code())]
+    LedgerImbalance { denom: String },
+
+    #[error("[E{}] Ledger records {recorded}{denom} but the contract holds {balance}{denom}", self.code())]
+    LedgerExceedsBalance {
+        denom: String,
+        recorded: Uint128,
+        balance: Uint128,
+    },
+
+    #[error("[E{}] Fewer ballots than the minimum were cast, the election is invalid", self.code())]
+    ElectionInvalid {},
+
+    #[error("[E{}] Slates need unique names and registered candidates in a single slate, in a plurality election without majority runoff", self.code())]
+    InvalidPartyList {},
+
+    #[error("[E{}] The election has no party list", self.code())]
+    PartyListDisabled {},
+
+    #[error("[E{}] Unknown slate {name}", self.code())]
+    UnknownSlate { name: String },
+
+    #[error("[E{}] Metadata needs a title and at most {max_tags} tags, each field within its length limit", self.code())]
+    InvalidMetadata { max_tags: usize },
+
+    #[error("[E{}] The voter roll can be frozen from height {start}", self.code())]
+    SnapshotTooEarly { start: u64 },
+
+    #[error("[E{}] The voter roll was frozen at height {height}", self.code())]
+    SnapshotTaken { height: u64 },
+
+    #[error("[E{}] A voter cannot be its own proxy", self.code())]
+    InvalidProxy {},
+
+    #[error("[E{}] Sender is not the proxy of {principal}", self.code())]
+    NotProxy { principal: HumanAddr },
+
+    #[error("[E{}] Proxy grant expires at height {expires}", self.code())]
+    ProxyExpired { expires: u64 },
+
+    #[error("[E{}] {candidate} is banned from standing", self.code())]
+    BannedCandidate { candidate: HumanAddr },
+
+    #[error("[E{}] The owner {candidate} cannot stand", self.code())]
+    OwnerCandidate { candidate: HumanAddr },
+
+    #[error("[E{}] Ballot fee must not be zero", self.code())]
+    InvalidBallotFee {},
+
+    #[error("[E{}] A fee of {amount}{denom} is required per ballot", self.code())]
+    InsufficientBallotFee { denom: String, amount: Uint128 },
+
+    #[error("[E{}] {voter} has no ballot fee to claim", self.code())]
+    NoBallotFee { voter: HumanAddr },
+
+    #[error("[E{}] No registry is configured", self.code())]
+    RegistryDisabled {},
+
+    #[error("[E{}] Invalid anti-sniping rule", self.code())]
+    InvalidAntiSnipe {},
+
+    #[error("[E{}] {voter} has no record in the earlier election", self.code())]
+    NoHistory { voter: HumanAddr },
+
+    #[error("[E{}] Questions need unique names and at least two unique options", self.code())]
+    InvalidQuestions {},
+
+    #[error("[E{}] This election asks no questions", self.code())]
+    NoQuestions {},
+
+    #[error("[E{}] Invalid answer to {question}", self.code())]
+    InvalidAnswer { question: String },
+
+    #[error("[E{}] The election is not archived", self.code())]
+    NotArchived {},
+
+    #[error("[E{}] The result can still be vetoed or disputed", self.code())]
+    ResultNotFinal {},
+
+    #[error("[E{}] The ballot answers no question", self.code())]
+    NoAnswers {},
+
+    #[error("[E{}] Reward claims closed at height {end}", self.code())]
+    ClaimWindowClosed { end: u64 },
+
+    #[error("[E{}] A runner-up already replaced a winner of this result", self.code())]
+    SuccessionApplied {},
+
+    #[error("[E{}] The prize was already released", self.code())]
+    PrizeReleased {},
+
+    #[error("[E{}] The result was overturned by a challenge", self.code())]
+    ResultOverturned {},
     // Add any other custom errors you like here.
     // Look at https://docs.rs/thiserror/1.0.21/thiserror/ for details.
 }
+
+impl ContractError {
+    /// code identifies the error for clients, which find it in the `[E<code>]`
+    /// prefix of the message. New errors take the next free code, codes are
+    /// never renumbered or reused.
+    pub fn code(&self) -> u32 {
+        match self {
+            ContractError::Std(_) => 1,
+            ContractError::Unauthorized { .. } => 2,
+            ContractError::NotAllowance { .. } => 3,
+            ContractError::OracleCompromised { .. } => 4,
+            ContractError::NoOracle { .. } => 5,
+            ContractError::InvalidKeyTemplate { .. } => 6,
+            ContractError::InvalidEligibilityFilter { .. } => 7,
+            ContractError::NotEligible { .. } => 8,
+            ContractError::InvalidMerkleProof { .. } => 9,
+            ContractError::InvalidMerkleRoot { .. } => 10,
+            ContractError::InvalidWeightDecay { .. } => 11,
+            ContractError::InvalidCandidateRules { .. } => 12,
+            ContractError::SelfVote { .. } => 13,
+            ContractError::CandidateRelayer { .. } => 14,
+            ContractError::RelayDepthExceeded { .. } => 15,
+            ContractError::NoPendingTie { .. } => 16,
+            ContractError::InvalidTieResolution { .. } => 17,
+            ContractError::InvalidMajorityRunoff { .. } => 18,
+            ContractError::InvalidReferendum { .. } => 19,
+            ContractError::NotAReferendum { .. } => 20,
+            ContractError::ReferendumBallotRequired { .. } => 21,
+            ContractError::DisclosureTooLong { .. } => 22,
+            ContractError::ContractVotesDisabled { .. } => 23,
+            ContractError::InvalidPolicy { .. } => 24,
+            ContractError::SuccessionDisabled { .. } => 25,
+            ContractError::SuccessionWindowClosed { .. } => 26,
+            ContractError::NotAWinner { .. } => 27,
+            ContractError::CandidateWon { .. } => 28,
+            ContractError::LockingDisabled { .. } => 29,
+            ContractError::NothingToLock { .. } => 30,
+            ContractError::LockingClosed { .. } => 31,
+            ContractError::NothingLocked { .. } => 32,
+            ContractError::ContributionsClosed { .. } => 33,
+            ContractError::NoContribution { .. } => 34,
+            ContractError::NoRunnerUp { .. } => 35,
+            ContractError::Paused { .. } => 36,
+            ContractError::ReadOnly { .. } => 37,
+            ContractError::FactoryDisabled { .. } => 38,
+            ContractError::UnknownSpawn { .. } => 39,
+            ContractError::InvalidTemplateName { .. } => 40,
+            ContractError::UnknownTemplate { .. } => 41,
+            ContractError::Halted { .. } => 42,
+            ContractError::InvalidExtension { .. } => 43,
+            ContractError::InvalidSeats { .. } => 44,
+            ContractError::InvalidPeriod { .. } => 45,
+            ContractError::NoCandidates { .. } => 46,
+            ContractError::DuplicateCandidate { .. } => 47,
+            ContractError::NominationDisabled { .. } => 48,
+            ContractError::NominationClosed { .. } => 49,
+            ContractError::AlreadyProposed { .. } => 50,
+            ContractError::UnknownProposal { .. } => 51,
+            ContractError::InvalidNomination { .. } => 52,
+            ContractError::ElectionStarted { .. } => 53,
+            ContractError::ElectionNotEnded { .. } => 54,
+            ContractError::AlreadyFinalized { .. } => 55,
+            ContractError::NotFinalized { .. } => 56,
+            ContractError::DisputesDisabled { .. } => 57,
+            ContractError::VetoDisabled { .. } => 58,
+            ContractError::VetoWindowClosed { .. } => 59,
+            ContractError::VetoWindowOpen { .. } => 60,
+            ContractError::AlreadyApproved { .. } => 61,
+            ContractError::Vetoed { .. } => 62,
+            ContractError::InvalidVetoCouncil { .. } => 63,
+            ContractError::DisputeWindowClosed { .. } => 64,
+            ContractError::DisputeWindowOpen { .. } => 65,
+            ContractError::UnknownChallenge { .. } => 66,
+            ContractError::AlreadyArchived { .. } => 67,
+            ContractError::RewardsUnclaimed { .. } => 68,
+            ContractError::WrongDenom { .. } => 69,
+            ContractError::InsufficientBond { .. } => 70,
+            ContractError::RankedBallotNotSupported { .. } => 71,
+            ContractError::EmptyRanking { .. } => 72,
+            ContractError::UnknownCandidate { .. } => 73,
+            ContractError::TooManyCandidates { .. } => 74,
+            ContractError::BallotLimitReached { .. } => 75,
+            ContractError::BlockBallotLimitReached { .. } => 76,
+            ContractError::TooFrequent { .. } => 77,
+            ContractError::CandidateWithdrawn { .. } => 78,
+            ContractError::WithdrawalClosed { .. } => 79,
+            ContractError::DuplicateRanking { .. } => 80,
+            ContractError::SplitBallotNotSupported { .. } => 81,
+            ContractError::PointsExceeded { .. } => 82,
+            ContractError::EmptyShares { .. } => 83,
+            ContractError::DuplicateShare { .. } => 84,
+            ContractError::NotTokenHolder { .. } => 85,
+            ContractError::TokenAlreadyUsed { .. } => 86,
+            ContractError::TooManyLabels { .. } => 87,
+            ContractError::InvalidLabel { .. } => 88,
+            ContractError::BatchTooLarge { .. } => 89,
+            ContractError::InvalidCohort { .. } => 90,
+            ContractError::DuplicateCohortMember { .. } => 91,
+            ContractError::InvalidIdempotencyKey { .. } => 95,
+            ContractError::RunoffBallotNotSupported { .. } => 96,
+            ContractError::NotEligibleForRunoff { .. } => 97,
+            ContractError::RegistrationClosed { .. } => 98,
+            ContractError::AlreadyRegistered { .. } => 99,
+            ContractError::NotRegistered { .. } => 100,
+            ContractError::NoParticipationReward { .. } => 101,
+            ContractError::NoRewardShare { .. } => 102,
+            ContractError::RewardAlreadyClaimed { .. } => 103,
+            ContractError::AlreadyVoted { .. } => 104,
+            ContractError::NotGroupMember { .. } => 105,
+            ContractError::NoVotingPower { .. } => 106,
+            ContractError::InsufficientBucketFunds { .. } => 107,
+            ContractError::LedgerImbalance { .. } => 108,
+            ContractError::LedgerExceedsBalance { .. } => 109,
+            ContractError::ElectionInvalid { .. } => 110,
+            ContractError::InvalidPartyList { .. } => 111,
+            ContractError::PartyListDisabled { .. } => 112,
+            ContractError::UnknownSlate { .. } => 113,
+            ContractError::InvalidMetadata { .. } => 114,
+            ContractError::SnapshotTooEarly { .. } => 115,
+            ContractError::SnapshotTaken { .. } => 116,
+            ContractError::InvalidProxy { .. } => 117,
+            ContractError::NotProxy { .. } => 118,
+            ContractError::ProxyExpired { .. } => 119,
+            ContractError::BannedCandidate { .. } => 120,
+            ContractError::OwnerCandidate { .. } => 121,
+            ContractError::InvalidBallotFee { .. } => 122,
+            ContractError::InsufficientBallotFee { .. } => 123,
+            ContractError::NoBallotFee { .. } => 124,
+            ContractError::RegistryDisabled { .. } => 125,
+            ContractError::InvalidAntiSnipe { .. } => 126,
+            ContractError::NoHistory { .. } => 127,
+            ContractError::InvalidQuestions { .. } => 128,
+            ContractError::NoQuestions { .. } => 129,
+            ContractError::InvalidAnswer { .. } => 130,
+            ContractError::NotArchived { .. } => 131,
+            ContractError::ResultNotFinal { .. } => 132,
+            ContractError::NoAnswers { .. } => 133,
+            ContractError::ClaimWindowClosed { .. } => 134,
+            ContractError::SuccessionApplied { .. } => 135,
+            ContractError::PrizeReleased { .. } => 136,
+            ContractError::ResultOverturned { .. } => 137,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn messages_start_with_code() {
+        let err = ContractError::NotAllowance { begin: 1, end: 2 };
+        assert_eq!(3, err.code());
+        assert!(err.to_string().starts_with("[E3] "));
+        let err = ContractError::from(StdError::generic_err("boom"));
+        assert_eq!("[E1] Generic error: boom", err.to_string());
+    }
+}
@@ -0,0 +1,581 @@
+use serde::de::DeserializeOwned;
+
+use cosmwasm_std::{
+    to_binary, Coin, CosmosMsg, Empty, HumanAddr, Querier, QueryRequest, StdResult, WasmMsg,
+    WasmQuery,
+};
+
+use crate::msg::{
+    Answer, AuditLogResponse, Ballot, BallotEntry, BallotResponse, CanVoteResponse,
+    CandidateCountResponse, CandidateProposalsResponse, CandidateVotesResponse, ConfigResponse,
+    ContributionsResponse, DisclosuresResponse, ElectionStatus, ElectionsResponse,
+    EligibilityFilter, ExtensionsResponse, HandleMsg, HistoryResponse, InitMsg,
+    LeaderboardResponse, LedgerResponse, LegacyVoteResponse, LockedBalanceResponse, MerkleProof,
+    MultiResponse, ParticipationResponse, PhaseResponse, ProxyResponse, QueryMsg, ReferendumChoice,
+    ReferendumResponse, ResultResponse, Role, Share, SimulatedTallyResponse, SnapshotResponse,
+    SpawnedElectionsResponse, StaleElectionsResponse, StatsResponse, TallyRoundsResponse,
+    TemplateResponse, VoteResponse, VoteSort, VoterRollResponse, VoterSetDiffResponse,
+    VotersResponse, VotingSystem,
+};
+use crate::state::{ArchivedResult, TallyProof};
+
+/// ElectionContract is a wrapper around the address of an election contract
+/// that builds its messages and queries, so other contracts and tests do not
+/// have to write the JSON by hand.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElectionContract(pub HumanAddr);
+
+impl ElectionContract {
+    pub fn addr(&self) -> HumanAddr {
+        self.0.clone()
+    }
+
+    /// call turns `msg` into an executable message sending `send` along
+    pub fn call(&self, msg: HandleMsg, send: Vec<Coin>) -> StdResult<CosmosMsg> {
+        Ok(WasmMsg::Execute {
+            contract_addr: self.addr(),
+            msg: to_binary(&msg)?,
+            send,
+        }
+        .into())
+    }
+
+    /// query_request turns `msg` into a smart query against this contract
+    pub fn query_request(&self, msg: &QueryMsg) -> StdResult<QueryRequest<Empty>> {
+        Ok(WasmQuery::Smart {
+            contract_addr: self.addr(),
+            msg: to_binary(msg)?,
+        }
+        .into())
+    }
+
+    pub fn query<Q: Querier, T: DeserializeOwned>(
+        &self,
+        querier: &Q,
+        msg: &QueryMsg,
+    ) -> StdResult<T> {
+        querier.query(&self.query_request(msg)?)
+    }
+
+    pub fn vote(&self, candidate: HumanAddr) -> StdResult<CosmosMsg> {
+        self.vote_with_proof(candidate, None)
+    }
+
+    pub fn vote_with_proof(
+        &self,
+        candidate: HumanAddr,
+        proof: Option<MerkleProof>,
+    ) -> StdResult<CosmosMsg> {
+        let msg = HandleMsg::Vote {
+            candidate,
+            proof,
+            answers: None,
+        };
+        self.call(msg, vec![])
+    }
+
+    pub fn slate_vote(&self, name: String) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::SlateVote { name }, vec![])
+    }
+
+    pub fn freeze_roll(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::Snapshot {}, vec![])
+    }
+
+    pub fn ranked_vote(&self, ranking: Vec<HumanAddr>) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::RankedVote { ranking }, vec![])
+    }
+
+    pub fn split_vote(&self, shares: Vec<Share>) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::SplitVote { shares }, vec![])
+    }
+
+    pub fn batch_vote(&self, votes: Vec<BallotEntry>) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::BatchVote { votes }, vec![])
+    }
+
+    pub fn withdraw_candidacy(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::WithdrawCandidacy {}, vec![])
+    }
+
+    pub fn register_voter(&self, bond: Vec<Coin>) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::RegisterVoter {}, bond)
+    }
+
+    pub fn claim_reward(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ClaimReward {}, vec![])
+    }
+
+    pub fn publish_result(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::PublishResult {}, vec![])
+    }
+
+    pub fn scrub(&self, limit: Option<u32>) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::Scrub { limit }, vec![])
+    }
+
+    pub fn archive(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::Archive {}, vec![])
+    }
+
+    pub fn withdraw_bond(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::WithdrawBond {}, vec![])
+    }
+
+    pub fn claim_ballot_fee(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ClaimBallotFee {}, vec![])
+    }
+
+    pub fn set_relayer(&self, relayer: Option<HumanAddr>) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::SetRelayer { relayer }, vec![])
+    }
+
+    pub fn vote_as_contract(
+        &self,
+        on_behalf_of_policy: String,
+        ballot: Ballot,
+    ) -> StdResult<CosmosMsg> {
+        let msg = HandleMsg::VoteAsContract {
+            on_behalf_of_policy,
+            ballot,
+        };
+        self.call(msg, vec![])
+    }
+
+    pub fn authorize_proxy(&self, proxy: HumanAddr, expires: u64) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::AuthorizeProxy { proxy, expires }, vec![])
+    }
+
+    pub fn revoke_proxy(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::RevokeProxy {}, vec![])
+    }
+
+    pub fn proxy_vote(&self, principal: HumanAddr, ballot: Ballot) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ProxyVote { principal, ballot }, vec![])
+    }
+
+    pub fn finalize(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::Finalize { limit: None }, vec![])
+    }
+
+    pub fn promote_runner_up(&self, vacated: HumanAddr) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::PromoteRunnerUp { vacated }, vec![])
+    }
+
+    pub fn referendum_vote(&self, choice: ReferendumChoice) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ReferendumVote { choice }, vec![])
+    }
+
+    /// vote_with_answers votes for `candidate` and answers the election's questions
+    pub fn vote_with_answers(
+        &self,
+        candidate: HumanAddr,
+        answers: Vec<Answer>,
+    ) -> StdResult<CosmosMsg> {
+        let msg = HandleMsg::Vote {
+            candidate,
+            proof: None,
+            answers: Some(answers),
+        };
+        self.call(msg, vec![])
+    }
+
+    pub fn resolve_tie(&self, winners: Vec<HumanAddr>) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ResolveTie { winners }, vec![])
+    }
+
+    pub fn challenge_result(&self, bond: Vec<Coin>) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ChallengeResult {}, bond)
+    }
+
+    pub fn challenge(&self, reason: String, bond: Vec<Coin>) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::Challenge { reason }, bond)
+    }
+
+    pub fn resolve_challenge(&self, id: u64, upheld: bool) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ResolveChallenge { id, upheld }, vec![])
+    }
+
+    pub fn approve_veto(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ApproveVeto {}, vec![])
+    }
+
+    pub fn release_prize(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ReleasePrize {}, vec![])
+    }
+
+    pub fn spawn_election(&self, label: String, params: InitMsg) -> StdResult<CosmosMsg> {
+        let msg = HandleMsg::SpawnElection {
+            label,
+            params: Box::new(params),
+        };
+        self.call(msg, vec![])
+    }
+
+    pub fn mark_oracle_compromised(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::MarkOracleCompromised {}, vec![])
+    }
+
+    pub fn replace_oracle(&self, address: HumanAddr) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ReplaceOracle { address }, vec![])
+    }
+
+    pub fn set_eligibility_filter(
+        &self,
+        filter: Option<EligibilityFilter>,
+    ) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::SetEligibilityFilter { filter }, vec![])
+    }
+
+    pub fn record_disclosure(
+        &self,
+        candidate: HumanAddr,
+        source: String,
+        amount: Coin,
+        memo: String,
+    ) -> StdResult<CosmosMsg> {
+        let msg = HandleMsg::RecordDisclosure {
+            candidate,
+            source,
+            amount,
+            memo,
+        };
+        self.call(msg, vec![])
+    }
+
+    pub fn update_period(&self, start: u64, end: u64) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::UpdatePeriod { start, end }, vec![])
+    }
+
+    pub fn propose_new_owner(&self, address: HumanAddr) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ProposeNewOwner { address }, vec![])
+    }
+
+    pub fn accept_ownership(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::AcceptOwnership {}, vec![])
+    }
+
+    pub fn contribute(&self, candidate: HumanAddr, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::Contribute { candidate }, funds)
+    }
+
+    pub fn release_contributions(&self, candidate: HumanAddr) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ReleaseContributions { candidate }, vec![])
+    }
+
+    pub fn refund_contribution(&self, candidate: HumanAddr) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::RefundContribution { candidate }, vec![])
+    }
+
+    pub fn propose_candidate(&self, candidate: HumanAddr) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ProposeCandidate { candidate }, vec![])
+    }
+
+    pub fn approve_candidate(&self, candidate: HumanAddr) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::ApproveCandidate { candidate }, vec![])
+    }
+
+    pub fn grant_role(&self, address: HumanAddr, role: Role) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::GrantRole { address, role }, vec![])
+    }
+
+    pub fn revoke_role(&self, address: HumanAddr, role: Role) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::RevokeRole { address, role }, vec![])
+    }
+
+    pub fn pause(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::Pause {}, vec![])
+    }
+
+    pub fn unpause(&self) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::Unpause {}, vec![])
+    }
+
+    pub fn set_read_only(&self, read_only: bool) -> StdResult<CosmosMsg> {
+        self.call(HandleMsg::SetReadOnly { read_only }, vec![])
+    }
+
+    pub fn vote_info<Q: Querier>(
+        &self,
+        querier: &Q,
+        locale: Option<String>,
+        sort: Option<VoteSort>,
+    ) -> StdResult<VoteResponse> {
+        self.query(querier, &QueryMsg::GetVoteInfo { locale, sort })
+    }
+
+    pub fn vote_info_legacy<Q: Querier>(
+        &self,
+        querier: &Q,
+        locale: Option<String>,
+        sort: Option<VoteSort>,
+    ) -> StdResult<LegacyVoteResponse> {
+        self.query(querier, &QueryMsg::GetVoteInfoLegacy { locale, sort })
+    }
+
+    pub fn result<Q: Querier>(
+        &self,
+        querier: &Q,
+        locale: Option<String>,
+    ) -> StdResult<ResultResponse> {
+        self.query(querier, &QueryMsg::GetResult { locale })
+    }
+
+    pub fn tally_rounds<Q: Querier>(
+        &self,
+        querier: &Q,
+        locale: Option<String>,
+    ) -> StdResult<TallyRoundsResponse> {
+        self.query(querier, &QueryMsg::GetTallyRounds { locale })
+    }
+
+    pub fn stale_elections<Q: Querier>(&self, querier: &Q) -> StdResult<StaleElectionsResponse> {
+        self.query(querier, &QueryMsg::ListStaleElections {})
+    }
+
+    pub fn ledger<Q: Querier>(&self, querier: &Q) -> StdResult<LedgerResponse> {
+        self.query(querier, &QueryMsg::GetLedger {})
+    }
+
+    pub fn ballot<Q: Querier>(&self, querier: &Q, voter: HumanAddr) -> StdResult<BallotResponse> {
+        self.query(querier, &QueryMsg::GetBallot { voter })
+    }
+
+    pub fn participation<Q: Querier>(&self, querier: &Q) -> StdResult<ParticipationResponse> {
+        self.query(querier, &QueryMsg::GetParticipation {})
+    }
+
+    pub fn history<Q: Querier>(&self, querier: &Q, voter: HumanAddr) -> StdResult<HistoryResponse> {
+        self.query(querier, &QueryMsg::GetHistory { voter })
+    }
+
+    pub fn voter_set_diff<Q: Querier>(
+        &self,
+        querier: &Q,
+        from: HumanAddr,
+        to: HumanAddr,
+    ) -> StdResult<VoterSetDiffResponse> {
+        self.query(querier, &QueryMsg::VoterSetDiff { from, to })
+    }
+
+    pub fn template<Q: Querier>(&self, querier: &Q, name: String) -> StdResult<TemplateResponse> {
+        self.query(querier, &QueryMsg::GetTemplate { name })
+    }
+
+    pub fn phase<Q: Querier>(&self, querier: &Q) -> StdResult<PhaseResponse> {
+        self.query(querier, &QueryMsg::GetPhase {})
+    }
+
+    pub fn spawned_elections<Q: Querier>(
+        &self,
+        querier: &Q,
+    ) -> StdResult<SpawnedElectionsResponse> {
+        self.query(querier, &QueryMsg::ListSpawnedElections {})
+    }
+
+    pub fn elections<Q: Querier>(
+        &self,
+        querier: &Q,
+        status_filter: Option<ElectionStatus>,
+        tag_filter: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<ElectionsResponse> {
+        let msg = QueryMsg::ListElections {
+            status_filter,
+            tag_filter,
+            start_after,
+            limit,
+        };
+        self.query(querier, &msg)
+    }
+
+    pub fn voter_roll<Q: Querier>(&self, querier: &Q) -> StdResult<VoterRollResponse> {
+        self.query(querier, &QueryMsg::GetVoterRoll {})
+    }
+
+    pub fn audit_log<Q: Querier>(
+        &self,
+        querier: &Q,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<AuditLogResponse> {
+        self.query(querier, &QueryMsg::GetAuditLog { start_after, limit })
+    }
+
+    pub fn proxy<Q: Querier>(&self, querier: &Q, principal: HumanAddr) -> StdResult<ProxyResponse> {
+        self.query(querier, &QueryMsg::GetProxy { principal })
+    }
+
+    pub fn voters<Q: Querier>(
+        &self,
+        querier: &Q,
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    ) -> StdResult<VotersResponse> {
+        self.query(querier, &QueryMsg::ListVoters { start_after, limit })
+    }
+
+    pub fn stats<Q: Querier>(&self, querier: &Q) -> StdResult<StatsResponse> {
+        self.query(querier, &QueryMsg::GetStats {})
+    }
+
+    pub fn snapshot<Q: Querier>(
+        &self,
+        querier: &Q,
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    ) -> StdResult<SnapshotResponse> {
+        self.query(querier, &QueryMsg::ExportSnapshot { start_after, limit })
+    }
+
+    pub fn disclosures<Q: Querier>(
+        &self,
+        querier: &Q,
+        candidate: HumanAddr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<DisclosuresResponse> {
+        let msg = QueryMsg::ListDisclosures {
+            candidate,
+            start_after,
+            limit,
+        };
+        self.query(querier, &msg)
+    }
+
+    pub fn can_vote<Q: Querier>(
+        &self,
+        querier: &Q,
+        voter: HumanAddr,
+        candidate: HumanAddr,
+    ) -> StdResult<CanVoteResponse> {
+        self.query(querier, &QueryMsg::CanVote { voter, candidate })
+    }
+
+    pub fn candidate_count<Q: Querier>(
+        &self,
+        querier: &Q,
+        candidate: HumanAddr,
+    ) -> StdResult<CandidateCountResponse> {
+        self.query(querier, &QueryMsg::GetCandidateCount { candidate })
+    }
+
+    pub fn extensions<Q: Querier>(&self, querier: &Q) -> StdResult<ExtensionsResponse> {
+        self.query(querier, &QueryMsg::GetExtensions {})
+    }
+
+    pub fn leaderboard<Q: Querier>(
+        &self,
+        querier: &Q,
+        limit: Option<u32>,
+    ) -> StdResult<LeaderboardResponse> {
+        self.query(querier, &QueryMsg::GetLeaderboard { limit })
+    }
+
+    pub fn candidate_votes<Q: Querier>(
+        &self,
+        querier: &Q,
+        candidate: HumanAddr,
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    ) -> StdResult<CandidateVotesResponse> {
+        let msg = QueryMsg::GetCandidateVotes {
+            candidate,
+            start_after,
+            limit,
+        };
+        self.query(querier, &msg)
+    }
+
+    pub fn archived_result<Q: Querier>(
+        &self,
+        querier: &Q,
+        election_id: u64,
+    ) -> StdResult<ArchivedResult> {
+        self.query(querier, &QueryMsg::GetArchivedResult { election_id })
+    }
+
+    pub fn candidate_proposals<Q: Querier>(
+        &self,
+        querier: &Q,
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    ) -> StdResult<CandidateProposalsResponse> {
+        self.query(
+            querier,
+            &QueryMsg::ListCandidateProposals { start_after, limit },
+        )
+    }
+
+    pub fn contributions<Q: Querier>(
+        &self,
+        querier: &Q,
+        candidate: HumanAddr,
+    ) -> StdResult<ContributionsResponse> {
+        self.query(querier, &QueryMsg::GetContributions { candidate })
+    }
+
+    pub fn locked_balance<Q: Querier>(
+        &self,
+        querier: &Q,
+        voter: HumanAddr,
+    ) -> StdResult<LockedBalanceResponse> {
+        self.query(querier, &QueryMsg::GetLockedBalance { voter })
+    }
+
+    pub fn tally_proof<Q: Querier>(&self, querier: &Q) -> StdResult<TallyProof> {
+        self.query(querier, &QueryMsg::GetTallyProof {})
+    }
+
+    pub fn simulate_tally<Q: Querier>(
+        &self,
+        querier: &Q,
+        method: Option<VotingSystem>,
+    ) -> StdResult<SimulatedTallyResponse> {
+        self.query(querier, &QueryMsg::SimulateTally { method })
+    }
+
+    pub fn referendum<Q: Querier>(&self, querier: &Q) -> StdResult<ReferendumResponse> {
+        self.query(querier, &QueryMsg::GetReferendum {})
+    }
+
+    pub fn config<Q: Querier>(&self, querier: &Q) -> StdResult<ConfigResponse> {
+        self.query(querier, &QueryMsg::GetConfig {})
+    }
+
+    pub fn multi<Q: Querier>(
+        &self,
+        querier: &Q,
+        queries: Vec<QueryMsg>,
+    ) -> StdResult<MultiResponse> {
+        self.query(querier, &QueryMsg::Multi { queries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::coins;
+
+    #[test]
+    fn builds_execute_and_query() {
+        let election = ElectionContract("election".into());
+        let msg = election.challenge_result(coins(5, "earth")).unwrap();
+        let expected: CosmosMsg = WasmMsg::Execute {
+            contract_addr: "election".into(),
+            msg: br#"{"challenge_result":{}}"#.to_vec().into(),
+            send: coins(5, "earth"),
+        }
+        .into();
+        assert_eq!(expected, msg);
+
+        let request = election.query_request(&QueryMsg::GetLedger {}).unwrap();
+        let expected: QueryRequest<Empty> = WasmQuery::Smart {
+            contract_addr: "election".into(),
+            msg: br#"{"get_ledger":{}}"#.to_vec().into(),
+        }
+        .into();
+        assert_eq!(expected, request);
+    }
+}
@@ -0,0 +1,196 @@
+use cosmwasm_std::{Coin, Uint128};
+
+use crate::error::ContractError;
+use crate::state::{BucketBalance, FundBucket, Ledger};
+
+impl Ledger {
+    /// balance returns the amount of `denom` reserved in `bucket`
+    pub fn balance(&self, bucket: FundBucket, denom: &str) -> Uint128 {
+        self.balances
+            .iter()
+            .find(|b| b.bucket == bucket && b.denom == denom)
+            .map(|b| b.amount)
+            .unwrap_or_else(Uint128::zero)
+    }
+
+    /// bucket returns every coin reserved in `bucket`
+    pub fn bucket(&self, bucket: FundBucket) -> Vec<Coin> {
+        self.balances
+            .iter()
+            .filter(|b| b.bucket == bucket && !b.amount.is_zero())
+            .map(|b| Coin {
+                denom: b.denom.clone(),
+                amount: b.amount,
+            })
+            .collect()
+    }
+
+    /// total returns the amount of `denom` held across all buckets
+    pub fn total(&self, denom: &str) -> Uint128 {
+        self.balances
+            .iter()
+            .filter(|b| b.denom == denom)
+            .map(|b| b.amount)
+            .sum()
+    }
+
+    /// deposit books coins received from outside the contract into `bucket`
+    pub fn deposit(&mut self, bucket: FundBucket, funds: &[Coin]) {
+        for coin in funds.iter().filter(|c| !c.amount.is_zero()) {
+            add_coin(&mut self.received, coin);
+            self.credit(bucket, coin);
+        }
+    }
+
+    /// withdraw books coins leaving the contract from `bucket`
+    pub fn withdraw(&mut self, bucket: FundBucket, funds: &[Coin]) -> Result<(), ContractError> {
+        for coin in funds.iter().filter(|c| !c.amount.is_zero()) {
+            self.debit(bucket, coin)?;
+            add_coin(&mut self.paid_out, coin);
+        }
+        Ok(())
+    }
+
+    /// transfer moves coins between two buckets without touching the bank balance
+    pub fn transfer(
+        &mut self,
+        from: FundBucket,
+        to: FundBucket,
+        funds: &[Coin],
+    ) -> Result<(), ContractError> {
+        for coin in funds.iter().filter(|c| !c.amount.is_zero()) {
+            self.debit(from, coin)?;
+            self.credit(to, coin);
+        }
+        Ok(())
+    }
+
+    /// check verifies that the books balance and that the contract actually
+    /// holds at least what the buckets claim, given its current bank balance
+    pub fn check(&self, bank: &[Coin]) -> Result<(), ContractError> {
+        for received in &self.received {
+            let paid_out = amount_of(&self.paid_out, &received.denom);
+            let recorded = self.total(&received.denom);
+            if received.amount.u128() != paid_out.u128() + recorded.u128() {
+                return Err(ContractError::LedgerImbalance {
+                    denom: received.denom.clone(),
+                });
+            }
+            let held = amount_of(bank, &received.denom);
+            if recorded > held {
+                return Err(ContractError::LedgerExceedsBalance {
+                    denom: received.denom.clone(),
+                    recorded,
+                    balance: held,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    fn credit(&mut self, bucket: FundBucket, coin: &Coin) {
+        match self
+            .balances
+            .iter_mut()
+            .find(|b| b.bucket == bucket && b.denom == coin.denom)
+        {
+            Some(entry) => entry.amount += coin.amount,
+            None => {
+                self.balances.push(BucketBalance {
+                    bucket,
+                    denom: coin.denom.clone(),
+                    amount: coin.amount,
+                });
+                self.balances
+                    .sort_by(|a, b| a.bucket.cmp(&b.bucket).then(a.denom.cmp(&b.denom)));
+            }
+        }
+    }
+
+    fn debit(&mut self, bucket: FundBucket, coin: &Coin) -> Result<(), ContractError> {
+        let available = self.balance(bucket, &coin.denom);
+        if available < coin.amount {
+            return Err(ContractError::InsufficientBucketFunds {
+                bucket,
+                denom: coin.denom.clone(),
+                available,
+                required: coin.amount,
+            });
+        }
+        let entry = self
+            .balances
+            .iter_mut()
+            .find(|b| b.bucket == bucket && b.denom == coin.denom)
+            .unwrap();
+        entry.amount = (entry.amount - coin.amount)?;
+        Ok(())
+    }
+}
+
+fn amount_of(coins: &[Coin], denom: &str) -> Uint128 {
+    coins
+        .iter()
+        .filter(|c| c.denom == denom)
+        .map(|c| c.amount)
+        .sum()
+}
+
+pub(crate) fn add_coin(coins: &mut Vec<Coin>, coin: &Coin) {
+    match coins.iter_mut().find(|c| c.denom == coin.denom) {
+        Some(existing) => existing.amount += coin.amount,
+        None => coins.push(coin.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::coins;
+
+    #[test]
+    fn postings_keep_books_balanced() {
+        let mut ledger = Ledger::default();
+        ledger.deposit(FundBucket::PrizePool, &coins(100, "earth"));
+        ledger.deposit(FundBucket::Fees, &coins(10, "earth"));
+        ledger
+            .transfer(FundBucket::Fees, FundBucket::Refunds, &coins(4, "earth"))
+            .unwrap();
+        ledger
+            .withdraw(FundBucket::PrizePool, &coins(60, "earth"))
+            .unwrap();
+
+        assert_eq!(Uint128(40), ledger.balance(FundBucket::PrizePool, "earth"));
+        assert_eq!(Uint128(6), ledger.balance(FundBucket::Fees, "earth"));
+        assert_eq!(coins(4, "earth"), ledger.bucket(FundBucket::Refunds));
+        assert_eq!(Uint128(50), ledger.total("earth"));
+        ledger.check(&coins(50, "earth")).unwrap();
+
+        // the bank must hold at least what the buckets claim
+        match ledger.check(&coins(49, "earth")).unwrap_err() {
+            ContractError::LedgerExceedsBalance { recorded, .. } => {
+                assert_eq!(Uint128(50), recorded)
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn cannot_spend_from_another_bucket() {
+        let mut ledger = Ledger::default();
+        ledger.deposit(FundBucket::Deposits, &coins(10, "earth"));
+
+        let err = ledger
+            .withdraw(FundBucket::PrizePool, &coins(1, "earth"))
+            .unwrap_err();
+        match err {
+            ContractError::InsufficientBucketFunds {
+                bucket, available, ..
+            } => {
+                assert_eq!(FundBucket::PrizePool, bucket);
+                assert_eq!(Uint128::zero(), available);
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert_eq!(Uint128(10), ledger.balance(FundBucket::Deposits, "earth"));
+    }
+}
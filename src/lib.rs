@@ -1,7 +1,17 @@
+pub mod bloom;
 pub mod contract;
 pub mod error;
+pub mod helpers;
+pub mod ledger;
+pub mod merkle;
 pub mod msg;
+pub mod schema;
+pub mod sha256;
+pub mod snapshot;
 pub mod state;
+pub mod tally;
+#[cfg(feature = "test-support")]
+pub mod testing;
 
 #[cfg(target_arch = "wasm32")]
 cosmwasm_std::create_entry_points!(contract);
@@ -0,0 +1,76 @@
+//! Merkle trees over sorted pairs. A leaf is the SHA-256 of `0x00` followed
+//! by its data and a node is the SHA-256 of `0x01` followed by its two
+//! children in byte order, so proofs are a plain list of sibling hashes
+//! without left/right flags.
+
+use cosmwasm_std::Binary;
+use std::convert::TryInto;
+
+use crate::sha256::sha256;
+
+pub fn leaf(data: &[u8]) -> [u8; 32] {
+    let mut preimage = vec![0x00];
+    preimage.extend_from_slice(data);
+    sha256(&preimage)
+}
+
+pub fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut preimage = vec![0x01];
+    preimage.extend_from_slice(first);
+    preimage.extend_from_slice(second);
+    sha256(&preimage)
+}
+
+/// root returns the root over `leaves`, pairing them up level by level and
+/// carrying an odd last hash up unchanged. The root of no leaves is all zeroes.
+pub fn root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = level
+            .chunks(2)
+            .map(|pair| match pair {
+                [a, b] => node(a, b),
+                [a] => *a,
+                _ => unreachable!(),
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// verify reports whether `proof` leads from `leaf` to `root`. Proof
+/// elements that are not 32 bytes long fail the proof.
+pub fn verify(root: &[u8], leaf: [u8; 32], proof: &[Binary]) -> bool {
+    let mut hash = leaf;
+    for sibling in proof {
+        let sibling: [u8; 32] = match sibling.as_slice().try_into() {
+            Ok(sibling) => sibling,
+            Err(_) => return false,
+        };
+        hash = node(&hash, &sibling);
+    }
+    hash[..] == *root
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn proofs_verify_against_root() {
+        let (a, b, c) = (leaf(b"alice"), leaf(b"bob"), leaf(b"carol"));
+        let ab = node(&a, &b);
+        let root = node(&ab, &c);
+
+        let proof = vec![Binary::from(a.to_vec()), Binary::from(c.to_vec())];
+        assert!(verify(&root, b, &proof));
+        assert!(verify(&root, c, &[Binary::from(ab.to_vec())]));
+        assert!(!verify(&root, leaf(b"mallory"), &proof));
+        assert!(!verify(&root, b, &[Binary::from(vec![0u8; 31])]));
+        assert_eq!(root, super::root(&[a, b, c]));
+    }
+}
@@ -1,26 +1,1363 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::HumanAddr;
+use cosmwasm_std::{Binary, Coin, Decimal, HumanAddr, Uint128};
+use std::convert::TryFrom;
+
+use crate::state::{
+    ArchivedResult, AuditEntry, BucketBalance, CandidateProposal, Challenge, DeadlineExtension,
+    Disclosure, LocalizedLabel, ReferendumTally, SpawnedElection, TallyRound, Winner,
+};
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct InitMsg {
     pub start: u64,
     pub end: u64,
+    /// number of candidates elected at finalization
+    pub seats: u32,
+    #[serde(default)]
+    pub voting_system: VotingSystem,
+    /// how plurality ties across the last seat are resolved
+    #[serde(default)]
+    pub tie_policy: TiePolicy,
+    /// what happens to ballots for a candidate that withdraws
+    #[serde(default)]
+    pub withdrawal_policy: WithdrawalPolicy,
+    /// weight ballots by the voter's bonded stake instead of one vote each
+    #[serde(default)]
+    pub stake_weight: Option<StakeWeight>,
+    /// how new ballots are weighted while the weight oracle is marked compromised
+    #[serde(default)]
+    pub oracle_fallback: OracleFallback,
+    /// only holders of a token from this cw721 collection may vote, one ballot per token
+    #[serde(default)]
+    pub nft_gate: Option<HumanAddr>,
+    /// staggered voting windows, each within `start`..`end`
+    #[serde(default)]
+    pub cohorts: Vec<CohortWindow>,
+    /// addresses besides the owner that may pause voting
+    #[serde(default)]
+    pub guardians: Vec<HumanAddr>,
+    /// roles granted besides the owner, who holds every role
+    #[serde(default)]
+    pub roles: Vec<RoleGrant>,
+    /// lets anyone challenge the result for a bond after finalization
+    #[serde(default)]
+    pub dispute: Option<DisputeConfig>,
+    /// guardians that may jointly veto the result after finalization
+    #[serde(default)]
+    pub veto_council: Option<VetoCouncil>,
+    /// contract notified with `CompletionHookMsg` once the election is finalized
+    #[serde(default)]
+    pub completion_hook: Option<HumanAddr>,
+    /// part of the funds sent at init paid to whoever finalizes the election
+    #[serde(default)]
+    pub finalization_bounty: Option<FinalizationBounty>,
+    /// part of the funds sent at init set aside for the voters, the rest is the prize
+    #[serde(default)]
+    pub participation_reward: Option<ParticipationReward>,
+    /// cw721 collection that mints a receipt to every voter, this contract
+    /// must be its minter and the collection should refuse transfers
+    #[serde(default)]
+    pub receipts: Option<HumanAddr>,
+    /// holds a yes/no vote on a proposal instead, `candidates` must be empty
+    #[serde(default)]
+    pub referendum: Option<Referendum>,
+    /// caps on candidates and ballots, unlimited if unset
+    #[serde(default)]
+    pub limits: Limits,
+    /// lets the runner-up replace a winner who steps down after finalization
+    #[serde(default)]
+    pub succession: Option<Succession>,
+    /// accept plurality votes for addresses outside `candidates`
+    #[serde(default)]
+    pub write_ins: Option<WriteIns>,
+    /// accept `VoteAsContract` from aggregator contracts
+    #[serde(default)]
+    pub allow_contract_votes: bool,
+    /// key-value registry the winners are published to at finalization
+    #[serde(default)]
+    pub registry: Option<RegistryConfig>,
+    /// second round between the top two when the leader misses the threshold
+    #[serde(default)]
+    pub majority_runoff: Option<MajorityRunoff>,
+    /// Merkle root of the eligible voters, who prove membership with their `Vote`
+    #[serde(default)]
+    pub merkle_roll: Option<MerkleRoll>,
+    /// lowers the weight of candidate ballots cast later in the voting window
+    #[serde(default)]
+    pub weight_decay: Option<WeightDecay>,
+    /// bloom filter of the eligible voters, anyone may vote if unset
+    #[serde(default)]
+    pub eligibility: Option<EligibilityFilter>,
+    /// only addresses that registered during this window may vote
+    #[serde(default)]
+    pub registration: Option<RegistrationConfig>,
+    /// code id of this contract, enables `SpawnElection`
+    #[serde(default)]
+    pub election_code_id: Option<u64>,
+    /// set by the factory when it spawns this election
+    #[serde(default)]
+    pub factory: Option<FactoryLink>,
+    /// lets a committee add candidates before voting starts, `candidates`
+    /// may then be empty
+    #[serde(default)]
+    pub nomination: Option<Nomination>,
+    /// keeps candidates from backing themselves through ballots and relayers
+    #[serde(default)]
+    pub candidate_rules: Option<CandidateRules>,
+    /// the only native denom the election accepts, in funds, bonds, pools
+    /// and locked stake. Any denom is accepted if unset.
+    #[serde(default)]
+    pub denom: Option<String>,
+    /// min_votes is the number of ballots below which `Finalize` declares
+    /// the election invalid, elects nobody and refunds the prize and reward
+    /// pools to the owner
+    #[serde(default)]
+    pub min_votes: Option<u64>,
+    /// party_list elects slates instead of single candidates, plurality only
+    #[serde(default)]
+    pub party_list: Option<PartyList>,
+    #[serde(default)]
+    pub metadata: Option<Metadata>,
+    /// ballot_fee is paid with every ballot and refunded through
+    /// `ClaimBallotFee` after finalization. The fee of a ballot discarded with
+    /// its candidate is forfeited.
+    #[serde(default)]
+    pub ballot_fee: Option<Coin>,
+    #[serde(default)]
+    pub anti_snipe: Option<AntiSnipe>,
+    #[serde(default)]
+    pub history: Option<HistoryRule>,
+    #[serde(default)]
+    pub questions: Vec<Question>,
+    /// key the ballots and every record kept per voter (the roster, weights,
+    /// locks, contributions, relayers, proxies, cohorts, the voter history,
+    /// answers, fees and claims) by a salted hash of the voter's canonical
+    /// address, which queries show in hex instead of the address. This keeps
+    /// addresses out of the stored keys and query responses but is no privacy
+    /// guarantee: the salt is derived from the contract address and the init
+    /// block and is readable on chain, so anyone can hash a known address and
+    /// find its records.
+    #[serde(default)]
+    pub pseudonymous: bool,
+    pub candidates: Vec<HumanAddr>,
+    /// display labels per candidate and locale
+    #[serde(default)]
+    pub labels: Vec<CandidateLabels>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CandidateLabels {
+    pub candidate: HumanAddr,
+    pub labels: Vec<LocalizedLabel>,
+}
+
+/// RegistrationConfig opens `RegisterVoter` between `start` and `end`, against
+/// an optional bond that can be withdrawn once the election is finalized
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RegistrationConfig {
+    pub start: u64,
+    pub end: u64,
+    pub bond: Option<Coin>,
+}
+
+/// Role is a set of admin messages that can be granted to an address.
+/// Admins hold every role and manage the grants.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// read-only mode, oracle replacement and role grants
+    Admin,
+    /// eligibility, voting period, disclosures, ties, challenges, succession,
+    /// archiving and spawning elections
+    ElectionManager,
+    /// pausing and marking the oracle compromised, like a guardian
+    Pauser,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoleGrant {
+    pub address: HumanAddr,
+    pub role: Role,
+}
+
+/// PartyList groups candidates into slates. A ballot counts for the slate
+/// of its candidate, the seats are split between the slates by `allocation`
+/// and each slate fills its seats in list order. Candidates outside every
+/// slate stand as slates of their own.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PartyList {
+    pub allocation: SeatAllocation,
+    pub slates: Vec<Slate>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Slate {
+    pub name: String,
+    /// candidates in list order, `SlateVote` counts for the first one
     pub candidates: Vec<HumanAddr>,
 }
 
+/// SeatAllocation is the highest averages method seats are split by
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SeatAllocation {
+    /// divisors 1, 2, 3, ..., favours larger slates
+    DHondt,
+    /// divisors 1, 3, 5, ...
+    SainteLague,
+}
+
+/// Metadata describes the election to the UIs listing it
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Metadata {
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// Nomination adds a proposed candidate once `approvals` members of the
+/// `committee` approved it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Nomination {
+    pub committee: Vec<HumanAddr>,
+    pub approvals: u32,
+}
+
+/// FactoryLink lets a spawned election report its address back to the
+/// factory, since instantiation does not return it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FactoryLink {
+    pub address: HumanAddr,
+    pub spawn_id: u64,
+}
+
+/// DisputeConfig allows `ChallengeResult` for `window` blocks after
+/// finalization against a `bond`, which goes to `treasury` if the recount
+/// confirms the result
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DisputeConfig {
+    pub window: u64,
+    pub bond: Coin,
+    pub treasury: HumanAddr,
+}
+
+/// VetoCouncil lets `threshold` of `members` veto the result within `window`
+/// blocks after finalization. The prize is held until the window passes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VetoCouncil {
+    pub members: Vec<HumanAddr>,
+    pub threshold: u32,
+    pub window: u64,
+}
+
+/// Succession allows `PromoteRunnerUp` for `window` blocks after
+/// finalization, sent by the owner or the `arbiter`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Succession {
+    pub window: u64,
+    pub arbiter: Option<HumanAddr>,
+}
+
+/// Referendum is a proposal voted on with `ReferendumVote`. At finalization
+/// it fails if turnout against `electorate` is below `quorum`, is vetoed if
+/// more than `veto_threshold` of all votes are `NoWithVeto` and passes if
+/// more than `threshold` of the votes that did not abstain are `Yes`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Referendum {
+    pub title: String,
+    pub description: String,
+    /// total voting power that may take part
+    pub electorate: Uint128,
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub veto_threshold: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferendumChoice {
+    Yes,
+    No,
+    NoWithVeto,
+    Abstain,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferendumStatus {
+    Passed,
+    Rejected,
+    Vetoed,
+    QuorumNotMet,
+}
+
+/// Question is asked next to the candidate election and answered with the
+/// `answers` of `Vote`. Each question is counted on its own with `method`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Question {
+    pub name: String,
+    pub options: Vec<String>,
+    pub method: QuestionMethod,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionMethod {
+    /// one option per answer
+    Plurality,
+    /// any number of options, each receiving the full weight
+    Approval,
+    /// options in order of preference, the option at position `i` receiving
+    /// `n - 1 - i` times the weight
+    Borda,
+}
+
+/// Answer picks `choices` among the options of `question`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Answer {
+    pub question: String,
+    pub choices: Vec<String>,
+}
+
+/// QuestionOutcome lists the options of a question best first. `winner` is
+/// unset while nobody answered or the best options tie.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QuestionOutcome {
+    pub question: String,
+    pub winner: Option<String>,
+    pub totals: Vec<OptionTotal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct OptionTotal {
+    pub option: String,
+    pub weight: Uint128,
+}
+
+/// WriteIns lets plurality voters vote for any address. A write-in is only
+/// listed in `VoteResponse` once it has `min_count` ballots, it is counted
+/// for the result regardless.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WriteIns {
+    pub min_count: Uint128,
+}
+
+/// MajorityRunoff makes `Finalize` open a runoff of `duration` blocks between
+/// the two leading candidates unless the leader holds at least `threshold`
+/// of the counted weight. Only voters of the first round vote in the runoff.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MajorityRunoff {
+    pub threshold: Decimal,
+    pub duration: u64,
+}
+
+/// AntiSnipe extends `end` by `extension` blocks when a ballot leaves more
+/// than `threshold` of all ballots cast in the last `window` blocks before
+/// `end`. Extensions stop once they add up to `max_extension` blocks.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AntiSnipe {
+    pub threshold: Decimal,
+    pub window: u64,
+    pub extension: u64,
+    pub max_extension: u64,
+}
+
+/// HistoryRule admits only voters on record in the earlier election at
+/// `election`, as reported by its `GetHistory` query, so that accounts created
+/// just before this election cannot vote
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HistoryRule {
+    /// the voter cast a ballot there
+    VotedIn { election: HumanAddr },
+    /// the voter was on its registered roll
+    Registered { election: HumanAddr },
+}
+
+/// RegistryConfig names the key-value registry contract results are written
+/// to. `{election}` and `{seat}` in `key_template` are replaced by the address
+/// of this contract and the 1-based seat number, e.g. `elections/{election}/{seat}`.
+/// A `deferred` registry is only written by `PublishResult`, so that a failing
+/// registry cannot hold up the finalization and publishing can be retried.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RegistryConfig {
+    pub address: HumanAddr,
+    pub key_template: String,
+    #[serde(default)]
+    pub deferred: bool,
+}
+
+/// EligibilityFilter commits to the electorate with a bloom filter over the
+/// canonical voter addresses, see the `bloom` module for the layout. Known
+/// false positives can be listed in `excluded`. `members` is the number of
+/// addresses inserted and only informs clients about the false positive rate.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EligibilityFilter {
+    pub bits: Binary,
+    pub hashes: u32,
+    pub members: u64,
+    #[serde(default)]
+    pub excluded: Vec<HumanAddr>,
+}
+
+/// MerkleRoll commits to the electorate with the root of a `merkle` tree.
+/// Each leaf is a voter address, followed by the voter's weight as 8 big
+/// endian bytes if `weighted`. The roll weight replaces `stake_weight`, an
+/// unweighted roll gives every voter one vote.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MerkleRoll {
+    pub root: Binary,
+    pub weighted: bool,
+}
+
+/// WeightDecay scales a ballot by how far into `start..end` it is cast.
+/// Decayed weights are counted in thousandths of the voter's weight and are
+/// fixed when the ballot is cast.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WeightDecay {
+    /// falls linearly from the full weight at `start` to `floor` of it at `end`
+    Linear { floor: Decimal },
+    /// halves every `half_life` blocks, falling linearly within each half-life
+    Exponential { half_life: u64 },
+}
+
+/// CandidateRules restricts who may stand and what candidates may do as
+/// voters and relayers. Without them a relayer submits ballots only for the
+/// voters that authorized it directly.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct CandidateRules {
+    /// reject ballots of a candidate that name the candidate itself
+    #[serde(default)]
+    pub no_self_vote: bool,
+    /// reject candidates in `SetRelayer`
+    #[serde(default)]
+    pub no_candidate_relayers: bool,
+    /// longest chain of relayers a `BatchVote` entry may be submitted
+    /// through, 1 (only the voter's own relayer) if unset
+    #[serde(default)]
+    pub max_relay_depth: Option<u32>,
+    /// addresses that cannot be registered as candidates
+    #[serde(default)]
+    pub banned: Vec<HumanAddr>,
+    /// keep the owner from registering as a candidate
+    #[serde(default)]
+    pub no_owner: bool,
+}
+
+/// MerkleProof holds the sibling hashes from the voter's leaf up to the root
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MerkleProof {
+    pub path: Vec<Binary>,
+    /// weight in the leaf, only for weighted rolls
+    pub weight: Option<u64>,
+}
+
+/// CohortWindow assigns `members` to a cohort that may only vote between `start` and `end`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CohortWindow {
+    pub name: String,
+    pub start: u64,
+    pub end: u64,
+    pub members: Vec<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VotingSystem {
+    /// one candidate per ballot, most votes wins
+    Plurality,
+    /// ranked ballots counted by single transferable vote with the Droop quota
+    Stv,
+    /// ranked ballots, each position is worth one point more than the next
+    Borda,
+    /// ranked ballots, the candidate beating every other head to head wins,
+    /// `fallback` picks among the remaining candidates when there is a cycle
+    Condorcet { fallback: CondorcetFallback },
+    /// every voter distributes `points` across candidates with `SplitVote`,
+    /// each candidate receiving the voter's weight per point. A plain `Vote`
+    /// puts all points on one candidate.
+    Cumulative { points: u32 },
+}
+
+impl Default for VotingSystem {
+    fn default() -> Self {
+        VotingSystem::Plurality
+    }
+}
+
+impl VotingSystem {
+    /// is_ranked reports whether ballots rank the candidates
+    pub fn is_ranked(&self) -> bool {
+        matches!(
+            self,
+            VotingSystem::Stv | VotingSystem::Borda | VotingSystem::Condorcet { .. }
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CondorcetFallback {
+    /// highest Borda score
+    Borda,
+    /// smallest worst head to head defeat
+    Minimax,
+}
+
+/// FinalizationBounty pays `amount` to the sender of the `Finalize` that
+/// stores the result, if it comes within `window` blocks after voting ends.
+/// A later finalization returns the bounty to the owner.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FinalizationBounty {
+    pub amount: Vec<Coin>,
+    pub window: u64,
+}
+
+/// ParticipationReward splits `pool` among the voters of the election, who
+/// claim their share with `ClaimReward` after finalization. `Archive` returns
+/// what is left to the owner once every voter has claimed, which leaves only
+/// the rounding dust, or once `claim_window` blocks have passed since
+/// finalization.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ParticipationReward {
+    pub pool: Vec<Coin>,
+    #[serde(default)]
+    pub split: RewardSplit,
+    #[serde(default)]
+    pub claim_window: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RewardSplit {
+    /// every voter gets the same share
+    Equal,
+    /// shares are proportional to ballot weight
+    Weight,
+}
+
+impl Default for RewardSplit {
+    fn default() -> Self {
+        RewardSplit::Equal
+    }
+}
+
+/// Limits caps the state an election accumulates. Write-ins count towards
+/// `max_candidates` and runoff ballots towards the ballot limits.
+/// `cooldown` is the number of blocks an address has to wait between
+/// ballots, registrations, withdrawals and relayer changes.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Limits {
+    pub max_candidates: Option<u32>,
+    pub max_total_votes: Option<u64>,
+    pub max_votes_per_block: Option<u32>,
+    #[serde(default)]
+    pub cooldown: Option<u64>,
+}
+
+/// WithdrawalPolicy decides what happens to the ballots of a candidate that
+/// withdraws. Voters whose ballot is discarded may vote again.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WithdrawalPolicy {
+    /// ballots with the candidate as first choice are discarded
+    Discard,
+    /// ranked ballots move to their next choice, other ballots are discarded
+    Redirect,
+}
+
+impl Default for WithdrawalPolicy {
+    fn default() -> Self {
+        WithdrawalPolicy::Discard
+    }
+}
+
+/// TiePolicy decides what happens when candidates tie for the last seat of a
+/// plurality election
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TiePolicy {
+    /// the lower address wins the seat
+    TieBreak,
+    /// every tied candidate is elected, so there may be more winners than seats
+    Expand,
+    /// `Finalize` opens a runoff of `duration` blocks among the tied
+    /// candidates, in which the voters of the election vote again
+    Runoff { duration: u64 },
+    /// the candidate listed first at init wins the seat
+    EarliestCandidate,
+    /// `Finalize` waits until the owner picks the winners with `ResolveTie`
+    Manual,
+}
+
+impl Default for TiePolicy {
+    fn default() -> Self {
+        TiePolicy::TieBreak
+    }
+}
+
+/// StakeWeight selects where a voter's weight is read from. The weight is
+/// looked up on the voter's first ballot and cached for the rest of the election.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StakeWeight {
+    /// delegations of the chain's bonded denom in the native staking module
+    Native {},
+    /// balance reported by a staking contract at `snapshot_height` (defaults to `start`)
+    Contract {
+        address: HumanAddr,
+        snapshot_height: Option<u64>,
+    },
+    /// member weight in a cw4 group, read when the member votes or at `start`
+    /// if `snapshot` is set. Addresses outside the group cannot vote.
+    Group {
+        address: HumanAddr,
+        #[serde(default)]
+        snapshot: bool,
+    },
+    /// tokens of `denom` the voter locked in this contract with `Lock`. They
+    /// stay locked until voting ends, so they cannot back a second address.
+    Locked { denom: String },
+    /// cw20 balance of the voter when it first votes. cw20 has no history,
+    /// so tokens moved after a vote can weigh a second ballot.
+    Cw20 { address: HumanAddr },
+    /// the same weight for every voter
+    Fixed { weight: u64 },
+    /// power reported at `start` by an adapter contract answering
+    /// `VotingPowerQueryMsg`, for weighting schemes this contract does not know
+    Adapter { address: HumanAddr },
+}
+
+/// OracleFallback is the safe mode used while a guardian has marked the
+/// staking or group contract behind `StakeWeight` as compromised
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OracleFallback {
+    /// reject ballots that need the oracle
+    Pause,
+    /// count every new ballot with weight 1
+    EqualWeight,
+}
+
+impl Default for OracleFallback {
+    fn default() -> Self {
+        OracleFallback::Pause
+    }
+}
+
+/// StakingQueryMsg is the query a staking contract must answer for `StakeWeight::Contract`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum StakingQueryMsg {
+    StakedBalanceAtHeight {
+        address: HumanAddr,
+        height: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StakedBalanceAtHeightResponse {
+    pub balance: Uint128,
+    pub height: u64,
+}
+
+/// VotingPowerQueryMsg is the query an adapter must answer for `StakeWeight::Adapter`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VotingPowerQueryMsg {
+    VotingPower { voter: HumanAddr, height: u64 },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotingPowerResponse {
+    pub power: Uint128,
+}
+
+/// Cw20QueryMsg is the subset of the cw20 query interface used for `StakeWeight::Cw20`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw20QueryMsg {
+    Balance { address: HumanAddr },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BalanceResponse {
+    pub balance: Uint128,
+}
+
+/// Cw4QueryMsg is the subset of the cw4 query interface used for `StakeWeight::Group`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw4QueryMsg {
+    Member {
+        addr: HumanAddr,
+        at_height: Option<u64>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MemberResponse {
+    pub weight: Option<u64>,
+}
+
+/// Cw721QueryMsg is the subset of the cw721 query interface used for `nft_gate`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721QueryMsg {
+    Tokens {
+        owner: HumanAddr,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+}
+
+/// Cw721HandleMsg is the subset of the cw721-base interface used to mint vote receipts
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Cw721HandleMsg {
+    Mint(MintMsg),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MintMsg {
+    pub token_id: String,
+    pub owner: HumanAddr,
+    pub name: String,
+    pub description: Option<String>,
+    pub image: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TokensResponse {
+    pub tokens: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum HandleMsg {
+    Vote {
+        candidate: HumanAddr,
+        /// proof of membership in the `merkle_roll`
+        #[serde(default)]
+        proof: Option<MerkleProof>,
+        /// answers to the election's questions, weighted like the ballot.
+        /// Questions left out are abstentions.
+        #[serde(default)]
+        answers: Option<Vec<Answer>>,
+    },
+    /// SlateVote votes for the slate `name` of the `party_list` as a whole
+    SlateVote {
+        name: String,
+    },
+    /// RankedVote casts a ballot listing candidates in order of preference
+    RankedVote {
+        ranking: Vec<HumanAddr>,
+    },
+    /// SplitVote casts one ballot whose weight is divided across candidates in
+    /// proportion to `shares`, e.g. an organization reflecting its members' preferences
+    SplitVote {
+        shares: Vec<Share>,
+    },
+    /// BatchVote casts ballots for several voters at once, each entry either for
+    /// the sender or for a voter that authorized the sender as its relayer.
+    /// Entries succeed or fail independently and are reported as `entry_<i>` attributes
+    /// with `ok`, `duplicate` or the failure reason.
+    BatchVote {
+        votes: Vec<BallotEntry>,
+    },
+    /// RegisterVoter adds the sender to the roster during the registration window
+    RegisterVoter {},
+    /// WithdrawBond returns the registration bond after finalization
+    WithdrawBond {},
+    /// ClaimBallotFee refunds the ballot fees the sender paid, for itself or
+    /// as a relayer or proxy, after finalization
+    ClaimBallotFee {},
+    /// ClaimReward pays the sender's share of the participation reward after finalization
+    ClaimReward {},
+    /// ProposeNewOwner starts handing the contract to `address`, owner only.
+    /// A new proposal replaces the pending one.
+    ProposeNewOwner {
+        address: HumanAddr,
+    },
+    /// AcceptOwnership completes the transfer, sent by the proposed owner
+    AcceptOwnership {},
+    /// Contribute escrows the attached funds for `candidate`'s campaign until
+    /// the election is finalized
+    Contribute {
+        candidate: HumanAddr,
+    },
+    /// ReleaseContributions pays the escrowed contributions to `candidate`
+    /// once it has won and the result is final
+    ReleaseContributions {
+        candidate: HumanAddr,
+    },
+    /// RefundContribution returns the sender's contribution to `candidate`
+    /// once it has lost or the result was vetoed, overturned or invalid
+    RefundContribution {
+        candidate: HumanAddr,
+    },
+    /// Snapshot freezes the voter roll, see `RollSnapshot`. The first ballot
+    /// takes the snapshot if nobody did before.
+    Snapshot {},
+    /// Lock holds the attached `StakeWeight::Locked` tokens until voting ends.
+    /// Only tokens locked before the first ballot count towards its weight.
+    Lock {},
+    /// Unlock returns the sender's locked tokens once voting has ended
+    Unlock {},
+    /// ProposeCandidate opens a nomination of `candidate`, before voting starts
+    ProposeCandidate {
+        candidate: HumanAddr,
+    },
+    /// ApproveCandidate records a committee member's approval. The candidate
+    /// is added once the nomination has enough approvals.
+    ApproveCandidate {
+        candidate: HumanAddr,
+    },
+    /// GrantRole gives `address` a role, admins only
+    GrantRole {
+        address: HumanAddr,
+        role: Role,
+    },
+    /// RevokeRole takes a role back from `address`, admins only
+    RevokeRole {
+        address: HumanAddr,
+        role: Role,
+    },
+    /// WithdrawCandidacy removes the sender from the race, before voting or the
+    /// runoff ends. Split ballots give the sender's share to their other
+    /// candidates, other ballots follow the `WithdrawalPolicy`.
+    WithdrawCandidacy {},
+    /// Archive keeps a summary of the finalized election and prunes its ballots.
+    /// Only the owner, once disputes are closed and the rewards claimed.
+    Archive {},
+    /// RecordArchive is sent by a spawned election when it is archived
+    RecordArchive {
+        spawn_id: u64,
+        summary: ArchivedResult,
+    },
+    /// SetRelayer authorizes `relayer` to submit ballots for the sender in a
+    /// `BatchVote`, or revokes the current relayer if `None`
+    SetRelayer {
+        relayer: Option<HumanAddr>,
+    },
+    /// VoteAsContract casts `ballot` for the calling contract, weighted like
+    /// any other voter, on behalf of the aggregator policy it names. Only
+    /// accepted if the election was created with `allow_contract_votes`.
+    VoteAsContract {
+        on_behalf_of_policy: String,
+        ballot: Ballot,
+    },
+    /// AuthorizeProxy lets `proxy` cast the sender's ballot with `ProxyVote`
+    /// up to height `expires`. Unlike a relayer or a delegation the proxy
+    /// casts one ballot, with the sender's weight, and the grant is used up.
+    AuthorizeProxy {
+        proxy: HumanAddr,
+        expires: u64,
+    },
+    /// RevokeProxy withdraws the sender's proxy grant
+    RevokeProxy {},
+    /// ProxyVote casts `ballot` for `principal`, who authorized the sender
+    ProxyVote {
+        principal: HumanAddr,
+        ballot: Ballot,
+    },
+    /// Finalize closes the election after `end` and selects the top `seats`
+    /// candidates. With a `limit` it counts at most that many more ballots
+    /// and only finalizes once every ballot has been counted. This spreads
+    /// the count of plurality and cumulative elections without a party list
+    /// over several calls, other counts run in full in the last call. It does
+    /// not bound the gas of a call: every call loads all ballots with the
+    /// configuration and the last one reads them again for the standings.
+    Finalize {
+        #[serde(default)]
+        limit: Option<u32>,
+    },
+    /// PromoteRunnerUp replaces the `vacated` winner by the best placed
+    /// candidate that is not a winner yet and notifies the completion hook
+    /// and registry again
+    PromoteRunnerUp {
+        vacated: HumanAddr,
+    },
+    /// PublishResult writes the released result to the registry. Anyone may
+    /// send it, as often as needed until the registry accepts it.
+    PublishResult {},
+    /// ResolveTie picks the winners among the tied candidates under the
+    /// `Manual` tie policy and finalizes the election
+    ResolveTie {
+        winners: Vec<HumanAddr>,
+    },
+    /// ChallengeResult recounts the raw ballots during the dispute window and
+    /// compares the standings with the committed tally. The attached bond is
+    /// refunded if they differ and forfeited to the treasury otherwise. Results
+    /// that are invalid, vetoed or changed by `PromoteRunnerUp` cannot be
+    /// challenged this way.
+    ChallengeResult {},
+    /// Challenge disputes the result for the `DisputeConfig` bond, to be
+    /// resolved by an election manager or a guardian
+    Challenge {
+        reason: String,
+    },
+    /// ResolveChallenge settles a challenge. An upheld challenge gets its
+    /// bond back and overturns the result, a rejected one forfeits the bond
+    /// to the treasury.
+    ResolveChallenge {
+        id: u64,
+        upheld: bool,
+    },
+    /// ApproveVeto records a council member's approval to veto the result.
+    /// The approval that reaches the threshold vetoes the election and
+    /// refunds the prize and the participation reward to the owner.
+    ApproveVeto {},
+    /// ReleasePrize pays the prize to the winners and notifies the completion
+    /// hook and the registry once the result is final. Elections with a veto
+    /// council or a dispute window hold all three until then.
+    ReleasePrize {},
+    /// CheckPhase lets a keeper advance the election to the phase of the
+    /// current height. Only the move to `Finalized` needs a transaction, the
+    /// other phases follow from the configured heights.
+    CheckPhase {},
+    /// SpawnElection instantiates a new election from the stored code id,
+    /// needs the `ElectionManager` role like `SpawnFromTemplate`
+    SpawnElection {
+        label: String,
+        params: Box<InitMsg>,
+    },
+    /// SaveTemplate stores `params` under `name` for `SpawnFromTemplate`.
+    /// Its `start` and `end` are offsets from the height of the spawn and its
+    /// candidates are replaced by the spawn's.
+    SaveTemplate {
+        name: String,
+        params: Box<InitMsg>,
+    },
+    /// SpawnFromTemplate spawns an election from the template `name`
+    SpawnFromTemplate {
+        name: String,
+        label: String,
+        candidates: Vec<HumanAddr>,
+    },
+    /// RegisterSpawned is sent by a spawned election from its init
+    RegisterSpawned {
+        spawn_id: u64,
+    },
+    /// MarkOracleCompromised lets the owner or a guardian stop trusting the
+    /// weight oracle until the owner replaces it with `ReplaceOracle`
+    MarkOracleCompromised {},
+    ReplaceOracle {
+        address: HumanAddr,
+    },
+    /// SetEligibilityFilter replaces the voter filter, `None` lets anyone vote.
+    /// Once the roll is frozen the running election keeps the old filter.
+    SetEligibilityFilter {
+        filter: Option<EligibilityFilter>,
+    },
+    /// ReferendumVote casts a ballot on the proposal of a referendum
+    ReferendumVote {
+        choice: ReferendumChoice,
+    },
+    /// Scrub removes up to `limit` records naming voters once the election is
+    /// archived: relayers, proxies, cohorts, weights, reward claims, rate
+    /// limits, used tokens, ballot payers, answers, referendum ballots and the
+    /// contributions to campaigns that won. The roster and the voter history
+    /// are kept, as `GetHistory` answers from them for the `HistoryRule` of
+    /// later elections, and so are locks and refundable contributions until
+    /// their owners withdraw them. It replies with the number of records
+    /// removed and can be repeated until that is 0.
+    Scrub {
+        #[serde(default)]
+        limit: Option<u32>,
+    },
+    /// RecordDisclosure appends a campaign finance entry to `candidate`, sent
+    /// by the candidate or an election manager. Entries cannot be changed or
+    /// removed.
+    RecordDisclosure {
+        candidate: HumanAddr,
+        source: String,
+        amount: Coin,
+        memo: String,
+    },
+    /// UpdatePeriod moves the voting window, sent by the owner before voting starts
+    UpdatePeriod {
+        start: u64,
+        end: u64,
+    },
+    /// Pause blocks voting immediately, sent by the owner or a guardian
+    Pause {},
+    Unpause {},
+    /// SetReadOnly lets the owner reject every other message while queries keep working
+    SetReadOnly {
+        read_only: bool,
+    },
+}
+
+/// SudoMsg is sent by the chain's native governance to the `sudo` entry point
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SudoMsg {
+    /// Extend moves the end of voting to a later height
+    Extend {
+        end: u64,
+    },
+    /// Halt stops voting and finalization until `Resume`
+    Halt {},
+    Resume {},
+    /// SetReadOnly switches read-only mode like `HandleMsg::SetReadOnly`
+    SetReadOnly {
+        read_only: bool,
+    },
+    /// CheckPhase runs `HandleMsg::CheckPhase`, any bounty goes to the owner
+    CheckPhase {},
+}
+
+/// CompletionHookMsg is executed on the `completion_hook` contract at finalization
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionHookMsg {
+    ElectionFinalized {
+        election: HumanAddr,
+        finalized_at: u64,
+        winners: Vec<Winner>,
+    },
+}
+
+/// RegistryMsg is executed on the `registry` contract at finalization, once per seat
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RegistryMsg {
+    Set { key: String, value: Binary },
+}
+
+/// PublishedWinner is the json value written to the registry for each seat.
+/// `result_hash` is the SHA-256 of the stored result, so readers can tell
+/// whether a later recount replaced it. `counts_hash` is the SHA-256 of the
+/// standings encoded by `snapshot::encode_tally` and `turnout` the number of
+/// ballots counted.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PublishedWinner {
+    pub election: HumanAddr,
+    pub seat: u32,
+    pub winner: HumanAddr,
+    pub finalized_at: u64,
+    pub result_hash: Binary,
+    pub counts_hash: Binary,
+    pub turnout: u64,
+}
+
+/// Ballot is one of the ballot shapes accepted by the single vote messages
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Ballot {
     Vote { candidate: HumanAddr },
+    RankedVote { ranking: Vec<HumanAddr> },
+    SplitVote { shares: Vec<Share> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BallotEntry {
+    pub voter: HumanAddr,
+    pub ballot: Ballot,
+    /// retries of an entry with a key the sender already used are skipped
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Share {
+    pub candidate: HumanAddr,
+    pub share: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
-    // GetVoteInfo returns the current count as a json-encoded number
-    GetVoteInfo {},
+    // GetVoteInfo returns the current count, counts and weights are Uint128 strings
+    GetVoteInfo {
+        locale: Option<String>,
+        #[serde(default)]
+        sort: Option<VoteSort>,
+    },
+    // GetVoteInfoLegacy returns the count with plain integer counts and weights
+    // for clients of the earlier schema, saturating values that do not fit
+    GetVoteInfoLegacy {
+        locale: Option<String>,
+        #[serde(default)]
+        sort: Option<VoteSort>,
+    },
+    // GetPhase returns the phase at the current height and when it ends
+    GetPhase {},
+    // GetResult returns the winners once the election is finalized
+    GetResult {
+        locale: Option<String>,
+    },
+    // GetTallyRounds returns the rounds of an STV count once finalized
+    GetTallyRounds {
+        locale: Option<String>,
+    },
+    // ListStaleElections returns elections past `end` that are not finalized yet
+    ListStaleElections {},
+    // GetLedger returns the funds reserved in every bucket next to the bank balance
+    GetLedger {},
+    // GetBallot returns the ballot cast by `voter`
+    GetBallot {
+        voter: HumanAddr,
+    },
+    // GetParticipation returns the addresses that cast a ballot
+    GetParticipation {},
+    // GetHistory returns what this election keeps on record about `voter`,
+    // also after `Archive`. It covers this election only: each earlier
+    // election is a separate contract and answers for its own voters
+    GetHistory {
+        voter: HumanAddr,
+    },
+    // VoterSetDiff compares the voters of two elections, `from` being the earlier one
+    VoterSetDiff {
+        from: HumanAddr,
+        to: HumanAddr,
+    },
+    // GetTemplate returns the election template stored under `name`
+    GetTemplate {
+        name: String,
+    },
+    // ListSpawnedElections returns the elections spawned by this factory
+    ListSpawnedElections {},
+    // ListElections returns the spawned elections in spawn order, optionally
+    // only those in `status_filter` or tagged with `tag_filter`
+    ListElections {
+        status_filter: Option<ElectionStatus>,
+        tag_filter: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // ListVoters returns the registered voters in key order, as pseudonyms if
+    // the election is pseudonymous
+    ListVoters {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    // GetVoterRoll returns the frozen voter roll, not found before `Snapshot`
+    GetVoterRoll {},
+    // GetStats returns turnout and participation figures
+    GetStats {},
+    // ExportSnapshot returns a page of ballots ordered by voter in the canonical binary encoding
+    ExportSnapshot {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    // ListCandidateProposals returns the pending nominations in candidate order
+    ListCandidateProposals {
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    // GetContributions returns the funds escrowed for `candidate`
+    GetContributions {
+        candidate: HumanAddr,
+    },
+    // GetLockedBalance returns the tokens `voter` has locked
+    GetLockedBalance {
+        voter: HumanAddr,
+    },
+    // GetAuditLog returns the privileged actions in the order they were taken
+    GetAuditLog {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // GetProxy returns the proxy `principal` authorized, if any
+    GetProxy {
+        principal: HumanAddr,
+    },
+    // ListChallenges returns the challenges of the result in the order they were submitted
+    ListChallenges {},
+    // ListDisclosures returns the disclosures of `candidate` in the order they were recorded
+    ListDisclosures {
+        candidate: HumanAddr,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // GetCandidateVotes returns the voters whose ballot counts for `candidate`,
+    // in key order and as pseudonyms if the election is pseudonymous
+    GetCandidateVotes {
+        candidate: HumanAddr,
+        start_after: Option<HumanAddr>,
+        limit: Option<u32>,
+    },
+    // GetCandidateCount returns the ballots and weight counted for `candidate`
+    // without loading the ballots
+    GetCandidateCount {
+        candidate: HumanAddr,
+    },
+    // GetLeaderboard returns up to `limit` candidates with the most ballots,
+    // most first. Candidates without ballots are not listed.
+    GetLeaderboard {
+        limit: Option<u32>,
+    },
+    // GetExtensions lists the extensions of `end` made by the anti-sniping rule
+    GetExtensions {},
+    // CanVote runs the checks of a `Vote` for `candidate` by `voter` without
+    // casting it
+    CanVote {
+        voter: HumanAddr,
+        candidate: HumanAddr,
+    },
+    // GetConfig returns the election parameters
+    GetConfig {},
+    // GetTallyProof returns the commitment to the ballots and the tally made at finalization
+    GetTallyProof {},
+    // SimulateTally counts the ballots cast so far with `method`, the
+    // election's own voting system if unset, without finalizing
+    SimulateTally {
+        method: Option<VotingSystem>,
+    },
+    // GetArchivedResult returns the summary of an archived election, by spawn id
+    // on a factory and under 0 on the archived election itself
+    GetArchivedResult {
+        election_id: u64,
+    },
+    // GetReferendum returns the proposal, the running tally and the outcome once finalized
+    GetReferendum {},
+    // Multi runs several queries at once and returns their responses in order
+    Multi {
+        queries: Vec<QueryMsg>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ConfigResponse {
+    pub owner: HumanAddr,
+    /// proposed owner that has not accepted yet
+    pub pending_owner: Option<HumanAddr>,
+    pub roles: Vec<RoleGrant>,
+    pub start: u64,
+    pub end: u64,
+    pub seats: u32,
+    pub voting_system: VotingSystem,
+    pub eligibility: Option<EligibilityParams>,
+    /// the only denom the election accepts, any if unset
+    pub denom: Option<String>,
+    pub min_votes: Option<u64>,
+    pub party_list: Option<PartyList>,
+    pub metadata: Option<Metadata>,
+    pub ballot_fee: Option<Coin>,
+    pub anti_snipe: Option<AntiSnipe>,
+    pub history: Option<HistoryRule>,
+    pub questions: Vec<Question>,
+    pub pseudonymous: bool,
+}
+
+/// EligibilityParams describe the eligibility filter without its bits
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct EligibilityParams {
+    /// size of the filter in bits
+    pub bits: u64,
+    pub hashes: u32,
+    pub members: u64,
+    pub excluded: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoterRollResponse {
+    pub height: u64,
+    pub eligibility: Option<EligibilityParams>,
+    pub registered: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferendumResponse {
+    pub title: String,
+    pub description: String,
+    pub tally: ReferendumTally,
+    pub status: Option<ReferendumStatus>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DisclosuresResponse {
+    pub disclosures: Vec<Disclosure>,
+}
+
+/// VoteCheck is the outcome of `CanVote`. Checks run in the order a `Vote`
+/// runs them, the first failing one is reported.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteCheck {
+    Eligible,
+    OutsideWindow {
+        begin: u64,
+        end: u64,
+    },
+    NotRegistered,
+    /// excluded by the eligibility filter
+    NotWhitelisted,
+    UnknownCandidate,
+    CandidateWithdrawn,
+    AlreadyVoted,
+    /// any other reason the ballot would be rejected, such as no voting power
+    Rejected {
+        reason: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CanVoteResponse {
+    pub check: VoteCheck,
+    /// weight the ballot would be counted with, zero unless eligible
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CandidateCountResponse {
+    pub candidate: HumanAddr,
+    pub count: u64,
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ExtensionsResponse {
+    pub end: u64,
+    pub extensions: Vec<DeadlineExtension>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LeaderboardResponse {
+    pub candidates: Vec<CandidateCountResponse>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CandidateVotesResponse {
+    pub votes: Vec<CandidateVote>,
+}
+
+/// CandidateVote is the weight a voter's ballot gives one candidate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CandidateVote {
+    pub voter: HumanAddr,
+    pub weight: u64,
+}
+
+/// MultiResponse holds the json encoded response of every query of a `Multi`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct MultiResponse {
+    pub responses: Vec<Binary>,
+}
+
+/// VoteSort is the order of `VoteResponse.votes`
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteSort {
+    /// most ballots first, then by candidate address
+    Count,
+    /// by candidate address
+    Candidate,
+}
+
+impl Default for VoteSort {
+    fn default() -> Self {
+        VoteSort::Count
+    }
 }
 
 // We define a custom struct for each query response
@@ -29,10 +1366,315 @@ pub struct VoteResponse {
     pub start: u64,
     pub end: u64,
     pub votes: Vec<Vote>,
+    /// 1, or 2 once a runoff was opened
+    pub round: u32,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Vote {
+    pub candidate: HumanAddr,
+    /// number of ballots
+    pub count: Uint128,
+    /// sum of ballot weights, equal to `count` when votes are not weighted
+    pub weight: Uint128,
+    /// display label in the requested locale
+    pub label: Option<String>,
+}
+
+/// LegacyVoteResponse is `VoteResponse` in the schema used before counts
+/// became `Uint128`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyVoteResponse {
+    pub start: u64,
+    pub end: u64,
+    pub votes: Vec<LegacyVote>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LegacyVote {
     pub candidate: HumanAddr,
     pub count: u32,
+    pub weight: u64,
+    pub label: Option<String>,
+}
+
+impl From<VoteResponse> for LegacyVoteResponse {
+    fn from(res: VoteResponse) -> Self {
+        LegacyVoteResponse {
+            start: res.start,
+            end: res.end,
+            votes: res
+                .votes
+                .into_iter()
+                .map(|vote| LegacyVote {
+                    candidate: vote.candidate,
+                    count: u32::try_from(vote.count.u128()).unwrap_or(u32::MAX),
+                    weight: u64::try_from(vote.weight.u128()).unwrap_or(u64::MAX),
+                    label: vote.label,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ResultResponse {
+    pub seats: u32,
+    pub finalized_at: u64,
+    /// winners in rank order
+    pub winners: Vec<Vote>,
+    pub tie_policy: TiePolicy,
+    /// candidates that tied across the last seat
+    pub tied: Vec<HumanAddr>,
+    /// round the winners were elected in
+    pub round: u32,
+    /// height at which the veto council vetoed the result
+    pub vetoed_at: Option<u64>,
+    /// method the ballots were counted with
+    pub voting_system: VotingSystem,
+    pub status: ResultStatus,
+    /// outcome of every question, in the order they were asked
+    #[serde(default)]
+    pub questions: Vec<QuestionOutcome>,
+}
+
+/// Phase is the stage an election is in at a given height
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// before registration and voting
+    Pending,
+    /// voters can register, voting has not started
+    Registration,
+    Voting,
+    /// voting ended and the election waits for `Finalize` or `CheckPhase`
+    Tallying,
+    Finalized,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PhaseResponse {
+    pub phase: Phase,
+    /// first height of the next phase, none if it needs a transaction
+    pub next_at: Option<u64>,
+}
+
+/// ResultStatus tells whether a finalized result still can be disputed
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ResultStatus {
+    /// the dispute or the veto window is open
+    Provisional,
+    /// a challenge is waiting for a resolution
+    Challenged,
+    /// a challenge was upheld
+    Overturned,
+    Vetoed,
+    /// fewer ballots than `min_votes` were cast, nobody was elected
+    Invalid,
+    /// the dispute and veto windows closed without an open or upheld
+    /// challenge and without a veto
+    Final,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CandidateProposalsResponse {
+    pub proposals: Vec<CandidateProposal>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ContributionsResponse {
+    pub candidate: HumanAddr,
+    /// escrowed and not yet released or refunded
+    pub total: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuditLogResponse {
+    pub entries: Vec<AuditEntry>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProxyResponse {
+    pub principal: HumanAddr,
+    pub proxy: HumanAddr,
+    pub expires: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LockedBalanceResponse {
+    pub voter: HumanAddr,
+    pub locked: Coin,
+    /// last height of voting, the tokens can be unlocked after it
+    pub until: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ChallengesResponse {
+    pub challenges: Vec<Challenge>,
+}
+
+/// SimulatedTallyResponse is what `Finalize` would elect right now under
+/// `voting_system`. Ballots without a ranking count as ranking only their
+/// candidate under the ranked systems.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulatedTallyResponse {
+    pub voting_system: VotingSystem,
+    pub ballots: u64,
+    pub winners: Vec<Winner>,
+    pub tied: Vec<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TallyRoundsResponse {
+    pub rounds: Vec<TallyRound>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StaleElectionsResponse {
+    pub elections: Vec<StaleElection>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StaleElection {
+    /// address of the contract hosting the election
+    pub address: HumanAddr,
+    pub end: u64,
+    /// number of blocks since `end`
+    pub stale_blocks: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LedgerResponse {
+    pub buckets: Vec<BucketBalance>,
+    /// coins actually held by the contract
+    pub balance: Vec<Coin>,
+    /// false if the books do not balance or claim more than the contract holds
+    pub solvent: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BallotResponse {
+    pub voter: HumanAddr,
+    pub candidate: HumanAddr,
+    pub ranking: Vec<HumanAddr>,
+    pub weight: u64,
+    pub allocations: Vec<Allocation>,
+    pub token_id: Option<String>,
+}
+
+/// Allocation is the part of a group ballot given to one candidate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Allocation {
+    pub candidate: HumanAddr,
+    /// relative share declared by the voter
+    pub share: u64,
+    /// weight counted for the candidate
+    pub weight: u64,
+}
+
+/// SnapshotResponse is one chunk of the ballot export. `chunk` is encoded as
+/// described in `snapshot::encode_ballots` for the given schema id and version.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SnapshotResponse {
+    pub schema_id: String,
+    pub schema_version: u32,
+    /// number of ballots in the whole election
+    pub total: u32,
+    pub chunk: Binary,
+    /// CRC-32 (IEEE) of `chunk`
+    pub checksum: u32,
+    /// voter to pass as `start_after` for the next chunk, none after the last one
+    pub next: Option<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistoryResponse {
+    /// height of the voter's ballot, if any
+    pub voted_at: Option<u64>,
+    pub registered: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ParticipationResponse {
+    /// none while the election is still open
+    pub finalized_at: Option<u64>,
+    /// voters in address order
+    pub voters: Vec<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoterSetDiffResponse {
+    /// voted in `to` but not in `from`
+    pub new_voters: Vec<HumanAddr>,
+    /// voted in `from` but not in `to`
+    pub lapsed_voters: Vec<HumanAddr>,
+    pub retained: u32,
+    /// share of the `from` voters that voted again
+    pub retention_rate: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpawnedElectionsResponse {
+    pub elections: Vec<SpawnedElection>,
+}
+
+/// ElectionStatus is where a spawned election stands by the heights it was
+/// spawned with, as seen by the factory
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ElectionStatus {
+    Upcoming,
+    Active,
+    Ended,
+    /// the election reported its summary with `RecordArchive`
+    Archived,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ElectionSummary {
+    pub spawn_id: u64,
+    pub label: String,
+    pub address: HumanAddr,
+    pub metadata: Option<Metadata>,
+    pub start: u64,
+    pub end: u64,
+    pub status: ElectionStatus,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ElectionsResponse {
+    pub elections: Vec<ElectionSummary>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TemplateResponse {
+    pub name: String,
+    pub params: InitMsg,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VotersResponse {
+    pub voters: Vec<HumanAddr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    /// ballots cast, runoff ballots included
+    pub ballots: u64,
+    pub unique_voters: u64,
+    /// share of registered voters that voted, none without a roster
+    pub turnout: Option<Decimal>,
+    pub histogram: Vec<HistogramBucket>,
+    /// candidate with the most weight and its lead over the runner-up
+    pub leader: Option<HumanAddr>,
+    pub margin: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct HistogramBucket {
+    /// first and last height of the bucket
+    pub start: u64,
+    pub end: u64,
+    pub ballots: u64,
 }
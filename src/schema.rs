@@ -0,0 +1,103 @@
+//! JSON schemas of the messages, the responses and the stored state, as
+//! written to `schema/` by `cargo run --example schema`
+
+use schemars::schema::RootSchema;
+use schemars::schema_for;
+
+use crate::msg::{
+    AuditLogResponse, BallotResponse, CanVoteResponse, CandidateCountResponse,
+    CandidateProposalsResponse, CandidateVotesResponse, ChallengesResponse, CompletionHookMsg,
+    ConfigResponse, ContributionsResponse, DisclosuresResponse, ElectionsResponse,
+    ExtensionsResponse, HandleMsg, HistoryResponse, InitMsg, LeaderboardResponse, LedgerResponse,
+    LegacyVoteResponse, LockedBalanceResponse, MultiResponse, ParticipationResponse, PhaseResponse,
+    ProxyResponse, PublishedWinner, QueryMsg, ReferendumResponse, RegistryMsg, ResultResponse,
+    SimulatedTallyResponse, SnapshotResponse, SpawnedElectionsResponse, StaleElectionsResponse,
+    StatsResponse, SudoMsg, TallyRoundsResponse, TemplateResponse, VoteResponse, VoterRollResponse,
+    VoterSetDiffResponse, VotersResponse, VotingPowerQueryMsg, VotingPowerResponse,
+};
+use crate::state::{ArchivedResult, State, TallyProof};
+
+/// schemas lists every exported schema, `export_schema` names each file
+/// after the schema's title
+pub fn schemas() -> Vec<RootSchema> {
+    vec![
+        schema_for!(InitMsg),
+        schema_for!(HandleMsg),
+        schema_for!(QueryMsg),
+        schema_for!(SudoMsg),
+        schema_for!(CompletionHookMsg),
+        schema_for!(RegistryMsg),
+        schema_for!(VotingPowerQueryMsg),
+        schema_for!(VotingPowerResponse),
+        schema_for!(PublishedWinner),
+        schema_for!(State),
+        schema_for!(VoteResponse),
+        schema_for!(LegacyVoteResponse),
+        schema_for!(ResultResponse),
+        schema_for!(TallyRoundsResponse),
+        schema_for!(StaleElectionsResponse),
+        schema_for!(LedgerResponse),
+        schema_for!(BallotResponse),
+        schema_for!(SnapshotResponse),
+        schema_for!(ParticipationResponse),
+        schema_for!(VoterSetDiffResponse),
+        schema_for!(SpawnedElectionsResponse),
+        schema_for!(ElectionsResponse),
+        schema_for!(VoterRollResponse),
+        schema_for!(ProxyResponse),
+        schema_for!(AuditLogResponse),
+        schema_for!(VotersResponse),
+        schema_for!(StatsResponse),
+        schema_for!(MultiResponse),
+        schema_for!(ConfigResponse),
+        schema_for!(DisclosuresResponse),
+        schema_for!(CanVoteResponse),
+        schema_for!(CandidateCountResponse),
+        schema_for!(LeaderboardResponse),
+        schema_for!(ExtensionsResponse),
+        schema_for!(HistoryResponse),
+        schema_for!(SimulatedTallyResponse),
+        schema_for!(CandidateVotesResponse),
+        schema_for!(CandidateProposalsResponse),
+        schema_for!(ContributionsResponse),
+        schema_for!(TemplateResponse),
+        schema_for!(PhaseResponse),
+        schema_for!(LockedBalanceResponse),
+        schema_for!(ChallengesResponse),
+        schema_for!(ReferendumResponse),
+        schema_for!(TallyProof),
+        schema_for!(ArchivedResult),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_schema::export_schema;
+    use std::fs::{create_dir_all, read_dir, read_to_string, remove_dir_all};
+
+    /// fails when a message or response changed without regenerating the
+    /// committed schemas with `cargo run --example schema`
+    #[test]
+    fn committed_schemas_are_current() {
+        let out_dir = std::env::temp_dir().join(format!("election-schema-{}", std::process::id()));
+        create_dir_all(&out_dir).unwrap();
+        for schema in schemas() {
+            export_schema(&schema, &out_dir);
+        }
+        let mut generated = vec![];
+        for entry in read_dir(&out_dir).unwrap() {
+            let path = entry.unwrap().path();
+            let name = path.file_name().unwrap().to_string_lossy().to_string();
+            let committed = read_to_string(format!("schema/{}", name)).unwrap_or_default();
+            assert!(
+                read_to_string(&path).unwrap() == committed,
+                "schema/{} is stale",
+                name
+            );
+            generated.push(name);
+        }
+        remove_dir_all(&out_dir).unwrap();
+        assert_eq!(generated.len(), read_dir("schema").unwrap().count());
+    }
+}
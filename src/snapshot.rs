@@ -0,0 +1,123 @@
+use crate::msg::BallotResponse;
+use crate::state::Winner;
+
+/// SCHEMA_ID names the ballot encoding produced by `encode_ballots`. It only
+/// changes together with `SCHEMA_VERSION`, never between releases on its own.
+pub const SCHEMA_ID: &str = "election.ballots";
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// encode_ballots writes `votes` in the canonical v1 layout. All integers are
+/// big endian and strings are utf-8 prefixed with their u32 byte length:
+///
+/// ```text
+/// chunk      := count:u32 ballot*
+/// ballot     := voter:str candidate:str weight:u64 ranking allocations token_id
+/// ranking    := count:u32 candidate:str*
+/// allocations:= count:u32 (candidate:str share:u64 weight:u64)*
+/// token_id   := 0:u8 | 1:u8 id:str
+/// ```
+pub fn encode_ballots(votes: &[&BallotResponse]) -> Vec<u8> {
+    let mut out = vec![];
+    put_u32(&mut out, votes.len() as u32);
+    for vote in votes {
+        out.extend(encode_ballot(vote));
+    }
+    out
+}
+
+/// encode_ballot writes a single `ballot` of the v1 layout
+pub fn encode_ballot(vote: &BallotResponse) -> Vec<u8> {
+    let mut out = vec![];
+    put_str(&mut out, vote.voter.as_str());
+    put_str(&mut out, vote.candidate.as_str());
+    out.extend_from_slice(&vote.weight.to_be_bytes());
+    put_u32(&mut out, vote.ranking.len() as u32);
+    for candidate in &vote.ranking {
+        put_str(&mut out, candidate.as_str());
+    }
+    put_u32(&mut out, vote.allocations.len() as u32);
+    for allocation in &vote.allocations {
+        put_str(&mut out, allocation.candidate.as_str());
+        out.extend_from_slice(&allocation.share.to_be_bytes());
+        out.extend_from_slice(&allocation.weight.to_be_bytes());
+    }
+    match &vote.token_id {
+        Some(token_id) => {
+            out.push(1);
+            put_str(&mut out, token_id);
+        }
+        None => out.push(0),
+    }
+    out
+}
+
+/// encode_tally writes the final standings in the same conventions:
+///
+/// ```text
+/// tally := count:u32 (candidate:str count:u128 weight:u128)*
+/// ```
+pub fn encode_tally(standings: &[Winner]) -> Vec<u8> {
+    let mut out = vec![];
+    put_u32(&mut out, standings.len() as u32);
+    for entry in standings {
+        put_str(&mut out, entry.candidate.as_str());
+        out.extend_from_slice(&entry.count.u128().to_be_bytes());
+        out.extend_from_slice(&entry.weight.u128().to_be_bytes());
+    }
+    out
+}
+
+/// crc32 is the IEEE 802.3 checksum (as used by zlib and gzip)
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend_from_slice(&value.to_be_bytes());
+}
+
+fn put_str(out: &mut Vec<u8>, value: &str) {
+    put_u32(out, value.len() as u32);
+    out.extend_from_slice(value.as_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crc32_matches_reference() {
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+        assert_eq!(0, crc32(b""));
+    }
+
+    #[test]
+    fn encodes_canonical_layout() {
+        let vote = BallotResponse {
+            voter: "v".into(),
+            candidate: "c".into(),
+            ranking: vec![],
+            weight: 2,
+            allocations: vec![],
+            token_id: Some("7".into()),
+        };
+        let expected: Vec<u8> = vec![
+            0, 0, 0, 1, // one ballot
+            0, 0, 0, 1, b'v', // voter
+            0, 0, 0, 1, b'c', // candidate
+            0, 0, 0, 0, 0, 0, 0, 2, // weight
+            0, 0, 0, 0, // ranking
+            0, 0, 0, 0, // allocations
+            1, 0, 0, 0, 1, b'7', // token id
+        ];
+        assert_eq!(expected, encode_ballots(&[&vote]));
+    }
+}
@@ -1,29 +1,935 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{HumanAddr, Storage};
-use cosmwasm_storage::{singleton, singleton_read, ReadonlySingleton, Singleton};
+use cosmwasm_std::{Binary, CanonicalAddr, Coin, Decimal, HumanAddr, Storage, Uint128};
+use cosmwasm_storage::{
+    bucket, bucket_read, singleton, singleton_read, Bucket, ReadonlyBucket, ReadonlySingleton,
+    Singleton,
+};
+
+use crate::msg::{
+    Answer, AntiSnipe, CandidateRules, DisputeConfig, EligibilityFilter, FactoryLink,
+    FinalizationBounty, HistoryRule, InitMsg, Limits, MajorityRunoff, MerkleRoll, Metadata,
+    Nomination, OracleFallback, ParticipationReward, PartyList, Question, Referendum,
+    ReferendumChoice, ReferendumStatus, RegistrationConfig, RegistryConfig, RoleGrant, StakeWeight,
+    Succession, TiePolicy, VetoCouncil, Vote, VotingSystem, WeightDecay, WithdrawalPolicy,
+    WriteIns,
+};
 
 pub static CONFIG_KEY: &[u8] = b"config";
+pub static RESULT_KEY: &[u8] = b"result";
+pub static TALLY_ROUNDS_KEY: &[u8] = b"tally_rounds";
+pub static LEDGER_KEY: &[u8] = b"ledger";
+pub static VOTER_WEIGHT_KEY: &[u8] = b"voter_weight";
+pub static NFT_VOTES_KEY: &[u8] = b"nft_votes";
+pub static LABELS_KEY: &[u8] = b"labels";
+pub static RELAYERS_KEY: &[u8] = b"relayers";
+pub static COHORT_MEMBERS_KEY: &[u8] = b"cohort_members";
+pub static PROCESSED_KEYS_KEY: &[u8] = b"processed_keys";
+pub static SPAWNED_KEY: &[u8] = b"spawned";
+pub static TEMPLATES_KEY: &[u8] = b"templates";
+pub static REGISTERED_KEY: &[u8] = b"registered";
+pub static STATS_KEY: &[u8] = b"stats";
+pub static DISCLOSURES_KEY: &[u8] = b"disclosures";
+pub static TALLY_PROOF_KEY: &[u8] = b"tally_proof";
+pub static REWARD_CLAIMS_KEY: &[u8] = b"reward_claims";
+pub static REFERENDUM_BALLOTS_KEY: &[u8] = b"referendum_ballots";
+pub static REFERENDUM_TALLY_KEY: &[u8] = b"referendum_tally";
+pub static REFERENDUM_OUTCOME_KEY: &[u8] = b"referendum_outcome";
+pub static LAST_ACTION_KEY: &[u8] = b"last_action";
+pub static CANDIDATE_VOTES_KEY: &[u8] = b"candidate_votes";
+pub static CANDIDATE_COUNTS_KEY: &[u8] = b"candidate_counts";
+pub static ARCHIVES_KEY: &[u8] = b"archives";
+pub static CHALLENGES_KEY: &[u8] = b"challenges";
+pub static PROPOSALS_KEY: &[u8] = b"proposals";
+pub static CONTRIBUTIONS_KEY: &[u8] = b"contributions";
+pub static CAMPAIGN_TOTALS_KEY: &[u8] = b"campaign_totals";
+pub static LOCKS_KEY: &[u8] = b"locks";
+pub static ROLL_SNAPSHOT_KEY: &[u8] = b"roll_snapshot";
+pub static PROXIES_KEY: &[u8] = b"proxies";
+pub static AUDIT_LOG_KEY: &[u8] = b"audit_log";
+pub static LEADERBOARD_KEY: &[u8] = b"leaderboard";
+pub static BALLOT_FEES_KEY: &[u8] = b"ballot_fees";
+pub static BALLOT_PAYERS_KEY: &[u8] = b"ballot_payers";
+pub static PARTICIPANTS_KEY: &[u8] = b"participants";
+pub static QUESTION_BALLOTS_KEY: &[u8] = b"question_ballots";
+pub static QUESTION_TALLY_KEY: &[u8] = b"question_tally";
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
+    /// instantiator, allowed to run admin messages
+    #[serde(default)]
+    pub owner: HumanAddr,
+    /// proposed by the owner, becomes the owner on `AcceptOwnership`
+    #[serde(default)]
+    pub pending_owner: Option<HumanAddr>,
     pub start: u64,
     pub end: u64,
-    pub candidates: Vec<HumanAddr>,
+    pub seats: u32,
+    pub voting_system: VotingSystem,
+    #[serde(default)]
+    pub tie_policy: TiePolicy,
+    #[serde(default)]
+    pub withdrawal_policy: WithdrawalPolicy,
+    /// candidates that withdrew, they keep their place in `candidates`
+    #[serde(default)]
+    pub withdrawn: Vec<CanonicalAddr>,
+    /// set once `Finalize` opens a runoff
+    #[serde(default)]
+    pub runoff: Option<Runoff>,
+    /// winners of a tie chosen by the owner under the manual tie policy
+    #[serde(default)]
+    pub tie_resolution: Option<Vec<CanonicalAddr>>,
+    /// weight ballots by the voter's bonded stake, one vote per ballot if unset
+    #[serde(default)]
+    pub stake_weight: Option<StakeWeight>,
+    #[serde(default)]
+    pub oracle_fallback: OracleFallback,
+    /// set by a guardian, the weight oracle is not queried while set
+    #[serde(default)]
+    pub oracle_compromised: bool,
+    /// cw721 collection whose holders may vote, anyone may vote if unset
+    #[serde(default)]
+    pub nft_gate: Option<HumanAddr>,
+    /// voting sub-windows of voter cohorts, voters outside any cohort use `start`/`end`
+    #[serde(default)]
+    pub cohorts: Vec<Cohort>,
+    /// may pause and unpause voting next to the owner
+    #[serde(default)]
+    pub guardians: Vec<HumanAddr>,
+    #[serde(default)]
+    pub roles: Vec<RoleGrant>,
+    /// circuit breaker, voting fails while set
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub dispute: Option<DisputeConfig>,
+    #[serde(default)]
+    pub veto_council: Option<VetoCouncil>,
+    /// council members that approved a veto
+    #[serde(default)]
+    pub veto_approvals: Vec<HumanAddr>,
+    #[serde(default)]
+    pub completion_hook: Option<HumanAddr>,
+    #[serde(default)]
+    pub finalization_bounty: Option<FinalizationBounty>,
+    #[serde(default)]
+    pub participation_reward: Option<ParticipationReward>,
+    #[serde(default)]
+    pub receipts: Option<HumanAddr>,
+    #[serde(default)]
+    pub limits: Limits,
+    #[serde(default)]
+    pub referendum: Option<Referendum>,
+    #[serde(default)]
+    pub succession: Option<Succession>,
+    #[serde(default)]
+    pub write_ins: Option<WriteIns>,
+    #[serde(default)]
+    pub allow_contract_votes: bool,
+    #[serde(default)]
+    pub registry: Option<RegistryConfig>,
+    #[serde(default)]
+    pub majority_runoff: Option<MajorityRunoff>,
+    #[serde(default)]
+    pub merkle_roll: Option<MerkleRoll>,
+    #[serde(default)]
+    pub weight_decay: Option<WeightDecay>,
+    #[serde(default)]
+    pub eligibility: Option<EligibilityFilter>,
+    #[serde(default)]
+    pub registration: Option<RegistrationConfig>,
+    /// code id spawned elections are instantiated from, not a factory if unset
+    #[serde(default)]
+    pub election_code_id: Option<u64>,
+    /// factory that spawned this election
+    #[serde(default)]
+    pub factory: Option<FactoryLink>,
+    #[serde(default)]
+    pub nomination: Option<Nomination>,
+    #[serde(default)]
+    pub candidate_rules: Option<CandidateRules>,
+    #[serde(default)]
+    pub denom: Option<String>,
+    #[serde(default)]
+    pub min_votes: Option<u64>,
+    #[serde(default)]
+    pub party_list: Option<PartyList>,
+    #[serde(default)]
+    pub metadata: Option<Metadata>,
+    #[serde(default)]
+    pub ballot_fee: Option<Coin>,
+    #[serde(default)]
+    pub anti_snipe: Option<AntiSnipe>,
+    #[serde(default)]
+    pub extensions: Vec<DeadlineExtension>,
+    #[serde(default)]
+    pub history: Option<HistoryRule>,
+    #[serde(default)]
+    pub questions: Vec<Question>,
+    /// salt of the voter hashes of a pseudonymous election, public like the
+    /// rest of the storage
+    #[serde(default)]
+    pub voter_salt: Option<Binary>,
+    /// set once the ballots have been pruned by `Archive`
+    #[serde(default)]
+    pub archived: bool,
+    /// progress of a `Finalize` split into batches
+    #[serde(default)]
+    pub tally_cursor: Option<TallyCursor>,
+    pub candidates: Vec<CanonicalAddr>,
     pub votes: Vec<VoteInfo>,
+    /// set once the first vote attempt after `end` has emitted a stale event
+    #[serde(default)]
+    pub stale_reported: bool,
+    /// set by governance to stop voting and finalization
+    #[serde(default)]
+    pub halted: bool,
+    /// set during incident investigations, every handle message except
+    /// `SetReadOnly` fails while queries keep working
+    #[serde(default)]
+    pub read_only: bool,
+}
+
+/// SpawnedElection is an election instantiated by this contract in factory
+/// mode. `address` is filled in when the child registers from its init.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SpawnedElection {
+    pub spawn_id: u64,
+    pub label: String,
+    pub address: Option<HumanAddr>,
+    #[serde(default)]
+    pub metadata: Option<Metadata>,
+    /// voting period the election was spawned with
+    #[serde(default)]
+    pub start: u64,
+    #[serde(default)]
+    pub end: u64,
+}
+
+/// AuditEntry records a privileged action. `actor` is the sender, or the
+/// contract itself for sudo messages, and `payload_hash` is the SHA-256 of
+/// the message as JSON.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AuditEntry {
+    pub id: u64,
+    pub actor: HumanAddr,
+    pub height: u64,
+    pub action: String,
+    pub payload_hash: Binary,
+}
+
+/// ProxyGrant lets `proxy` cast the principal's ballot up to height `expires`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ProxyGrant {
+    pub proxy: HumanAddr,
+    pub expires: u64,
+}
+
+/// BallotPayment is the fee paid for a candidate ballot and who paid it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BallotPayment {
+    pub payer: CanonicalAddr,
+    pub fee: Coin,
+}
+
+/// Registration is a voter's entry in the roster, with the bond it posted
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Registration {
+    pub height: u64,
+    pub bond: Vec<Coin>,
+}
+
+/// Stats are counters maintained as ballots come in, so that statistics do
+/// not need to scan every vote
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Stats {
+    pub ballots: u64,
+    pub unique_voters: u64,
+    pub registered: u64,
+    /// ballots per equal slice of the voting period
+    pub histogram: Vec<u64>,
+    pub totals: Vec<CandidateTotal>,
+    /// ballots cast at `block_height`, for the per block limit
+    #[serde(default)]
+    pub block_height: u64,
+    #[serde(default)]
+    pub block_ballots: u32,
+    /// ballots per block in the anti-sniping window
+    #[serde(default)]
+    pub late_blocks: Vec<BlockBallots>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BlockBallots {
+    pub height: u64,
+    pub ballots: u64,
+}
+
+/// DeadlineExtension records a move of `end` by the anti-sniping rule
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct DeadlineExtension {
+    /// height of the ballot that triggered it
+    pub height: u64,
+    pub previous_end: u64,
+    pub end: u64,
+}
+
+/// CandidateCount is the number of ballots for a candidate and their weight
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct CandidateCount {
+    pub count: u64,
+    pub weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CandidateTotal {
+    pub candidate: CanonicalAddr,
+    pub weight: Uint128,
+}
+
+/// TallyProof commits to the ballots and the final tally. `ballots_root` is
+/// the `merkle` root over the ballots sorted by voter, each leaf being the
+/// ballot as encoded by `snapshot::encode_ballot`, and `tally_hash` is the
+/// SHA-256 of `tally`, encoded by `snapshot::encode_tally`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TallyProof {
+    pub schema_version: u32,
+    pub ballots: u32,
+    pub ballots_root: Binary,
+    pub tally: Binary,
+    pub tally_hash: Binary,
+    pub finalized_at: u64,
+}
+
+/// ArchivedResult is what remains of an election after `Archive`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ArchivedResult {
+    pub winners: Vec<Winner>,
+    pub standings: Vec<Winner>,
+    pub ballots: u64,
+    pub unique_voters: u64,
+    pub finalized_at: u64,
+    pub archived_at: u64,
+}
+
+/// ReferendumTally is the weight behind each choice of a referendum
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct ReferendumTally {
+    pub yes: Uint128,
+    pub no: Uint128,
+    pub no_with_veto: Uint128,
+    pub abstain: Uint128,
+}
+
+impl ReferendumTally {
+    pub fn total(&self) -> u128 {
+        self.yes.u128() + self.no.u128() + self.no_with_veto.u128() + self.abstain.u128()
+    }
+}
+
+/// QuestionTally is the weight behind each option of a question, in the
+/// order of its options
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct QuestionTally {
+    pub question: String,
+    pub weights: Vec<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferendumBallot {
+    pub choice: ReferendumChoice,
+    pub weight: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReferendumOutcome {
+    pub status: ReferendumStatus,
+    pub finalized_at: u64,
+}
+
+/// CandidateProposal is a pending nomination with the committee members
+/// that approved it
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct CandidateProposal {
+    pub candidate: HumanAddr,
+    pub proposer: HumanAddr,
+    pub approvals: Vec<HumanAddr>,
+    pub height: u64,
+}
+
+/// Challenge is a dispute of the result submitted with `Challenge`.
+/// `upheld` is set once the owner or a guardian resolves it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Challenge {
+    pub id: u64,
+    pub challenger: HumanAddr,
+    pub reason: String,
+    pub bond: Vec<Coin>,
+    pub height: u64,
+    pub upheld: Option<bool>,
+}
+
+/// Disclosure is a campaign finance entry recorded against a candidate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Disclosure {
+    /// position in the candidate's disclosures, starting at 0
+    pub id: u64,
+    pub source: String,
+    pub amount: Coin,
+    pub memo: String,
+    pub recorded_by: HumanAddr,
+    pub height: u64,
+}
+
+/// Cohort is a group of voters (district, tier, shift) with its own voting window
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Cohort {
+    pub name: String,
+    pub start: u64,
+    pub end: u64,
 }
 
+/// VoteInfo is a ballot as stored, addresses are humanized when queried
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct VoteInfo {
-    pub voter: HumanAddr,
+    pub voter: CanonicalAddr,
+    pub candidate: CanonicalAddr,
+    /// full preference order for ranked ballots, empty for plurality ballots
+    #[serde(default)]
+    pub ranking: Vec<CanonicalAddr>,
+    pub weight: u64,
+    /// split of `weight` across candidates for group ballots, empty otherwise
+    #[serde(default)]
+    pub allocations: Vec<SubAllocation>,
+    /// token of the gating collection this ballot was cast with
+    #[serde(default)]
+    pub token_id: Option<String>,
+}
+
+/// SubAllocation is the part of a group ballot given to one candidate
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SubAllocation {
+    pub candidate: CanonicalAddr,
+    /// relative share declared by the voter
+    pub share: u64,
+    /// weight counted for the candidate
+    pub weight: u64,
+}
+
+/// VoterWeight caches the voting power of a voter the first time it is looked up
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct VoterWeight {
+    pub weight: u64,
+    /// height the weight was snapshotted at
+    pub height: u64,
+}
+
+/// RollSnapshot freezes the voter roll when voting opens. Eligibility is
+/// checked against its filter and group weights are read at its height, so
+/// later filter or group changes do not reach the running election.
+/// Registration closes with it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RollSnapshot {
+    pub height: u64,
+    pub eligibility: Option<EligibilityFilter>,
+    /// registered voters at `height`
+    pub registered: u64,
+}
+
+/// ElectionResult is written once by `Finalize` and never changes afterwards
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ElectionResult {
+    /// winners in rank order, at most `seats` entries unless ties were expanded
+    pub winners: Vec<Winner>,
+    pub finalized_at: u64,
+    #[serde(default)]
+    pub tie_policy: TiePolicy,
+    /// candidates that tied across the last seat
+    #[serde(default)]
+    pub tied: Vec<HumanAddr>,
+    /// every candidate in final rank order, winners first
+    #[serde(default)]
+    pub standings: Vec<Winner>,
+    /// winners that stepped down and were replaced by a runner-up
+    #[serde(default)]
+    pub vacated: Vec<HumanAddr>,
+    #[serde(default)]
+    pub vetoed_at: Option<u64>,
+    #[serde(default)]
+    pub voting_system: VotingSystem,
+    /// set when fewer than `min_votes` ballots were cast
+    #[serde(default)]
+    pub invalid: bool,
+    /// set once the prize was paid and the result announced
+    #[serde(default)]
+    pub released: bool,
+}
+
+/// Runoff is a second vote among the candidates tied for the last seats.
+/// `elected` holds the candidates that won outright in the first round.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Runoff {
+    pub candidates: Vec<CanonicalAddr>,
+    pub seats: u32,
+    pub elected: Vec<Winner>,
+    pub start: u64,
+    pub end: u64,
+    pub votes: Vec<VoteInfo>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Winner {
+    pub candidate: HumanAddr,
+    pub count: Uint128,
+    pub weight: Uint128,
+}
+
+/// TallyCursor sums up the ballots before `position` in `counts`, only the
+/// plurality and cumulative counts without a party list use it
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct TallyCursor {
+    pub position: u64,
+    pub counts: Vec<Vote>,
+}
+
+/// TallyRound records one round of an STV count
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct TallyRound {
+    pub round: u32,
+    /// value held by every hopeful candidate at the start of the round
+    pub tallies: Vec<RoundTally>,
+    pub elected: Vec<HumanAddr>,
+    pub eliminated: Option<HumanAddr>,
+    /// value of ballots with no hopeful preference left
+    pub exhausted: Decimal,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RoundTally {
     pub candidate: HumanAddr,
+    pub votes: Decimal,
+    /// display label in the requested locale, only filled in query responses
+    #[serde(default)]
+    pub label: Option<String>,
 }
 
-pub fn config<S: Storage>(storage: &mut S) -> Singleton<S, State> {
+/// LocalizedLabel is the display text of a candidate in one locale. Candidates
+/// are always identified by address, labels are presentation only.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LocalizedLabel {
+    pub locale: String,
+    pub label: String,
+}
+
+/// FundBucket is the purpose a held coin is reserved for
+#[derive(
+    Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, JsonSchema,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum FundBucket {
+    Fees,
+    Deposits,
+    PrizePool,
+    RewardPool,
+    Bounty,
+    MatchingPool,
+    Refunds,
+    /// campaign contributions escrowed for the candidates
+    Campaigns,
+    /// voter stake locked for `StakeWeight::Locked`
+    Locked,
+    /// ballot fees waiting to be claimed back by their voters
+    BallotFees,
+    /// ballot fees forfeited by discarded ballots. There is no burn message in
+    /// this version of cosmwasm, so they stay in the contract for good.
+    Burned,
+}
+
+/// Ledger accounts for every coin the contract holds. Each posting moves value
+/// between two accounts (a bucket or the outside world), so for every denom
+/// `received - paid_out` always equals the sum of the bucket balances.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct Ledger {
+    pub balances: Vec<BucketBalance>,
+    pub received: Vec<Coin>,
+    pub paid_out: Vec<Coin>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BucketBalance {
+    pub bucket: FundBucket,
+    pub denom: String,
+    pub amount: Uint128,
+}
+
+pub fn config<S: Storage>(storage: &mut S) -> Singleton<'_, S, State> {
     singleton(storage, CONFIG_KEY)
 }
 
-pub fn config_read<S: Storage>(storage: &S) -> ReadonlySingleton<S, State> {
+pub fn config_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, State> {
     singleton_read(storage, CONFIG_KEY)
 }
+
+pub fn result<S: Storage>(storage: &mut S) -> Singleton<'_, S, ElectionResult> {
+    singleton(storage, RESULT_KEY)
+}
+
+pub fn result_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, ElectionResult> {
+    singleton_read(storage, RESULT_KEY)
+}
+
+pub fn tally_rounds<S: Storage>(storage: &mut S) -> Singleton<'_, S, Vec<TallyRound>> {
+    singleton(storage, TALLY_ROUNDS_KEY)
+}
+
+pub fn tally_rounds_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, Vec<TallyRound>> {
+    singleton_read(storage, TALLY_ROUNDS_KEY)
+}
+
+pub fn ledger<S: Storage>(storage: &mut S) -> Singleton<'_, S, Ledger> {
+    singleton(storage, LEDGER_KEY)
+}
+
+pub fn ledger_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, Ledger> {
+    singleton_read(storage, LEDGER_KEY)
+}
+
+pub fn voter_weights<S: Storage>(storage: &mut S) -> Bucket<'_, S, VoterWeight> {
+    bucket(storage, VOTER_WEIGHT_KEY)
+}
+
+pub fn voter_weights_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, VoterWeight> {
+    bucket_read(storage, VOTER_WEIGHT_KEY)
+}
+
+/// nft_votes maps a token ID of the gating collection to the voter who used it
+pub fn nft_votes<S: Storage>(storage: &mut S) -> Bucket<'_, S, CanonicalAddr> {
+    bucket(storage, NFT_VOTES_KEY)
+}
+
+pub fn nft_votes_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, CanonicalAddr> {
+    bucket_read(storage, NFT_VOTES_KEY)
+}
+
+/// labels maps a candidate to its display labels
+pub fn labels<S: Storage>(storage: &mut S) -> Bucket<'_, S, Vec<LocalizedLabel>> {
+    bucket(storage, LABELS_KEY)
+}
+
+pub fn labels_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, Vec<LocalizedLabel>> {
+    bucket_read(storage, LABELS_KEY)
+}
+
+/// relayers maps a voter to the address allowed to batch its ballots
+pub fn relayers<S: Storage>(storage: &mut S) -> Bucket<'_, S, HumanAddr> {
+    bucket(storage, RELAYERS_KEY)
+}
+
+pub fn relayers_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, HumanAddr> {
+    bucket_read(storage, RELAYERS_KEY)
+}
+
+/// proxies maps a voter to the proxy it authorized with `AuthorizeProxy`
+pub fn proxies<S: Storage>(storage: &mut S) -> Bucket<'_, S, ProxyGrant> {
+    bucket(storage, PROXIES_KEY)
+}
+
+pub fn proxies_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, ProxyGrant> {
+    bucket_read(storage, PROXIES_KEY)
+}
+
+/// cohort_members maps a voter to the name of its cohort
+pub fn cohort_members<S: Storage>(storage: &mut S) -> Bucket<'_, S, String> {
+    bucket(storage, COHORT_MEMBERS_KEY)
+}
+
+pub fn cohort_members_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, String> {
+    bucket_read(storage, COHORT_MEMBERS_KEY)
+}
+
+/// processed_keys records the idempotency keys `sender` has used, with the
+/// height they were processed at
+pub fn processed_keys<'a, S: Storage>(
+    storage: &'a mut S,
+    sender: &CanonicalAddr,
+) -> Bucket<'a, S, u64> {
+    Bucket::multilevel(storage, &[PROCESSED_KEYS_KEY, sender.as_slice()])
+}
+
+pub fn processed_keys_read<'a, S: Storage>(
+    storage: &'a S,
+    sender: &CanonicalAddr,
+) -> ReadonlyBucket<'a, S, u64> {
+    ReadonlyBucket::multilevel(storage, &[PROCESSED_KEYS_KEY, sender.as_slice()])
+}
+
+/// election templates of a factory keyed by name
+pub fn templates<S: Storage>(storage: &mut S) -> Bucket<'_, S, InitMsg> {
+    bucket(storage, TEMPLATES_KEY)
+}
+
+pub fn templates_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, InitMsg> {
+    bucket_read(storage, TEMPLATES_KEY)
+}
+
+pub fn spawned<S: Storage>(storage: &mut S) -> Singleton<'_, S, Vec<SpawnedElection>> {
+    singleton(storage, SPAWNED_KEY)
+}
+
+pub fn spawned_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, Vec<SpawnedElection>> {
+    singleton_read(storage, SPAWNED_KEY)
+}
+
+/// registered maps a voter to its registration
+pub fn registered<S: Storage>(storage: &mut S) -> Bucket<'_, S, Registration> {
+    bucket(storage, REGISTERED_KEY)
+}
+
+pub fn registered_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, Registration> {
+    bucket_read(storage, REGISTERED_KEY)
+}
+
+pub fn stats<S: Storage>(storage: &mut S) -> Singleton<'_, S, Stats> {
+    singleton(storage, STATS_KEY)
+}
+
+pub fn stats_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, Stats> {
+    singleton_read(storage, STATS_KEY)
+}
+
+/// disclosures holds the disclosures of `candidate` keyed by big endian id
+pub fn disclosures<'a, S: Storage>(
+    storage: &'a mut S,
+    candidate: &HumanAddr,
+) -> Bucket<'a, S, Disclosure> {
+    Bucket::multilevel(storage, &[DISCLOSURES_KEY, candidate.as_bytes()])
+}
+
+pub fn disclosures_read<'a, S: Storage>(
+    storage: &'a S,
+    candidate: &HumanAddr,
+) -> ReadonlyBucket<'a, S, Disclosure> {
+    ReadonlyBucket::multilevel(storage, &[DISCLOSURES_KEY, candidate.as_bytes()])
+}
+
+/// candidate_votes indexes the weight each ballot gives `candidate` by voter.
+/// Split ballots are indexed under every candidate they allocate weight to.
+pub fn candidate_votes<'a, S: Storage>(
+    storage: &'a mut S,
+    candidate: &CanonicalAddr,
+) -> Bucket<'a, S, u64> {
+    Bucket::multilevel(storage, &[CANDIDATE_VOTES_KEY, candidate.as_slice()])
+}
+
+pub fn candidate_votes_read<'a, S: Storage>(
+    storage: &'a S,
+    candidate: &CanonicalAddr,
+) -> ReadonlyBucket<'a, S, u64> {
+    ReadonlyBucket::multilevel(storage, &[CANDIDATE_VOTES_KEY, candidate.as_slice()])
+}
+
+/// ballot counts keyed by candidate, kept with the candidate_votes index
+pub fn candidate_counts<S: Storage>(storage: &mut S) -> Bucket<'_, S, CandidateCount> {
+    bucket(storage, CANDIDATE_COUNTS_KEY)
+}
+
+pub fn candidate_counts_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, CandidateCount> {
+    bucket_read(storage, CANDIDATE_COUNTS_KEY)
+}
+
+/// contributions to `candidate`'s campaign keyed by contributor
+pub fn contributions<'a, S: Storage>(
+    storage: &'a mut S,
+    candidate: &CanonicalAddr,
+) -> Bucket<'a, S, Vec<Coin>> {
+    Bucket::multilevel(storage, &[CONTRIBUTIONS_KEY, candidate.as_slice()])
+}
+
+pub fn contributions_read<'a, S: Storage>(
+    storage: &'a S,
+    candidate: &CanonicalAddr,
+) -> ReadonlyBucket<'a, S, Vec<Coin>> {
+    ReadonlyBucket::multilevel(storage, &[CONTRIBUTIONS_KEY, candidate.as_slice()])
+}
+
+/// escrowed campaign funds keyed by canonical candidate address
+pub fn campaign_totals<S: Storage>(storage: &mut S) -> Bucket<'_, S, Vec<Coin>> {
+    bucket(storage, CAMPAIGN_TOTALS_KEY)
+}
+
+pub fn campaign_totals_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, Vec<Coin>> {
+    bucket_read(storage, CAMPAIGN_TOTALS_KEY)
+}
+
+/// tokens locked for `StakeWeight::Locked` keyed by voter
+pub fn locks<S: Storage>(storage: &mut S) -> Bucket<'_, S, Uint128> {
+    bucket(storage, LOCKS_KEY)
+}
+
+pub fn locks_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, Uint128> {
+    bucket_read(storage, LOCKS_KEY)
+}
+
+/// pending candidate nominations keyed by candidate
+pub fn proposals<S: Storage>(storage: &mut S) -> Bucket<'_, S, CandidateProposal> {
+    bucket(storage, PROPOSALS_KEY)
+}
+
+pub fn proposals_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, CandidateProposal> {
+    bucket_read(storage, PROPOSALS_KEY)
+}
+
+/// challenges of the result keyed by big endian id
+pub fn challenges<S: Storage>(storage: &mut S) -> Bucket<'_, S, Challenge> {
+    bucket(storage, CHALLENGES_KEY)
+}
+
+pub fn challenges_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, Challenge> {
+    bucket_read(storage, CHALLENGES_KEY)
+}
+
+/// audit_log holds the privileged actions keyed by big endian id, entries
+/// are only ever appended
+pub fn audit_log<S: Storage>(storage: &mut S) -> Bucket<'_, S, AuditEntry> {
+    bucket(storage, AUDIT_LOG_KEY)
+}
+
+pub fn audit_log_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, AuditEntry> {
+    bucket_read(storage, AUDIT_LOG_KEY)
+}
+
+/// participants holds the height of every ballot keyed by voter. Unlike the
+/// ballots it is kept through `Archive`, as the history later elections check.
+pub fn participants<S: Storage>(storage: &mut S) -> Bucket<'_, S, u64> {
+    bucket(storage, PARTICIPANTS_KEY)
+}
+
+pub fn participants_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, u64> {
+    bucket_read(storage, PARTICIPANTS_KEY)
+}
+
+/// ballot fees paid and not yet refunded, keyed by payer
+pub fn ballot_fees<S: Storage>(storage: &mut S) -> Bucket<'_, S, Coin> {
+    bucket(storage, BALLOT_FEES_KEY)
+}
+
+pub fn ballot_fees_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, Coin> {
+    bucket_read(storage, BALLOT_FEES_KEY)
+}
+
+/// payments of the candidate ballots, keyed by voter, so that the fee of a
+/// discarded ballot can be taken from its payer
+pub fn ballot_payers<S: Storage>(storage: &mut S) -> Bucket<'_, S, BallotPayment> {
+    bucket(storage, BALLOT_PAYERS_KEY)
+}
+
+pub fn ballot_payers_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, BallotPayment> {
+    bucket_read(storage, BALLOT_PAYERS_KEY)
+}
+
+/// leaderboard holds the candidate counts again under `leaderboard_key`, so
+/// ranging over it yields candidates with the most ballots first
+pub fn leaderboard<S: Storage>(storage: &mut S) -> Bucket<'_, S, CandidateCount> {
+    bucket(storage, LEADERBOARD_KEY)
+}
+
+pub fn leaderboard_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, CandidateCount> {
+    bucket_read(storage, LEADERBOARD_KEY)
+}
+
+/// leaderboard_key is the inverted big endian `count` followed by `candidate`
+pub fn leaderboard_key(count: u64, candidate: &CanonicalAddr) -> Vec<u8> {
+    let mut key = (u64::MAX - count).to_be_bytes().to_vec();
+    key.extend_from_slice(candidate.as_slice());
+    key
+}
+
+/// archives holds archived results keyed by big endian election id
+pub fn archives<S: Storage>(storage: &mut S) -> Bucket<'_, S, ArchivedResult> {
+    bucket(storage, ARCHIVES_KEY)
+}
+
+pub fn archives_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, ArchivedResult> {
+    bucket_read(storage, ARCHIVES_KEY)
+}
+
+pub fn tally_proof<S: Storage>(storage: &mut S) -> Singleton<'_, S, TallyProof> {
+    singleton(storage, TALLY_PROOF_KEY)
+}
+
+pub fn tally_proof_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, TallyProof> {
+    singleton_read(storage, TALLY_PROOF_KEY)
+}
+
+/// height at which each voter claimed the participation reward
+pub fn reward_claims<S: Storage>(storage: &mut S) -> Bucket<'_, S, u64> {
+    bucket(storage, REWARD_CLAIMS_KEY)
+}
+
+pub fn reward_claims_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, u64> {
+    bucket_read(storage, REWARD_CLAIMS_KEY)
+}
+
+/// height of the last rate limited message of each sender
+pub fn last_action<S: Storage>(storage: &mut S) -> Bucket<'_, S, u64> {
+    bucket(storage, LAST_ACTION_KEY)
+}
+
+pub fn last_action_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, u64> {
+    bucket_read(storage, LAST_ACTION_KEY)
+}
+
+/// referendum ballots keyed by voter
+pub fn referendum_ballots<S: Storage>(storage: &mut S) -> Bucket<'_, S, ReferendumBallot> {
+    bucket(storage, REFERENDUM_BALLOTS_KEY)
+}
+
+pub fn referendum_ballots_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, ReferendumBallot> {
+    bucket_read(storage, REFERENDUM_BALLOTS_KEY)
+}
+
+pub fn referendum_tally<S: Storage>(storage: &mut S) -> Singleton<'_, S, ReferendumTally> {
+    singleton(storage, REFERENDUM_TALLY_KEY)
+}
+
+pub fn referendum_tally_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, ReferendumTally> {
+    singleton_read(storage, REFERENDUM_TALLY_KEY)
+}
+
+/// question_ballots holds the answers of every voter keyed by voter
+pub fn question_ballots<S: Storage>(storage: &mut S) -> Bucket<'_, S, Vec<Answer>> {
+    bucket(storage, QUESTION_BALLOTS_KEY)
+}
+
+pub fn question_ballots_read<S: Storage>(storage: &S) -> ReadonlyBucket<'_, S, Vec<Answer>> {
+    bucket_read(storage, QUESTION_BALLOTS_KEY)
+}
+
+pub fn question_tally<S: Storage>(storage: &mut S) -> Singleton<'_, S, Vec<QuestionTally>> {
+    singleton(storage, QUESTION_TALLY_KEY)
+}
+
+pub fn question_tally_read<S: Storage>(
+    storage: &S,
+) -> ReadonlySingleton<'_, S, Vec<QuestionTally>> {
+    singleton_read(storage, QUESTION_TALLY_KEY)
+}
+
+pub fn referendum_outcome<S: Storage>(storage: &mut S) -> Singleton<'_, S, ReferendumOutcome> {
+    singleton(storage, REFERENDUM_OUTCOME_KEY)
+}
+
+pub fn referendum_outcome_read<S: Storage>(
+    storage: &S,
+) -> ReadonlySingleton<'_, S, ReferendumOutcome> {
+    singleton_read(storage, REFERENDUM_OUTCOME_KEY)
+}
+
+pub fn roll_snapshot<S: Storage>(storage: &mut S) -> Singleton<'_, S, RollSnapshot> {
+    singleton(storage, ROLL_SNAPSHOT_KEY)
+}
+
+pub fn roll_snapshot_read<S: Storage>(storage: &S) -> ReadonlySingleton<'_, S, RollSnapshot> {
+    singleton_read(storage, ROLL_SNAPSHOT_KEY)
+}
@@ -0,0 +1,483 @@
+use cosmwasm_std::{Decimal, HumanAddr};
+
+use crate::msg::{CondorcetFallback, SeatAllocation};
+use crate::state::{RoundTally, TallyRound};
+
+/// Ballot values are tracked in fixed point so that surplus transfers keep
+/// their fractional part across rounds.
+const SCALE: u128 = 1_000_000_000;
+
+/// RankedBallot is a single ballot as seen by the STV count
+pub struct RankedBallot<'a> {
+    pub ranking: &'a [HumanAddr],
+    pub weight: u64,
+}
+
+/// StvOutcome holds the elected candidates in the order they were elected,
+/// together with the whole votes each held when elected, and the per-round record.
+pub struct StvOutcome {
+    pub elected: Vec<(HumanAddr, u128)>,
+    pub rounds: Vec<TallyRound>,
+}
+
+struct Pile {
+    ranking: Vec<usize>,
+    value: u128,
+    position: usize,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Status {
+    Hopeful,
+    Elected,
+    Eliminated,
+}
+
+/// droop_quota returns the smallest whole number of votes that only `seats`
+/// candidates can reach at the same time
+pub fn droop_quota(total: u128, seats: u32) -> u128 {
+    total / (u128::from(seats) + 1) + 1
+}
+
+/// stv counts ranked ballots with the Droop quota, transferring surpluses of
+/// elected candidates at a reduced value (Gregory method) and eliminating the
+/// weakest candidate whenever nobody reaches the quota.
+///
+/// Ties are resolved deterministically: among equal tallies the candidate with
+/// the lower address is elected first and the one with the higher address is
+/// eliminated first.
+pub fn stv(candidates: &[HumanAddr], ballots: &[RankedBallot], seats: u32) -> StvOutcome {
+    let mut status = vec![Status::Hopeful; candidates.len()];
+    let mut piles: Vec<Pile> = ballots
+        .iter()
+        .map(|ballot| Pile {
+            ranking: ballot
+                .ranking
+                .iter()
+                .filter_map(|c| candidates.iter().position(|known| known == c))
+                .collect(),
+            value: u128::from(ballot.weight) * SCALE,
+            position: 0,
+        })
+        .collect();
+    let total: u128 = ballots.iter().map(|b| u128::from(b.weight)).sum();
+    let quota = droop_quota(total, seats) * SCALE;
+
+    let mut elected: Vec<(HumanAddr, u128)> = vec![];
+    let mut rounds: Vec<TallyRound> = vec![];
+    while elected.len() < seats as usize {
+        let tallies = tally_piles(&mut piles, &status, candidates.len());
+        let mut hopeful: Vec<usize> = (0..candidates.len())
+            .filter(|&i| status[i] == Status::Hopeful)
+            .collect();
+        if hopeful.is_empty() {
+            break;
+        }
+        // strongest first, lower address first among equals
+        hopeful.sort_by(|&a, &b| {
+            tallies[b]
+                .cmp(&tallies[a])
+                .then(candidates[a].cmp(&candidates[b]))
+        });
+
+        let mut round = TallyRound {
+            round: rounds.len() as u32 + 1,
+            tallies: hopeful
+                .iter()
+                .map(|&i| RoundTally {
+                    candidate: candidates[i].clone(),
+                    votes: to_decimal(tallies[i]),
+                    label: None,
+                })
+                .collect(),
+            elected: vec![],
+            eliminated: None,
+            exhausted: to_decimal(exhausted_value(&piles, &status)),
+        };
+
+        let remaining = seats as usize - elected.len();
+        let reached: Vec<usize> = if hopeful.len() <= remaining {
+            // every remaining candidate fills a seat
+            hopeful.clone()
+        } else {
+            hopeful
+                .iter()
+                .copied()
+                .filter(|&i| tallies[i] >= quota)
+                .take(remaining)
+                .collect()
+        };
+
+        if reached.is_empty() {
+            let loser = *hopeful.last().unwrap();
+            status[loser] = Status::Eliminated;
+            round.eliminated = Some(candidates[loser].clone());
+        } else {
+            for &winner in &reached {
+                status[winner] = Status::Elected;
+                elected.push((candidates[winner].clone(), tallies[winner] / SCALE));
+                round.elected.push(candidates[winner].clone());
+            }
+            for &winner in &reached {
+                let tally = tallies[winner];
+                if tally > quota {
+                    let surplus = tally - quota;
+                    for pile in piles.iter_mut().filter(|p| current(p) == Some(winner)) {
+                        pile.value = mul_div(pile.value, surplus, tally);
+                    }
+                } else {
+                    for pile in piles.iter_mut().filter(|p| current(p) == Some(winner)) {
+                        pile.value = 0;
+                    }
+                }
+            }
+        }
+        rounds.push(round);
+    }
+
+    StvOutcome { elected, rounds }
+}
+
+/// to_decimal converts a fixed point value to votes. `Decimal::from_ratio`
+/// scales its nominator by 1e18, so the whole votes and the fraction are
+/// converted separately to keep large weights from overflowing.
+fn to_decimal(value: u128) -> Decimal {
+    Decimal::from_ratio(value / SCALE, 1u128) + Decimal::from_ratio(value % SCALE, SCALE)
+}
+
+/// mul_div returns `a * b / c` rounded down for `b <= c`, without overflowing
+/// when `a * b` does not fit in 128 bits
+fn mul_div(a: u128, b: u128, c: u128) -> u128 {
+    if let Some(product) = a.checked_mul(b) {
+        return product / c;
+    }
+    // long multiplication over the bits of `b`, keeping `quotient * c +
+    // remainder` equal to the product so far
+    let (whole, part) = (a / c, a % c);
+    let (mut quotient, mut remainder) = (0u128, 0u128);
+    for bit in (0..128).rev() {
+        quotient *= 2;
+        if remainder >= c - remainder {
+            quotient += 1;
+            remainder -= c - remainder;
+        } else {
+            remainder *= 2;
+        }
+        if b >> bit & 1 == 1 {
+            quotient += whole;
+            if remainder >= c - part {
+                quotient += 1;
+                remainder -= c - part;
+            } else {
+                remainder += part;
+            }
+        }
+    }
+    quotient
+}
+
+/// borda gives each ballot's candidate at position `i` of the ranking
+/// `n - 1 - i` points times the ballot weight, `n` being the number of
+/// candidates. Candidates are returned by score, the lower address first
+/// among equals.
+pub fn borda(candidates: &[HumanAddr], ballots: &[RankedBallot]) -> Vec<(HumanAddr, u128)> {
+    let n = candidates.len() as u128;
+    let mut scores = vec![0u128; candidates.len()];
+    for ballot in ballots {
+        for (i, candidate) in ballot.ranking.iter().enumerate() {
+            if let Some(index) = candidates.iter().position(|c| c == candidate) {
+                scores[index] += n.saturating_sub(1 + i as u128) * u128::from(ballot.weight);
+            }
+        }
+    }
+    let mut ranked: Vec<(HumanAddr, u128)> = candidates.iter().cloned().zip(scores).collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    ranked
+}
+
+/// condorcet orders the candidates by repeatedly taking the one that beats
+/// every other remaining candidate head to head, or the `fallback` choice if
+/// there is none. A ranked candidate beats an unranked one. Each candidate is
+/// returned with its number of head to head wins.
+pub fn condorcet(
+    candidates: &[HumanAddr],
+    ballots: &[RankedBallot],
+    fallback: CondorcetFallback,
+) -> Vec<(HumanAddr, u128)> {
+    let n = candidates.len();
+    // prefer[a][b] is the weight ranking a above b
+    let mut prefer = vec![vec![0u128; n]; n];
+    for ballot in ballots {
+        let position: Vec<usize> = candidates
+            .iter()
+            .map(|c| {
+                ballot
+                    .ranking
+                    .iter()
+                    .position(|r| r == c)
+                    .unwrap_or(usize::MAX)
+            })
+            .collect();
+        for a in 0..n {
+            for b in 0..n {
+                if position[a] < position[b] {
+                    prefer[a][b] += u128::from(ballot.weight);
+                }
+            }
+        }
+    }
+    let wins: Vec<u128> = (0..n)
+        .map(|a| (0..n).filter(|&b| prefer[a][b] > prefer[b][a]).count() as u128)
+        .collect();
+    let borda_order = borda(candidates, ballots);
+
+    let mut remaining: Vec<usize> = (0..n).collect();
+    // lower address first among equals
+    remaining.sort_by(|&a, &b| candidates[a].cmp(&candidates[b]));
+    let mut order = vec![];
+    while !remaining.is_empty() {
+        let beats_all = |a: usize| {
+            remaining
+                .iter()
+                .all(|&b| a == b || prefer[a][b] > prefer[b][a])
+        };
+        let pick = match remaining.iter().position(|&a| beats_all(a)) {
+            Some(pick) => pick,
+            None => match fallback {
+                CondorcetFallback::Borda => borda_order
+                    .iter()
+                    .find_map(|(c, _)| remaining.iter().position(|&a| &candidates[a] == c))
+                    .unwrap_or(0),
+                CondorcetFallback::Minimax => {
+                    let worst_defeat = |a: usize| {
+                        remaining
+                            .iter()
+                            .map(|&b| prefer[b][a].saturating_sub(prefer[a][b]))
+                            .max()
+                            .unwrap_or(0)
+                    };
+                    (0..remaining.len())
+                        .min_by_key(|&i| worst_defeat(remaining[i]))
+                        .unwrap_or(0)
+                }
+            },
+        };
+        order.push(remaining.remove(pick));
+    }
+    order
+        .into_iter()
+        .map(|a| (candidates[a].clone(), wins[a]))
+        .collect()
+}
+
+/// highest_averages hands out `seats` one at a time to the slate with the
+/// highest `total / divisor`, the divisor growing with the seats a slate
+/// already holds. A slate gets at most `capacity` seats and the earlier slate
+/// wins a tie. Returns the slate index of each seat in the order awarded.
+pub fn highest_averages(
+    totals: &[u128],
+    capacity: &[usize],
+    seats: u32,
+    method: SeatAllocation,
+) -> Vec<usize> {
+    let divisor = |held: usize| match method {
+        SeatAllocation::DHondt => held as u128 + 1,
+        SeatAllocation::SainteLague => 2 * held as u128 + 1,
+    };
+    let mut held = vec![0usize; totals.len()];
+    let mut order = vec![];
+    for _ in 0..seats {
+        let mut best: Option<usize> = None;
+        for slate in (0..totals.len()).filter(|&s| held[s] < capacity[s]) {
+            // a / b > c / d  <=>  a * d > c * b
+            let better = match best {
+                None => true,
+                Some(b) => totals[slate] * divisor(held[b]) > totals[b] * divisor(held[slate]),
+            };
+            if better {
+                best = Some(slate);
+            }
+        }
+        match best {
+            Some(slate) => {
+                held[slate] += 1;
+                order.push(slate);
+            }
+            None => break,
+        }
+    }
+    order
+}
+
+fn current(pile: &Pile) -> Option<usize> {
+    pile.ranking.get(pile.position).copied()
+}
+
+/// tally_piles moves every ballot to its highest ranked hopeful candidate
+/// (ballots resting on an elected candidate carry their transferred value on)
+/// and sums the value held by each candidate.
+fn tally_piles(piles: &mut [Pile], status: &[Status], count: usize) -> Vec<u128> {
+    let mut tallies = vec![0u128; count];
+    for pile in piles.iter_mut() {
+        while let Some(candidate) = current(pile) {
+            if status[candidate] == Status::Hopeful {
+                tallies[candidate] += pile.value;
+                break;
+            }
+            pile.position += 1;
+        }
+    }
+    tallies
+}
+
+fn exhausted_value(piles: &[Pile], status: &[Status]) -> u128 {
+    piles
+        .iter()
+        .filter(|pile| {
+            pile.ranking[pile.position.min(pile.ranking.len())..]
+                .iter()
+                .all(|&c| status[c] != Status::Hopeful)
+        })
+        .map(|pile| pile.value)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addrs(names: &[&str]) -> Vec<HumanAddr> {
+        names.iter().map(|n| HumanAddr::from(*n)).collect()
+    }
+
+    #[test]
+    fn droop_quota_works() {
+        assert_eq!(34, droop_quota(100, 2));
+        assert_eq!(51, droop_quota(100, 1));
+        assert_eq!(1, droop_quota(0, 3));
+    }
+
+    #[test]
+    fn surplus_transfer_elects_second_choice() {
+        let candidates = addrs(&["alice", "bob", "carol"]);
+        let alice_bob = addrs(&["alice", "bob"]);
+        let carol = addrs(&["carol"]);
+        let bob = addrs(&["bob"]);
+        let mut ballots = vec![];
+        // 7 x alice > bob, 3 x carol, 1 x bob: quota is 4 with 2 seats
+        for _ in 0..7 {
+            ballots.push(RankedBallot {
+                ranking: &alice_bob,
+                weight: 1,
+            });
+        }
+        for _ in 0..3 {
+            ballots.push(RankedBallot {
+                ranking: &carol,
+                weight: 1,
+            });
+        }
+        ballots.push(RankedBallot {
+            ranking: &bob,
+            weight: 1,
+        });
+
+        let outcome = stv(&candidates, &ballots, 2);
+        let elected: Vec<HumanAddr> = outcome.elected.iter().map(|(c, _)| c.clone()).collect();
+        // alice's surplus of 3 lifts bob just short of the quota, above carol
+        assert_eq!(addrs(&["alice", "bob"]), elected);
+        assert_eq!(3, outcome.rounds.len());
+        assert_eq!(addrs(&["alice"]), outcome.rounds[0].elected);
+        assert_eq!(
+            HumanAddr::from("bob"),
+            outcome.rounds[1].tallies[0].candidate
+        );
+        assert_eq!(Some("carol".into()), outcome.rounds[1].eliminated);
+        assert_eq!(addrs(&["bob"]), outcome.rounds[2].elected);
+    }
+
+    #[test]
+    fn borda_and_condorcet_rankings() {
+        let candidates = addrs(&["alice", "bob", "carol"]);
+        let abc = addrs(&["alice", "bob", "carol"]);
+        let bca = addrs(&["bob", "carol", "alice"]);
+        let cab = addrs(&["carol", "alice", "bob"]);
+        let ballot = |ranking, weight| RankedBallot { ranking, weight };
+
+        // bob is everyone's first or second choice
+        let ballots = vec![ballot(&abc, 3), ballot(&bca, 2), ballot(&cab, 1)];
+        let scores = borda(&candidates, &ballots);
+        assert_eq!(("alice".into(), 7), scores[0]);
+        assert_eq!(("bob".into(), 7), scores[1]);
+        let order = condorcet(&candidates, &ballots, CondorcetFallback::Borda);
+        // alice beats bob 4 to 2 and carol 3 to 3 is a draw, so there is no winner
+        // of all; the Borda fallback picks alice first
+        assert_eq!(HumanAddr::from("alice"), order[0].0);
+
+        // a rock-paper-scissors cycle of equal strength
+        let ballots = vec![ballot(&abc, 1), ballot(&bca, 1), ballot(&cab, 1)];
+        let order = condorcet(&candidates, &ballots, CondorcetFallback::Minimax);
+        assert_eq!(
+            addrs(&["alice", "bob", "carol"]),
+            order.iter().map(|(c, _)| c.clone()).collect::<Vec<_>>()
+        );
+        assert_eq!(1, order[0].1);
+
+        // a clear Condorcet winner
+        let ballots = vec![ballot(&bca, 2), ballot(&abc, 1)];
+        let order = condorcet(&candidates, &ballots, CondorcetFallback::Minimax);
+        assert_eq!(HumanAddr::from("bob"), order[0].0);
+        assert_eq!(2, order[0].1);
+    }
+
+    #[test]
+    fn highest_averages_methods_differ() {
+        let totals = [100_000, 80_000, 30_000, 20_000];
+        let capacity = [8, 8, 8, 8];
+        let seats = |method| {
+            let mut held = [0; 4];
+            for slate in highest_averages(&totals, &capacity, 8, method) {
+                held[slate] += 1;
+            }
+            held
+        };
+        assert_eq!([4, 3, 1, 0], seats(SeatAllocation::DHondt));
+        assert_eq!([3, 3, 1, 1], seats(SeatAllocation::SainteLague));
+        // a slate without further candidates passes its seats on
+        let order = highest_averages(&totals, &[1, 8, 8, 8], 3, SeatAllocation::DHondt);
+        assert_eq!(vec![0, 1, 1], order);
+    }
+
+    #[test]
+    fn large_weights_do_not_overflow() {
+        let candidates = addrs(&["alice", "bob", "carol"]);
+        let alice_bob = addrs(&["alice", "bob"]);
+        let carol = addrs(&["carol"]);
+        let ballots = vec![
+            RankedBallot {
+                ranking: &alice_bob,
+                weight: 10_000_000_000_000,
+            },
+            RankedBallot {
+                ranking: &alice_bob,
+                weight: 10_000_000_000_000,
+            },
+            RankedBallot {
+                ranking: &carol,
+                weight: 10_000_000_000_000,
+            },
+        ];
+        let outcome = stv(&candidates, &ballots, 2);
+        let elected: Vec<HumanAddr> = outcome.elected.iter().map(|e| e.0.clone()).collect();
+        assert_eq!(addrs(&["alice", "carol"]), elected);
+        assert_eq!(
+            Decimal::from_ratio(20_000_000_000_000u128, 1u128),
+            outcome.rounds[0].tallies[0].votes
+        );
+
+        assert_eq!(6, mul_div(4, 3, 2));
+        assert_eq!(u128::MAX / 3, mul_div(u128::MAX, 1, 3));
+        assert_eq!(u128::MAX / 7, mul_div(u128::MAX / 7, 3, 3));
+        assert_eq!(1 << 99, mul_div(1 << 100, 1 << 100, 1 << 101));
+    }
+}
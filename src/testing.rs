@@ -0,0 +1,483 @@
+//! Fixtures for crates that test against this contract, enabled with the
+//! `test-support` feature.
+//!
+//! The fixture functions return an `InitMsg` for each voting mode and
+//! `Scenario` drives an election on the mock dependencies:
+//!
+//! ```ignore
+//! let winners = Scenario::new(plurality(&["alice", "bob"]))
+//!     .open()
+//!     .vote("voter1", "alice")
+//!     .vote("voter2", "alice")
+//!     .finalize()
+//!     .winners();
+//! ```
+//!
+//! `ElectionHarness` builds the `InitMsg` and drives the election in one
+//! chain, moving the block time along with the height:
+//!
+//! ```ignore
+//! let winners = ElectionHarness::new()
+//!     .with_candidates(&["alice", "bob"])
+//!     .with_period(15_000, 15_100)
+//!     .advance_to(15_000)
+//!     .vote("voter1", "alice")
+//!     .expect_phase(Phase::Voting)
+//!     .finalize()
+//!     .winners();
+//! ```
+//!
+//! There is no multi-contract test framework for this version of cosmwasm, so
+//! calls to other contracts (hooks, registries, staking contracts) are not
+//! executed. This contract has no commit-reveal mode, so there is no fixture
+//! for it.
+
+use cosmwasm_std::testing::{
+    mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage,
+};
+use cosmwasm_std::{
+    coin, from_binary, Env, Extern, FullDelegation, HandleResponse, HumanAddr, Uint128,
+};
+
+use crate::contract::{handle, init, query};
+use crate::error::ContractError;
+use crate::msg::{
+    HandleMsg, InitMsg, Limits, OracleFallback, Phase, PhaseResponse, QueryMsg, ResultResponse,
+    StakeWeight, TiePolicy, VoteResponse, VotingSystem, WithdrawalPolicy,
+};
+
+/// sender of `InitMsg` and of the admin messages of a `Scenario`
+pub const OWNER: &str = "owner";
+/// denom delegated by `Scenario::stake`
+pub const STAKE_DENOM: &str = "ustake";
+/// seconds an `ElectionHarness` moves the block time per block
+pub const BLOCK_SECONDS: u64 = 5;
+
+/// plurality is a one seat, one vote per ballot election open from height 10 to 100
+pub fn plurality(candidates: &[&str]) -> InitMsg {
+    InitMsg {
+        start: 10,
+        end: 100,
+        seats: 1,
+        voting_system: VotingSystem::Plurality,
+        tie_policy: TiePolicy::TieBreak,
+        withdrawal_policy: WithdrawalPolicy::Discard,
+        stake_weight: None,
+        oracle_fallback: OracleFallback::Pause,
+        nft_gate: None,
+        cohorts: vec![],
+        guardians: vec![],
+        roles: vec![],
+        dispute: None,
+        veto_council: None,
+        completion_hook: None,
+        referendum: None,
+        succession: None,
+        finalization_bounty: None,
+        participation_reward: None,
+        receipts: None,
+        limits: Limits::default(),
+        write_ins: None,
+        allow_contract_votes: false,
+        registry: None,
+        majority_runoff: None,
+        merkle_roll: None,
+        weight_decay: None,
+        eligibility: None,
+        registration: None,
+        election_code_id: None,
+        factory: None,
+        nomination: None,
+        candidate_rules: None,
+        denom: None,
+        min_votes: None,
+        party_list: None,
+        metadata: None,
+        ballot_fee: None,
+        anti_snipe: None,
+        history: None,
+        questions: vec![],
+        pseudonymous: false,
+        candidates: candidates.iter().map(|c| HumanAddr::from(*c)).collect(),
+        labels: vec![],
+    }
+}
+
+/// weighted weighs ballots by the voter's native delegations, see `Scenario::stake`
+pub fn weighted(candidates: &[&str]) -> InitMsg {
+    InitMsg {
+        stake_weight: Some(StakeWeight::Native {}),
+        ..plurality(candidates)
+    }
+}
+
+/// ranked is a single transferable vote election
+pub fn ranked(candidates: &[&str], seats: u32) -> InitMsg {
+    InitMsg {
+        voting_system: VotingSystem::Stv,
+        seats,
+        ..plurality(candidates)
+    }
+}
+
+/// multi_seat elects the `seats` candidates with the most votes
+pub fn multi_seat(candidates: &[&str], seats: u32) -> InitMsg {
+    InitMsg {
+        seats,
+        ..plurality(candidates)
+    }
+}
+
+/// Scenario is an instantiated election together with the block it is at.
+/// The step methods panic when the contract returns an error, use
+/// `try_handle` to check for errors.
+pub struct Scenario {
+    pub deps: Extern<MockStorage, MockApi, MockQuerier>,
+    pub env: Env,
+    start: u64,
+    end: u64,
+    delegations: Vec<FullDelegation>,
+}
+
+impl Scenario {
+    /// new instantiates the election, sent by `OWNER`
+    pub fn new(msg: InitMsg) -> Self {
+        let mut deps = mock_dependencies(&[]);
+        let (start, end) = (msg.start, msg.end);
+        init(&mut deps, mock_env(), mock_info(OWNER, &[]), msg).unwrap();
+        Scenario {
+            deps,
+            env: mock_env(),
+            start,
+            end,
+            delegations: vec![],
+        }
+    }
+
+    /// stake delegates `amount` of `STAKE_DENOM` from `voter`
+    pub fn stake(&mut self, voter: &str, amount: u128) -> &mut Self {
+        self.delegations.push(FullDelegation {
+            delegator: voter.into(),
+            validator: "validator".into(),
+            amount: coin(amount, STAKE_DENOM),
+            can_redelegate: coin(0, STAKE_DENOM),
+            accumulated_rewards: vec![],
+        });
+        self.deps
+            .querier
+            .update_staking(STAKE_DENOM, &[], &self.delegations);
+        self
+    }
+
+    /// at moves to block `height`
+    pub fn at(&mut self, height: u64) -> &mut Self {
+        self.env.block.height = height;
+        self
+    }
+
+    /// open moves to the first block of voting
+    pub fn open(&mut self) -> &mut Self {
+        let start = self.start;
+        self.at(start)
+    }
+
+    pub fn try_handle(
+        &mut self,
+        sender: &str,
+        msg: HandleMsg,
+    ) -> Result<HandleResponse, ContractError> {
+        handle(
+            &mut self.deps,
+            self.env.clone(),
+            mock_info(sender, &[]),
+            msg,
+        )
+    }
+
+    pub fn vote(&mut self, voter: &str, candidate: &str) -> &mut Self {
+        let msg = HandleMsg::Vote {
+            candidate: candidate.into(),
+            proof: None,
+            answers: None,
+        };
+        self.try_handle(voter, msg).unwrap();
+        self
+    }
+
+    pub fn rank(&mut self, voter: &str, ranking: &[&str]) -> &mut Self {
+        let msg = HandleMsg::RankedVote {
+            ranking: ranking.iter().map(|c| HumanAddr::from(*c)).collect(),
+        };
+        self.try_handle(voter, msg).unwrap();
+        self
+    }
+
+    /// finalize moves past the end of voting and finalizes
+    pub fn finalize(&mut self) -> &mut Self {
+        let end = self.end;
+        self.at(end + 1);
+        self.try_handle(OWNER, HandleMsg::Finalize { limit: None })
+            .unwrap();
+        self
+    }
+
+    pub fn votes(&self) -> VoteResponse {
+        let msg = QueryMsg::GetVoteInfo {
+            locale: None,
+            sort: Default::default(),
+        };
+        from_binary(&query(&self.deps, self.env.clone(), msg).unwrap()).unwrap()
+    }
+
+    /// weight returns the weight counted for `candidate` so far
+    pub fn weight(&self, candidate: &str) -> Uint128 {
+        self.votes()
+            .votes
+            .into_iter()
+            .find(|vote| vote.candidate.as_str() == candidate)
+            .map(|vote| vote.weight)
+            .unwrap_or_else(Uint128::zero)
+    }
+
+    pub fn result(&self) -> ResultResponse {
+        let msg = QueryMsg::GetResult { locale: None };
+        from_binary(&query(&self.deps, self.env.clone(), msg).unwrap()).unwrap()
+    }
+
+    /// winners returns the finalized winners in rank order
+    pub fn winners(&self) -> Vec<HumanAddr> {
+        self.result()
+            .winners
+            .into_iter()
+            .map(|winner| winner.candidate)
+            .collect()
+    }
+}
+
+/// ElectionHarness is a fluent builder over `Scenario`. The `with_*` methods
+/// configure the election and the first step instantiates it, after which
+/// configuring panics. Like `Scenario`, the steps panic on contract errors.
+pub struct ElectionHarness {
+    msg: InitMsg,
+    scenario: Option<Scenario>,
+}
+
+impl Default for ElectionHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ElectionHarness {
+    /// new starts from `plurality` without candidates
+    pub fn new() -> Self {
+        Self::from_msg(plurality(&[]))
+    }
+
+    pub fn from_msg(msg: InitMsg) -> Self {
+        ElectionHarness {
+            msg,
+            scenario: None,
+        }
+    }
+
+    /// configure changes any field of the `InitMsg`
+    pub fn configure(mut self, change: impl FnOnce(&mut InitMsg)) -> Self {
+        assert!(
+            self.scenario.is_none(),
+            "the election is already instantiated"
+        );
+        change(&mut self.msg);
+        self
+    }
+
+    pub fn with_candidates(self, candidates: &[&str]) -> Self {
+        self.configure(|msg| {
+            msg.candidates = candidates.iter().map(|c| HumanAddr::from(*c)).collect()
+        })
+    }
+
+    /// with_period sets the first and the last height of voting
+    pub fn with_period(self, start: u64, end: u64) -> Self {
+        self.configure(|msg| {
+            msg.start = start;
+            msg.end = end;
+        })
+    }
+
+    pub fn with_seats(self, seats: u32) -> Self {
+        self.configure(|msg| msg.seats = seats)
+    }
+
+    pub fn with_voting_system(self, voting_system: VotingSystem) -> Self {
+        self.configure(|msg| msg.voting_system = voting_system)
+    }
+
+    /// scenario instantiates the election if needed
+    pub fn scenario(&mut self) -> &mut Scenario {
+        let msg = &self.msg;
+        self.scenario
+            .get_or_insert_with(|| Scenario::new(msg.clone()))
+    }
+
+    /// advance_to moves to block `height`, the block time following by
+    /// `BLOCK_SECONDS` per block
+    pub fn advance_to(mut self, height: u64) -> Self {
+        let scenario = self.scenario();
+        let env = &mut scenario.env;
+        let base = mock_env().block;
+        env.block.time = if height >= base.height {
+            base.time + (height - base.height) * BLOCK_SECONDS
+        } else {
+            base.time
+                .saturating_sub((base.height - height) * BLOCK_SECONDS)
+        };
+        scenario.at(height);
+        self
+    }
+
+    pub fn advance_by(mut self, blocks: u64) -> Self {
+        let height = self.scenario().env.block.height + blocks;
+        self.advance_to(height)
+    }
+
+    /// open moves to the first block of voting
+    pub fn open(mut self) -> Self {
+        let start = self.scenario().start;
+        self.advance_to(start)
+    }
+
+    /// close moves to the first block after voting, taking deadline
+    /// extensions into account
+    pub fn close(mut self) -> Self {
+        match self.phase() {
+            PhaseResponse {
+                phase: Phase::Voting,
+                next_at: Some(end),
+            } => self.advance_to(end),
+            PhaseResponse {
+                phase: Phase::Pending,
+                ..
+            }
+            | PhaseResponse {
+                phase: Phase::Registration,
+                ..
+            } => self.open().close(),
+            _ => self,
+        }
+    }
+
+    /// execute handles `msg` sent by `sender` at the current block
+    pub fn execute(mut self, sender: &str, msg: HandleMsg) -> Self {
+        if let Err(err) = self.scenario().try_handle(sender, msg) {
+            panic!("{} failed: {}", sender, err);
+        }
+        self
+    }
+
+    pub fn vote(mut self, voter: &str, candidate: &str) -> Self {
+        self.scenario().vote(voter, candidate);
+        self
+    }
+
+    /// vote_all casts a vote for each `(voter, candidate)` pair in one block
+    pub fn vote_all(mut self, ballots: &[(&str, &str)]) -> Self {
+        for (voter, candidate) in ballots {
+            self.scenario().vote(voter, candidate);
+        }
+        self
+    }
+
+    pub fn rank(mut self, voter: &str, ranking: &[&str]) -> Self {
+        self.scenario().rank(voter, ranking);
+        self
+    }
+
+    /// finalize closes voting and finalizes
+    pub fn finalize(self) -> Self {
+        self.close()
+            .execute(OWNER, HandleMsg::Finalize { limit: None })
+    }
+
+    pub fn phase(&mut self) -> PhaseResponse {
+        let scenario = self.scenario();
+        let msg = QueryMsg::GetPhase {};
+        from_binary(&query(&scenario.deps, scenario.env.clone(), msg).unwrap()).unwrap()
+    }
+
+    /// expect_phase asserts the phase at the current block
+    pub fn expect_phase(mut self, phase: Phase) -> Self {
+        assert_eq!(phase, self.phase().phase);
+        self
+    }
+
+    pub fn result(&mut self) -> ResultResponse {
+        self.scenario().result()
+    }
+
+    pub fn winners(&mut self) -> Vec<HumanAddr> {
+        self.scenario().winners()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addrs(names: &[&str]) -> Vec<HumanAddr> {
+        names.iter().map(|n| HumanAddr::from(*n)).collect()
+    }
+
+    #[test]
+    fn fixtures_drive_each_mode() {
+        let winners = Scenario::new(multi_seat(&["alice", "bob", "carol"], 2))
+            .open()
+            .vote("voter1", "alice")
+            .vote("voter2", "carol")
+            .vote("voter3", "alice")
+            .finalize()
+            .winners();
+        assert_eq!(addrs(&["alice", "carol"]), winners);
+
+        let mut scenario = Scenario::new(weighted(&["alice", "bob"]));
+        scenario
+            .stake("whale", 500)
+            .stake("minnow", 20)
+            .open()
+            .vote("whale", "bob")
+            .vote("minnow", "alice");
+        assert_eq!(Uint128(500), scenario.weight("bob"));
+        assert_eq!(addrs(&["bob"]), scenario.finalize().winners());
+
+        let winners = Scenario::new(ranked(&["alice", "bob"], 1))
+            .open()
+            .rank("voter1", &["bob", "alice"])
+            .finalize()
+            .winners();
+        assert_eq!(addrs(&["bob"]), winners);
+    }
+
+    #[test]
+    fn harness_walks_the_phases() {
+        let mut harness = ElectionHarness::new()
+            .with_candidates(&["alice", "bob"])
+            .with_period(15_000, 15_100)
+            .advance_to(14_000)
+            .expect_phase(Phase::Pending)
+            .advance_to(15_000)
+            .expect_phase(Phase::Voting)
+            .vote_all(&[("voter1", "bob"), ("voter2", "alice")])
+            .advance_by(50)
+            .vote("voter3", "bob")
+            .close()
+            .expect_phase(Phase::Tallying);
+        let env = &harness.scenario().env;
+        assert_eq!(15_101, env.block.height);
+        assert_eq!(
+            mock_env().block.time + (15_101 - 12_345) * BLOCK_SECONDS,
+            env.block.time
+        );
+        let winners = harness.finalize().expect_phase(Phase::Finalized).winners();
+        assert_eq!(addrs(&["bob"]), winners);
+    }
+}